@@ -14,13 +14,24 @@ export interface Project {
   scan_summary: string | null
   created_at: string
   updated_at: string
+  // 启用的采集器 id 列表（JSON 字符串），为空表示使用默认采集器
+  collectors?: string | null
+  // 时间窗口模式，为空按 rolling；frozen 时附带固定的起止时间
+  window_mode?: WindowMode | null
+  window_start?: string | null
+  window_end?: string | null
 }
 
+// rolling：按当前时间滚动；frozen：固定为创建时的范围；retain：滚动并保留移出范围的条目（标记为归档）
+export type WindowMode = 'rolling' | 'frozen' | 'retain'
+
 export interface ProjectInput {
   name: string
   time_range: string
   scan_scope?: 'ALL' | 'CUSTOM'
   scan_folders?: string[]
+  collectors?: string[]
+  window_mode?: WindowMode
 }
 
 export interface ProjectListResponse {
@@ -42,6 +53,12 @@ export interface ResultItem {
   is_valid: boolean
   inserted_at: string
   updated_at: string
+  archived: boolean
+  // 人工整理：备注、星标等级（0 为未标记）、不写入报告、标签
+  note: string
+  star: number
+  excluded: boolean
+  tags: string[]
 }
 
 export interface Paged<T> {
@@ -49,12 +66,111 @@ export interface Paged<T> {
   total: number
   page: number
   total_pages: number
+  // 游标分页时下一页的游标，最后一页或按页码分页时不返回
+  next_cursor?: string
 }
 
 export interface ScanSummary {
   git_repos: number
   documents: number
   chat_locations: { app: string; path: string }[]
+  // 各采集器本次采集到的条目数（key 为采集器 id）
+  collected?: Record<string, number>
+  // frozen 模式下采集后因晚于窗口终点而移除的条目数（key 为采集器 id）
+  trimmed?: Record<string, number>
+  started_at?: string
+  finished_at?: string
+  duration_ms?: number
+  // 文档结果按来源 / 文件类型统计
+  by_source?: Record<string, number>
+  by_type?: Record<string, number>
+  // 跳过（无法访问）与失败（解析、写库出错）的条目
+  issues?: ScanIssues
+}
+
+export interface ScanIssues {
+  skipped: number
+  failed: number
+  // key 如 permission_denied、broken_symlink、symlink_loop、db_error
+  by_reason: Record<string, number>
+  samples: { stage: string; reason: string; path: string | null; message: string }[]
+}
+
+export interface ScanRunRecord {
+  id: string
+  project_id: string
+  started_at: string
+  finished_at: string
+  duration_ms: number
+  skipped: number
+  failed: number
+  summary: ScanSummary
+}
+
+// 实时监听状态
+export interface WatchStatus {
+  project_id: string
+  roots: string[]
+}
+
+// 监听到文件变更并同步 scan_results 后推送的 results-changed 事件
+export interface ResultsChanged {
+  project_id: string
+  upserted: string[]
+  removed: string[]
+  // 识别为改名或移动的文件，条目 id 与标签、备注等随之迁移
+  moved: { id: string; from: string; to: string }[]
+}
+
+// 定时重扫计划，cron 为五段式表达式（分 时 日 月 周）或 @daily 等简写
+export interface Schedule {
+  id: string
+  project_id: string
+  cron: string
+  enabled: boolean
+  next_run_at: string | null
+  last_run_at: string | null
+  last_status: string | null
+  last_message: string | null
+  created_at: string
+  updated_at: string
+}
+
+// 计划的一次执行结果，status 为 ok / partial / failed；执行后也会以 schedule-run 事件推送
+export interface ScheduleRun {
+  id: string
+  schedule_id: string
+  project_id: string
+  started_at: string
+  finished_at: string
+  status: string
+  message: string
+  catch_up: boolean
+}
+
+// 扫描过程中的类型化事件（scan-event），stage 为采集器 id
+export type ScanEvent =
+  | { kind: 'scan_started'; project_id: string; project_name: string; time_range: string; project_folder: string }
+  | { kind: 'stage_started'; stage: string; label: string }
+  | { kind: 'file_found'; stage: string; path: string; items: number | null }
+  | { kind: 'warning'; stage: string; path: string | null; reason: string | null; message: string }
+  | { kind: 'pruned'; stage: string; rule: string; label: string; path: string | null; count: number }
+  | { kind: 'stage_finished'; stage: string; label: string; count: number }
+  | { kind: 'progress'; progress: number; done: number; total: number; per_second: number; eta_secs: number | null }
+
+// 项目级目录遍历选项
+export interface WalkOptions {
+  follow_symlinks: boolean
+  same_file_system: boolean
+  max_depth: number | null
+  max_files_per_dir: number | null
+  skip_hidden: boolean
+}
+
+export interface CollectorInfo {
+  id: string
+  label: string
+  enabled_by_default: boolean
 }
 
 // ==================== 项目管理 API ====================
@@ -160,15 +276,175 @@ export async function startScan(projectName: string, timeRange: string, onLog: (
   }
 }
 
+// ====== 采集器配置 ======
+export async function listCollectors(): Promise<CollectorInfo[]> {
+  return await invoke('list_collectors')
+}
+
+export async function setProjectCollectors(project_id: string, collectors: string[]): Promise<void> {
+  return await invoke('set_project_collectors', { project_id, collectors })
+}
+
+export async function setProjectWindowMode(project_id: string, window_mode: WindowMode): Promise<void> {
+  return await invoke('set_project_window_mode', { project_id, window_mode })
+}
+
+// 采集器的项目级配置，如 browser_history: { include_domains, exclude_domains }
+export async function getCollectorSettings<T = Record<string, unknown>>(project_id: string, collector_id: string): Promise<T | null> {
+  return await invoke('get_collector_settings', { project_id, collector_id })
+}
+
+export async function setCollectorSettings(project_id: string, collector_id: string, settings: Record<string, unknown>): Promise<void> {
+  return await invoke('set_collector_settings', { project_id, collector_id, settings })
+}
+
+export async function getWalkOptions(project_id: string): Promise<WalkOptions> {
+  return await invoke('get_walk_options', { project_id })
+}
+
+export async function setWalkOptions(project_id: string, options: WalkOptions): Promise<void> {
+  return await invoke('set_walk_options', { project_id, options })
+}
+
+// 导入用户选择的 .ics 文件，返回写入的会议条数；文件会记入 calendar 采集器配置，重扫时保留
+export async function importCalendarFiles(project_id: string, paths: string[]): Promise<number> {
+  return await invoke('import_calendar_files', { project_id, paths })
+}
+
+export interface ShellHistoryGroup {
+  git_repo: string | null
+  cwd: string | null
+  commands: number
+  first_at: string
+  last_at: string
+}
+
+// 终端命令按工作目录与 Git 仓库分组统计
+export async function getShellHistoryGroups(project_id: string): Promise<ShellHistoryGroup[]> {
+  return await invoke('get_shell_history_groups', { project_id })
+}
+
+export interface EmailItem {
+  id: string
+  message_id: string
+  sender: string
+  recipients: string[]
+  subject: string
+  sent_at: string
+  snippet: string
+  attachments: string[]
+  source_path: string
+}
+
+export async function searchEmails(params: { project_id: string; page: number; page_size: number; q?: string }): Promise<Paged<EmailItem>> {
+  const { project_id, page, page_size, q } = params
+  return await invoke('search_emails', { project_id, q: q ?? null, page, page_size })
+}
+
 export async function getSummary(): Promise<ScanSummary | null> {
   return await invoke('get_scan_summary')
 }
 
-// 获取扫描结果（分页）
-export async function getResultsPaginated(project_id: string, page: number, page_size: number): Promise<Paged<ResultItem>> {
+// 项目的扫描运行记录，最近的在前
+export async function getScanRuns(projectId: string, limit?: number): Promise<ScanRunRecord[]> {
+  return await invoke('get_scan_runs', { project_id: projectId, limit: limit ?? null })
+}
+
+// 监听项目的扫描目录，变更实时同步到扫描结果；同一时间只监听一个项目
+export async function startWatch(project_id: string): Promise<WatchStatus> {
+  return await invoke('start_watch', { project_id })
+}
+
+export async function stopWatch(): Promise<boolean> {
+  return await invoke('stop_watch')
+}
+
+export async function getWatchStatus(): Promise<WatchStatus | null> {
+  return await invoke('get_watch_status')
+}
+
+export async function listSchedules(project_id?: string): Promise<Schedule[]> {
+  return await invoke('list_schedules', { project_id: project_id ?? null })
+}
+
+export async function createSchedule(project_id: string, cron: string): Promise<Schedule> {
+  return await invoke('create_schedule', { project_id, cron })
+}
+
+export async function updateSchedule(id: string, changes: { cron?: string; enabled?: boolean }): Promise<Schedule> {
+  return await invoke('update_schedule', { id, cron: changes.cron ?? null, enabled: changes.enabled ?? null })
+}
+
+export async function deleteSchedule(id: string): Promise<void> {
+  return await invoke('delete_schedule', { id })
+}
+
+export async function getScheduleRuns(schedule_id: string, limit?: number): Promise<ScheduleRun[]> {
+  return await invoke('get_schedule_runs', { schedule_id, limit: limit ?? null })
+}
+
+export async function onResultsChanged(handler: (changed: ResultsChanged) => void): Promise<() => void> {
+  return await listen<ResultsChanged>('results-changed', (e) => handler(e.payload))
+}
+
+// 时间线聚合
+export type TimelineGranularity = 'day' | 'week' | 'month'
+
+export interface TimelineItem {
+  source: string
+  title: string
+  detail: string
+  time: string
+}
+
+export interface TimelineBucket {
+  start: string
+  end: string
+  label: string
+  total: number
+  counts: Record<string, number>
+  top_items: TimelineItem[]
+}
+
+export interface Timeline {
+  granularity: TimelineGranularity
+  sources: { id: string; label: string; total: number }[]
+  buckets: TimelineBucket[]
+}
+
+export async function getTimeline(params: { project_id: string; granularity: TimelineGranularity; sources?: string[]; top_n?: number }): Promise<Timeline> {
+  const { project_id, granularity, sources, top_n } = params
+  return await invoke('get_timeline', { project_id, granularity, sources: sources ?? null, top_n: top_n ?? null })
+}
+
+// 项目归档：备份 / 迁移
+export async function exportProject(project_id: string, output_path?: string): Promise<string> {
+  return await invoke('export_project', { project_id, output_path: output_path ?? null })
+}
+
+export async function importProject(archive_path: string): Promise<Project> {
+  return await invoke('import_project', { archive_path })
+}
+
+// 回顾报告
+export interface GeneratedReport {
+  markdown_path: string
+  html_path: string
+}
+
+export async function generateReport(project_id: string): Promise<GeneratedReport> {
+  return await invoke('generate_report', { project_id })
+}
+
+export async function initReportTemplates(): Promise<string> {
+  return await invoke('init_report_templates')
+}
+
+// 获取扫描结果（分页）；传 cursor 时按游标分页，'' 为第一页，之后传上一页的 next_cursor
+export async function getResultsPaginated(project_id: string, page: number, page_size: number, cursor?: string): Promise<Paged<ResultItem>> {
   try {
-    console.log('API call get_results_paginated:', { project_id, page, page_size })
-    const result = await invoke<Paged<ResultItem>>('get_results_paginated', { project_id, page, page_size })
+    console.log('API call get_results_paginated:', { project_id, page, page_size, cursor })
+    const result = await invoke<Paged<ResultItem>>('get_results_paginated', { project_id, page, page_size, cursor: cursor ?? null })
     console.log('API result:', result)
     return result
   } catch (error) {
@@ -195,9 +471,165 @@ export async function getCurrentProject(): Promise<Project | null> {
   return await invoke('get_current_project')
 }
 
-export async function getResultsPaginatedAdv(params: { project_id: string; page: number; page_size: number; q?: string; file_types?: string[] }): Promise<Paged<ResultItem>> {
-  const { project_id, page, page_size, q, file_types } = params
-  return await invoke('get_results_paginated_adv', { project_id, page, page_size, q: q ?? null, file_types: file_types ?? null })
+export async function getResultsPaginatedAdv(params: { project_id: string; page: number; page_size: number; q?: string; file_types?: string[]; cursor?: string }): Promise<Paged<ResultItem>> {
+  const { project_id, page, page_size, q, file_types, cursor } = params
+  return await invoke('get_results_paginated_adv', { project_id, page, page_size, q: q ?? null, file_types: file_types ?? null, cursor: cursor ?? null })
+}
+
+// 高级结果查询：多条件筛选、排序，并返回按类型 / 来源 / 月份的分面计数
+// 分面不受自身维度的筛选影响，日期可只写 YYYY-MM-DD（终点包含当天）
+export type ResultSortColumn = 'file_path' | 'file_type' | 'source' | 'created_at' | 'modified_at' | 'size_bytes' | 'is_valid' | 'inserted_at' | 'updated_at'
+
+export interface ResultQuery {
+  q?: string
+  file_types?: string[]
+  sources?: string[]
+  min_size?: number
+  max_size?: number
+  created_from?: string
+  created_to?: string
+  modified_from?: string
+  modified_to?: string
+  // 创建、修改时间的筛选优先使用内嵌日期（照片拍摄时间、文档属性中的时间）
+  embedded_dates?: boolean
+  is_valid?: boolean
+  archived?: boolean
+  root?: string
+  tags?: string[]
+  min_star?: number
+  excluded?: boolean
+  has_note?: boolean
+  sort_by?: ResultSortColumn
+  sort_order?: 'asc' | 'desc'
+}
+
+export interface FacetCount {
+  value: string
+  count: number
+}
+
+export interface ResultQueryResponse extends Paged<ResultItem> {
+  // 游标续页时不返回，沿用第一页的分面
+  facets?: {
+    by_type: FacetCount[]
+    by_source: FacetCount[]
+    by_month: FacetCount[]
+  }
+  // 带关键词时同时检索邮件，附带最相关的几封与命中总数；续页不返回
+  emails?: Paged<EmailItem>
+}
+
+export async function queryScanResults(project_id: string, query: ResultQuery, page: number, page_size: number, cursor?: string): Promise<ResultQueryResponse> {
+  return await invoke<ResultQueryResponse>('query_scan_results', { project_id, query, page, page_size, cursor: cursor ?? null })
+}
+
+// 智能集合：按项目保存的结果查询，count 为当前结果数；pinned 的集合在报告中单独成节
+export interface SavedSearch {
+  id: string
+  project_id: string
+  name: string
+  query: ResultQuery
+  pinned: boolean
+  count: number
+  created_at: string
+  updated_at: string
+}
+
+export async function listSavedSearches(project_id: string): Promise<SavedSearch[]> {
+  return await invoke('list_saved_searches', { project_id })
+}
+
+export async function createSavedSearch(project_id: string, name: string, query: ResultQuery, pinned?: boolean): Promise<SavedSearch> {
+  return await invoke('create_saved_search', { project_id, name, query, pinned: pinned ?? null })
+}
+
+export async function updateSavedSearch(id: string, changes: { name?: string; query?: ResultQuery; pinned?: boolean }): Promise<SavedSearch> {
+  return await invoke('update_saved_search', { id, name: changes.name ?? null, query: changes.query ?? null, pinned: changes.pinned ?? null })
+}
+
+export async function deleteSavedSearch(id: string): Promise<void> {
+  return await invoke('delete_saved_search', { id })
+}
+
+export async function openSavedSearch(id: string, page: number, page_size: number, cursor?: string): Promise<ResultQueryResponse> {
+  return await invoke<ResultQueryResponse>('open_saved_search', { id, page, page_size, cursor: cursor ?? null })
+}
+
+// 人工整理：按文件路径批量修改，未传的字段保持不变；star 为 0–3，note 传空字符串清除
+export interface AnnotationPatch {
+  note?: string
+  star?: number
+  excluded?: boolean
+  add_tags?: string[]
+  remove_tags?: string[]
+}
+
+export async function annotateResults(project_id: string, file_paths: string[], patch: AnnotationPatch): Promise<number> {
+  return await invoke('annotate_results', { project_id, file_paths, patch })
+}
+
+export async function listResultTags(project_id: string): Promise<{ tag: string; count: number }[]> {
+  return await invoke('list_result_tags', { project_id })
+}
+
+// 文件内嵌的元数据（EXIF、Office 文档属性、PDF 文档信息），时间为本地时间 YYYY-MM-DD HH:mm:ss
+export interface FileMetadata {
+  created_at: string | null
+  modified_at: string | null
+  author: string | null
+  title: string | null
+  last_modified_by: string | null
+  revision: string | null
+  application: string | null
+  camera: string | null
+  latitude: number | null
+  longitude: number | null
+}
+
+// 文档采集的项目设置（collector_settings 中 id 为 documents 的一项），通过 setCollectorSettings 保存
+export interface DocumentSettings {
+  metadata: boolean
+  gps: boolean
+  embedded_dates: boolean
+}
+
+export async function getResultMetadata(project_id: string, file_path: string): Promise<FileMetadata | null> {
+  return await invoke('get_result_metadata', { project_id, file_path })
+}
+
+// 缩略图（图片与 PDF 首页）：已缓存时返回缓存文件路径，否则后台生成后推送 thumbnail-ready；size 为长边像素，默认 256
+export interface ThumbnailReady {
+  project_id: string
+  file_path: string
+  size: number
+  path: string | null
+  error: string | null
+}
+
+export async function getThumbnail(project_id: string, file_path: string, size?: number): Promise<string | null> {
+  return await invoke('get_thumbnail', { project_id, file_path, size: size ?? null })
+}
+
+// 缩略图的 PNG 字节，未缓存时当场生成
+export async function getThumbnailBytes(project_id: string, file_path: string, size?: number): Promise<ArrayBuffer> {
+  return await invoke<ArrayBuffer>('get_thumbnail_bytes', { project_id, file_path, size: size ?? null })
+}
+
+export async function onThumbnailReady(handler: (ready: ThumbnailReady) => void): Promise<() => void> {
+  return await listen<ThumbnailReady>('thumbnail-ready', (e) => handler(e.payload))
+}
+
+// 导出结果（CSV / JSON Lines / Excel），未指定 output_dir 时写入项目目录
+export type ExportFormat = 'csv' | 'jsonl' | 'xlsx'
+
+export interface ExportResult {
+  files: string[]
+  tables: { table: string; rows: number }[]
+}
+
+export async function exportResults(params: { project_id: string; format: ExportFormat; tables?: string[]; q?: string; file_types?: string[]; output_dir?: string }): Promise<ExportResult> {
+  const { project_id, format, tables, q, file_types, output_dir } = params
+  return await invoke('export_results', { project_id, format, tables: tables ?? null, q: q ?? null, file_types: file_types ?? null, output_dir: output_dir ?? null })
 }
 
 export async function getProjectScanRoots(project_id: string): Promise<string[]> {
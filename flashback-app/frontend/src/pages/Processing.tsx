@@ -5,7 +5,7 @@ import { listen } from '@tauri-apps/api/event'
 import Sidebar from '../components/Sidebar'
 import Pagination from '../components/Pagination'
 import { getResultsPaginatedAdv, getProjectScanRoots } from '../lib/tauri'
-import { startScan, startScanWithId, type ScanSummary, getResultsPaginated, type ResultItem, getCurrentProject, getProjectByName, setCurrentProject } from '../lib/tauri'
+import { startScan, startScanWithId, type ScanSummary, type ScanEvent, getResultsPaginated, type ResultItem, getCurrentProject, getProjectByName, setCurrentProject } from '../lib/tauri'
 import { useProjectStore } from '../lib/projectStore'
 
 interface LogEntry {
@@ -13,11 +13,19 @@ interface LogEntry {
   text: string
 }
 
+function formatEta(secs: number) {
+  if (secs < 60) return `${secs} 秒`
+  const m = Math.floor(secs / 60)
+  if (m < 60) return `${m} 分 ${secs % 60} 秒`
+  return `${Math.floor(m / 60)} 小时 ${m % 60} 分`
+}
+
 export default function Processing() {
   const navigate = useNavigate()
   const location = useLocation()
   const [logs, setLogs] = useState<LogEntry[]>([])
   const [progress, setProgress] = useState(0)
+  const [throughput, setThroughput] = useState<{ perSecond: number; etaSecs: number | null } | null>(null)
   const [projectId, setProjectId] = useState<string>('')
   const [projectName, setProjectName] = useState<string>('')
   const [timeRange, setTimeRange] = useState<string>('')
@@ -62,23 +70,29 @@ export default function Processing() {
     if (!projectName) return
 
     let unlistenLog: (() => void) | undefined
+    let unlistenEvent: (() => void) | undefined
     let unlistenProgress: (() => void) | undefined
     let unlistenDone: (() => void) | undefined
 
     const setupListeners = async () => {
       // 监听日志
       unlistenLog = await listen<{ icon: string; text: string }>('scan-log', (event) => {
-        const text = event.payload.text
-        setLogs(prev => [...prev, { icon: event.payload.icon, text }])
-        if (text?.startsWith('扫描目录: ')) {
-          setCurrentFolder(text.replace('扫描目录: ', ''))
+        setLogs(prev => [...prev, { icon: event.payload.icon, text: event.payload.text }])
+      })
+
+      // 文档采集器每进入一个扫描目录发送一次 file_found
+      unlistenEvent = await listen<ScanEvent>('scan-event', (event) => {
+        const e = event.payload
+        if (e.kind === 'file_found' && e.stage === 'documents') {
+          setCurrentFolder(e.path)
         }
       })
 
       // 监听进度
-      unlistenProgress = await listen<{ progress: number }>('scan-progress', async (event) => {
+      unlistenProgress = await listen<{ progress: number; per_second: number; eta_secs: number | null }>('scan-progress', async (event) => {
         const p = event.payload.progress
         setProgress(p)
+        setThroughput({ perSecond: event.payload.per_second, etaSecs: event.payload.eta_secs })
         // 扫描过程中，按 10% 阶梯刷新一次分页数据与总数
         if (projectId && p - lastProgressQueriedRef.current >= 10) {
           lastProgressQueriedRef.current = p
@@ -185,6 +199,7 @@ export default function Processing() {
 
     return () => {
       unlistenLog?.()
+      unlistenEvent?.()
       unlistenProgress?.()
       unlistenDone?.()
     }
@@ -259,7 +274,15 @@ export default function Processing() {
               <div className="w-full max-w-2xl mb-4">
                 <div className="flex items-center justify-between mb-1">
                   <span className="text-[12px] font-medium text-slate-600 dark:text-slate-300">扫描进度</span>
-                  <span className="text-[12px] font-mono text-primary">{progress}%</span>
+                  <span className="text-[12px] font-mono text-primary">
+                    {throughput && throughput.perSecond > 0 && (
+                      <span className="text-slate-500 dark:text-slate-400 mr-2">
+                        {Math.round(throughput.perSecond)} 个/秒
+                        {throughput.etaSecs != null && ` · 剩余 ${formatEta(throughput.etaSecs)}`}
+                      </span>
+                    )}
+                    {progress}%
+                  </span>
                 </div>
                 <div className="w-full bg-slate-200 dark:bg-slate-700 rounded-full h-1.5 overflow-hidden">
                   <div className="bg-primary h-1.5 rounded-full transition-all duration-300 ease-out" style={{ width: `${progress}%` }} />
@@ -309,6 +332,14 @@ export default function Processing() {
                 </>
               )}
             </p>
+            {scanComplete && scanSummary?.issues && scanSummary.issues.skipped + scanSummary.issues.failed > 0 && (
+              <p
+                className="text-[11px] text-amber-600 dark:text-amber-400"
+                title={scanSummary.issues.samples.map(s => `${s.reason}: ${s.path ?? ''} ${s.message}`).join('\n')}
+              >
+                跳过 {scanSummary.issues.skipped} 个条目，失败 {scanSummary.issues.failed} 个
+              </p>
+            )}
           </div>
 
           {/* 扫描完成后的操作按钮 */}
@@ -2,7 +2,32 @@ import { useLocation } from 'react-router-dom'
 import { useProjectStore } from '../lib/projectStore'
 import { useState, useEffect } from 'react'
 import Sidebar from '../components/Sidebar'
-import { getResultsPaginated, type ResultItem, getCurrentProject } from '../lib/tauri'
+import { queryScanResults, type ResultItem, type ResultQuery, type ResultSortColumn, type FacetCount, annotateResults, getThumbnailBytes, getCurrentProject, getWatchStatus, startWatch, stopWatch, onResultsChanged } from '../lib/tauri'
+
+// 可生成缩略图的文件类型（图片与 PDF 首页）
+const THUMBNAIL_TYPES = ['png', 'jpg', 'jpeg', 'gif', 'webp', 'bmp', 'tif', 'tiff', 'pdf']
+
+function ResultThumbnail({ projectId, filePath }: { projectId: string; filePath: string }) {
+  const [url, setUrl] = useState<string>()
+
+  useEffect(() => {
+    let objectUrl: string | undefined
+    let cancelled = false
+    getThumbnailBytes(projectId, filePath, 64)
+      .then(bytes => {
+        if (cancelled) return
+        objectUrl = URL.createObjectURL(new Blob([bytes], { type: 'image/png' }))
+        setUrl(objectUrl)
+      })
+      .catch(() => setUrl(undefined))
+    return () => {
+      cancelled = true
+      if (objectUrl) URL.revokeObjectURL(objectUrl)
+    }
+  }, [projectId, filePath])
+
+  return url ? <img src={url} alt="" className="w-6 h-6 object-cover rounded shrink-0" /> : null
+}
 
 export default function Results() {
   const location = useLocation()
@@ -15,6 +40,11 @@ export default function Results() {
   const PAGE_SIZE = 8
   const [total, setTotal] = useState(0)
   const [totalPages, setTotalPages] = useState(1)
+  const [watching, setWatching] = useState(false)
+  const [reloadKey, setReloadKey] = useState(0)
+  const [query, setQuery] = useState<ResultQuery>({ sort_by: 'updated_at', sort_order: 'desc' })
+  const [typeFacets, setTypeFacets] = useState<FacetCount[]>([])
+  const [sourceFacets, setSourceFacets] = useState<FacetCount[]>([])
 
   useEffect(() => {
     (async () => {
@@ -43,17 +73,98 @@ export default function Results() {
 
     console.log('Loading results:', { projectId, page, PAGE_SIZE })
 
-    getResultsPaginated(projectId, page, PAGE_SIZE)
+    queryScanResults(projectId, query, page, PAGE_SIZE)
       .then(res => {
         console.log('Results loaded:', res)
         setItems(res.items)
         setTotal(res.total)
         setTotalPages(res.total_pages)
+        if (res.facets) {
+          setTypeFacets(res.facets.by_type)
+          setSourceFacets(res.facets.by_source)
+        }
       })
       .catch(error => {
         console.error('Failed to load results:', error)
       })
-  }, [projectId, page])
+  }, [projectId, page, reloadKey, query])
+
+  useEffect(() => {
+    if (!projectId) return
+    getWatchStatus()
+      .then(status => setWatching(status?.project_id === projectId))
+      .catch(() => setWatching(false))
+    let unlisten: (() => void) | undefined
+    onResultsChanged(changed => {
+      if (changed.project_id === projectId) setReloadKey(k => k + 1)
+    }).then(fn => { unlisten = fn })
+    return () => { unlisten?.() }
+  }, [projectId])
+
+  const updateQuery = (patch: Partial<ResultQuery>) => {
+    setQuery(q => ({ ...q, ...patch }))
+    setPage(1)
+  }
+
+  // 再次点击同一列时切换升降序
+  const toggleSort = (column: ResultSortColumn) => {
+    if (query.sort_by === column) {
+      updateQuery({ sort_order: query.sort_order === 'asc' ? 'desc' : 'asc' })
+    } else {
+      updateQuery({ sort_by: column, sort_order: 'desc' })
+    }
+  }
+
+  const toggleFacet = (key: 'file_types' | 'sources', value: string) => {
+    const current = query[key] ?? []
+    updateQuery({ [key]: current.includes(value) ? current.filter(v => v !== value) : [...current, value] })
+  }
+
+  const sortHeader = (column: ResultSortColumn, label: string, className: string) => (
+    <button onClick={() => toggleSort(column)} className={`${className} flex items-center gap-0.5 text-left hover:text-primary`}>
+      {label}
+      {query.sort_by === column && (
+        <span className="material-symbols-outlined text-[12px]">{query.sort_order === 'asc' ? 'arrow_upward' : 'arrow_downward'}</span>
+      )}
+    </button>
+  )
+
+  const facetChips = (key: 'file_types' | 'sources', facets: FacetCount[]) =>
+    facets.map(f => {
+      const active = (query[key] ?? []).includes(f.value)
+      return (
+        <button
+          key={`${key}-${f.value}`}
+          onClick={() => toggleFacet(key, f.value)}
+          className={`px-2 py-0.5 rounded-full text-[10px] border ${active ? 'border-primary text-primary bg-primary/10' : 'border-slate-200 dark:border-slate-700 text-slate-500 dark:text-slate-400'}`}
+        >
+          {f.value || '-'} · {f.count}
+        </button>
+      )
+    })
+
+  const toggleStar = async (row: ResultItem) => {
+    try {
+      await annotateResults(projectId, [row.file_path], { star: row.star > 0 ? 0 : 1 })
+      setReloadKey(k => k + 1)
+    } catch (error) {
+      console.error('Failed to update star:', error)
+    }
+  }
+
+  const toggleWatch = async () => {
+    try {
+      if (watching) {
+        await stopWatch()
+        setWatching(false)
+      } else {
+        await startWatch(projectId)
+        setWatching(true)
+      }
+    } catch (error) {
+      console.error('Failed to toggle watch:', error)
+    }
+  }
 
   return (
     <div className="flex min-h-screen bg-slate-50 dark:bg-slate-900 font-display">
@@ -79,23 +190,43 @@ export default function Results() {
                   <span className="text-[12px] font-bold text-slate-700 dark:text-slate-200">扫描结果</span>
                   <span className="text-[11px] text-slate-500 dark:text-slate-400 ml-1">(共 {total} 条)</span>
                 </div>
+                <button
+                  onClick={toggleWatch}
+                  disabled={!projectId}
+                  className={`flex items-center gap-1 px-2 py-1 rounded-md text-[11px] border ${watching ? 'border-primary text-primary' : 'border-slate-200 dark:border-slate-700 text-slate-500 dark:text-slate-400'}`}
+                >
+                  <span className="material-symbols-outlined text-[14px]">{watching ? 'visibility' : 'visibility_off'}</span>
+                  {watching ? '实时监听中' : '实时监听'}
+                </button>
               </div>
+              {(typeFacets.length > 0 || sourceFacets.length > 0) && (
+                <div className="flex flex-wrap gap-1.5 px-4 py-2 border-b border-slate-200 dark:border-slate-700">
+                  {facetChips('file_types', typeFacets)}
+                  {facetChips('sources', sourceFacets)}
+                </div>
+              )}
               <div className="divide-y divide-slate-200 dark:divide-slate-700">
                 <div className="grid grid-cols-12 gap-2 px-4 py-2 bg-slate-50 dark:bg-slate-900/40 text-[11px] text-slate-500 dark:text-slate-400">
-                  <div className="col-span-5">文件路径</div>
-                  <div className="col-span-1">类型</div>
-                  <div className="col-span-2">来源</div>
-                  <div className="col-span-1 text-right">大小</div>
-                  <div className="col-span-1">创建时间</div>
-                  <div className="col-span-1">修改时间</div>
-                  <div className="col-span-1 text-center">有效</div>
+                  {sortHeader('file_path', '文件路径', 'col-span-5')}
+                  {sortHeader('file_type', '类型', 'col-span-1')}
+                  {sortHeader('source', '来源', 'col-span-2')}
+                  {sortHeader('size_bytes', '大小', 'col-span-1 justify-end')}
+                  {sortHeader('created_at', '创建时间', 'col-span-1')}
+                  {sortHeader('modified_at', '修改时间', 'col-span-1')}
+                  {sortHeader('is_valid', '有效', 'col-span-1 justify-center')}
                 </div>
                 {items.length === 0 ? (
                   <div className="px-4 py-10 text-center text-[12px] text-slate-500 dark:text-slate-400">暂无数据</div>
                 ) : (
                   items.map(row => (
-                    <div key={row.id} className="grid grid-cols-12 gap-2 px-4 py-2 text-[11px] text-slate-700 dark:text-slate-300">
-                      <div className="col-span-5 truncate font-mono">{row.file_path}</div>
+                    <div key={row.id} className={`grid grid-cols-12 gap-2 px-4 py-2 text-[11px] text-slate-700 dark:text-slate-300 ${row.archived ? 'opacity-50' : ''}`}>
+                      <div className="col-span-5 flex items-center gap-1 min-w-0">
+                        <button onClick={() => toggleStar(row)} title={row.star > 0 ? '取消星标' : '加星标'} className={row.star > 0 ? 'text-amber-500' : 'text-slate-300 dark:text-slate-600'}>
+                          <span className="material-symbols-outlined text-[14px]">{row.star > 0 ? 'star' : 'star_outline'}</span>
+                        </button>
+                        {THUMBNAIL_TYPES.includes(row.file_type) && <ResultThumbnail projectId={projectId} filePath={row.file_path} />}
+                        <span className={`truncate font-mono ${row.excluded ? 'line-through' : ''}`} title={row.note || (row.archived ? '已归档：已移出时间范围' : undefined)}>{row.file_path}</span>
+                      </div>
                       <div className="col-span-1">{row.file_type || '-'}</div>
                       <div className="col-span-2">{row.source}</div>
                       <div className="col-span-1 text-right">{(row.size_bytes/1024).toFixed(1)} KB</div>
@@ -0,0 +1,497 @@
+//! 项目归档：把项目记录、各项目数据表与项目目录打包为一个 zip，用于备份或迁移到另一台电脑
+//! 归档结构：
+//! - manifest.json：格式标识、版本、导出时间、各表行数
+//! - project.json：projects 表中的项目记录
+//! - tables/<表名>.jsonl：每行一条记录，BLOB 以 {"$blob": "<hex>"} 表示
+//! - folder/...：项目目录下的文件
+//!
+//! 导入时项目 ID 或行 ID 冲突会重新生成，项目名冲突会追加序号；全文索引等派生数据在导入后重建；
+//! 扫描计划导入后处于停用状态
+
+use rusqlite::types::{Value, ValueRef};
+use rusqlite::Connection;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs::File;
+use std::io::{BufRead, BufReader, BufWriter, Read, Write};
+use std::path::{Path, PathBuf};
+use uuid::Uuid;
+use walkdir::WalkDir;
+use zip::write::SimpleFileOptions;
+
+use crate::collectors::CollectorRegistry;
+use crate::{export, thumbnail, DatabaseManager};
+
+const ARCHIVE_FORMAT: &str = "flashback-project";
+/// 归档格式版本；结构变化时递增，导入时拒绝高于当前版本的归档
+const ARCHIVE_VERSION: u32 = 1;
+/// 单个归档条目解压后的大小上限，防止压缩炸弹占满磁盘或内存
+const MAX_ENTRY_SIZE: u64 = 4 << 30;
+
+#[derive(Debug, Serialize, Deserialize)]
+struct Manifest {
+    format: String,
+    version: u32,
+    exported_at: String,
+    project_id: String,
+    project_name: String,
+    /// 各表导出的行数
+    tables: BTreeMap<String, usize>,
+}
+
+/// 归档中包含的表：scan_results、采集器配置、扫描记录、人工整理、保存的搜索、扫描计划与各采集器的表（不含虚拟表）
+fn archived_tables(conn: &Connection) -> Result<Vec<&'static str>, String> {
+    let mut tables = export::exportable_tables(conn)?;
+    tables.push("collector_settings");
+    tables.push("scan_runs");
+    tables.push("result_annotations");
+    tables.push("result_tags");
+    tables.push("saved_searches");
+    tables.push("schedules");
+    Ok(tables)
+}
+
+fn table_columns(conn: &Connection, table: &str) -> Result<Vec<String>, String> {
+    let mut stmt = conn
+        .prepare(&format!("PRAGMA table_info({})", table))
+        .map_err(|e| e.to_string())?;
+    let rows = stmt
+        .query_map([], |row| row.get::<_, String>(1))
+        .map_err(|e| e.to_string())?;
+    rows.collect::<Result<_, _>>().map_err(|e| e.to_string())
+}
+
+fn to_json(v: ValueRef) -> serde_json::Value {
+    match v {
+        ValueRef::Null => serde_json::Value::Null,
+        ValueRef::Integer(i) => i.into(),
+        ValueRef::Real(f) => f.into(),
+        ValueRef::Text(t) => String::from_utf8_lossy(t).to_string().into(),
+        ValueRef::Blob(b) => {
+            let hex: String = b.iter().map(|x| format!("{:02x}", x)).collect();
+            serde_json::json!({ "$blob": hex })
+        }
+    }
+}
+
+fn from_json(v: &serde_json::Value) -> Result<Value, String> {
+    Ok(match v {
+        serde_json::Value::Null => Value::Null,
+        serde_json::Value::Bool(b) => Value::Integer(*b as i64),
+        serde_json::Value::Number(n) => match n.as_i64() {
+            Some(i) => Value::Integer(i),
+            None => Value::Real(n.as_f64().unwrap_or_default()),
+        },
+        serde_json::Value::String(s) => Value::Text(s.clone()),
+        serde_json::Value::Object(o) => match o.get("$blob").and_then(|h| h.as_str()) {
+            Some(hex) => {
+                let invalid = || "BLOB 数据有误".to_string();
+                if !hex.is_ascii() || !hex.len().is_multiple_of(2) {
+                    return Err(invalid());
+                }
+                Value::Blob(
+                    (0..hex.len())
+                        .step_by(2)
+                        .map(|i| u8::from_str_radix(&hex[i..i + 2], 16))
+                        .collect::<Result<_, _>>()
+                        .map_err(|_| invalid())?,
+                )
+            }
+            None => Value::Text(v.to_string()),
+        },
+        serde_json::Value::Array(_) => Value::Text(v.to_string()),
+    })
+}
+
+fn too_large(name: &str) -> String {
+    format!("归档条目 {} 过大", name)
+}
+
+/// 查询结果逐行转为 JSON 对象
+fn write_rows(
+    out: &mut impl Write,
+    conn: &Connection,
+    sql: &str,
+    project_id: &str,
+) -> Result<usize, String> {
+    let mut stmt = conn.prepare(sql).map_err(|e| e.to_string())?;
+    let columns: Vec<String> = stmt.column_names().iter().map(|c| c.to_string()).collect();
+    let mut rows = stmt.query([project_id]).map_err(|e| e.to_string())?;
+    let mut count = 0usize;
+    while let Some(row) = rows.next().map_err(|e| e.to_string())? {
+        let mut obj = serde_json::Map::with_capacity(columns.len());
+        for (i, name) in columns.iter().enumerate() {
+            obj.insert(
+                name.clone(),
+                to_json(row.get_ref(i).map_err(|e| e.to_string())?),
+            );
+        }
+        serde_json::to_writer(&mut *out, &obj).map_err(|e| e.to_string())?;
+        out.write_all(b"\n").map_err(|e| e.to_string())?;
+        count += 1;
+    }
+    Ok(count)
+}
+
+/// 导出项目归档，output_path 为空时写入 ~/FlashBack/backups，返回归档路径
+pub(crate) fn export_project(
+    conn: &Connection,
+    project_id: &str,
+    output_path: Option<&str>,
+) -> Result<String, String> {
+    let (project_name, folder_path): (String, String) = conn
+        .query_row(
+            "SELECT name, folder_path FROM projects WHERE id = ?",
+            [project_id],
+            |row| Ok((row.get(0)?, row.get(1)?)),
+        )
+        .map_err(|_| "项目不存在".to_string())?;
+
+    let now = chrono::Local::now();
+    let path = match output_path.filter(|s| !s.trim().is_empty()) {
+        Some(p) => PathBuf::from(p),
+        None => backups_dir().join(format!(
+            "{}-{}.zip",
+            project_name,
+            now.format("%Y%m%d-%H%M%S")
+        )),
+    };
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+    }
+
+    let file = File::create(&path).map_err(|e| e.to_string())?;
+    let mut zip = zip::ZipWriter::new(BufWriter::new(file));
+    let options = SimpleFileOptions::default().large_file(true);
+
+    zip.start_file("project.json", options)
+        .map_err(|e| e.to_string())?;
+    write_rows(
+        &mut zip,
+        conn,
+        "SELECT * FROM projects WHERE id = ?",
+        project_id,
+    )?;
+
+    let mut tables = BTreeMap::new();
+    for table in archived_tables(conn)? {
+        zip.start_file(format!("tables/{}.jsonl", table), options)
+            .map_err(|e| e.to_string())?;
+        let sql = format!(
+            "SELECT * FROM {} WHERE project_id = ? ORDER BY rowid",
+            table
+        );
+        let count = write_rows(&mut zip, conn, &sql, project_id)?;
+        tables.insert(table.to_string(), count);
+    }
+
+    let folder = Path::new(&folder_path);
+    if folder.is_dir() {
+        for entry in WalkDir::new(folder).into_iter().filter_map(Result::ok) {
+            // 归档本身写在项目目录下时跳过
+            if !entry.file_type().is_file() || entry.path() == path {
+                continue;
+            }
+            let rel = match entry.path().strip_prefix(folder) {
+                Ok(r) => r,
+                Err(_) => continue,
+            };
+            // 缩略图缓存可随时重新生成，不打包
+            if rel.starts_with(thumbnail::CACHE_DIR) {
+                continue;
+            }
+            let name = rel
+                .components()
+                .map(|c| c.as_os_str().to_string_lossy())
+                .collect::<Vec<_>>()
+                .join("/");
+            zip.start_file(format!("folder/{}", name), options)
+                .map_err(|e| e.to_string())?;
+            let mut f = File::open(entry.path()).map_err(|e| e.to_string())?;
+            std::io::copy(&mut f, &mut zip).map_err(|e| e.to_string())?;
+        }
+    }
+
+    let manifest = Manifest {
+        format: ARCHIVE_FORMAT.to_string(),
+        version: ARCHIVE_VERSION,
+        exported_at: now.format("%Y-%m-%d %H:%M:%S").to_string(),
+        project_id: project_id.to_string(),
+        project_name,
+        tables,
+    };
+    zip.start_file("manifest.json", options)
+        .map_err(|e| e.to_string())?;
+    serde_json::to_writer_pretty(&mut zip, &manifest).map_err(|e| e.to_string())?;
+    zip.finish()
+        .and_then(|mut w| w.flush().map_err(Into::into))
+        .map_err(|e| e.to_string())?;
+
+    Ok(path.to_string_lossy().to_string())
+}
+
+fn backups_dir() -> PathBuf {
+    DatabaseManager::get_db_path()
+        .parent()
+        .map(Path::to_path_buf)
+        .unwrap_or_else(|| PathBuf::from("."))
+        .join("backups")
+}
+
+/// 项目目录与 create_project 一致：~/FlashBack/<项目名>
+fn project_folder(name: &str) -> PathBuf {
+    DatabaseManager::get_db_path()
+        .parent()
+        .map(Path::to_path_buf)
+        .unwrap_or_else(|| PathBuf::from("."))
+        .join(name)
+}
+
+/// 归档中的项目名来自外部文件，用作目录名前替换路径分隔符与控制字符，
+/// 去掉首尾的点与空白，避免 `..`、绝对路径等把项目目录放到 FlashBack 目录之外
+fn sanitize_name(name: &str) -> String {
+    let cleaned: String = name
+        .chars()
+        .map(|c| match c {
+            '/' | '\\' | ':' => '_',
+            c if c.is_control() => '_',
+            c => c,
+        })
+        .collect();
+    let cleaned = cleaned.trim_matches(|c: char| c == '.' || c.is_whitespace());
+    if cleaned.is_empty() {
+        "导入的项目".to_string()
+    } else {
+        cleaned.to_string()
+    }
+}
+
+/// 名称已被占用（或同名目录非空）时追加序号
+fn unique_name(conn: &Connection, name: &str) -> Result<String, String> {
+    let taken = |candidate: &str| -> Result<bool, String> {
+        let in_db: bool = conn
+            .query_row(
+                "SELECT EXISTS(SELECT 1 FROM projects WHERE name = ?)",
+                [candidate],
+                |row| row.get(0),
+            )
+            .map_err(|e| e.to_string())?;
+        let folder_used = std::fs::read_dir(project_folder(candidate))
+            .map(|mut d| d.next().is_some())
+            .unwrap_or(false);
+        Ok(in_db || folder_used)
+    };
+    if !taken(name)? {
+        return Ok(name.to_string());
+    }
+    let mut n = 2;
+    loop {
+        let candidate = format!("{} ({})", name, n);
+        if !taken(&candidate)? {
+            return Ok(candidate);
+        }
+        n += 1;
+    }
+}
+
+fn read_entry(zip: &mut zip::ZipArchive<BufReader<File>>, name: &str) -> Result<String, String> {
+    let mut entry = zip
+        .by_name(name)
+        .map_err(|_| format!("归档缺少 {}", name))?;
+    let mut text = String::new();
+    let n = (&mut entry)
+        .take(MAX_ENTRY_SIZE + 1)
+        .read_to_string(&mut text)
+        .map_err(|e| e.to_string())?;
+    if n as u64 > MAX_ENTRY_SIZE {
+        return Err(too_large(name));
+    }
+    Ok(text)
+}
+
+/// 插入一条记录：只写入当前表中存在的列（兼容不同版本的表结构），project_id 改为新项目
+fn insert_row(
+    conn: &Connection,
+    table: &str,
+    columns: &[String],
+    mut obj: serde_json::Map<String, serde_json::Value>,
+    project_id: &str,
+) -> Result<(), String> {
+    obj.insert("project_id".into(), project_id.into());
+    // 行 ID 已被占用（如同一归档重复导入）时重新生成
+    if let Some(id) = obj.get("id").and_then(|v| v.as_str()) {
+        let exists: bool = conn
+            .query_row(
+                &format!("SELECT EXISTS(SELECT 1 FROM {} WHERE id = ?)", table),
+                [id],
+                |row| row.get(0),
+            )
+            .map_err(|e| e.to_string())?;
+        if exists {
+            obj.insert("id".into(), Uuid::new_v4().to_string().into());
+        }
+    }
+    let (names, values): (Vec<&str>, Vec<Value>) = obj
+        .iter()
+        .filter(|(k, _)| columns.iter().any(|c| c == *k))
+        .map(|(k, v)| from_json(v).map(|v| (k.as_str(), v)))
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| format!("{} {}", table, e))?
+        .into_iter()
+        .unzip();
+    let sql = format!(
+        "INSERT INTO {} ({}) VALUES ({})",
+        table,
+        names.join(", "),
+        vec!["?"; names.len()].join(", ")
+    );
+    conn.execute(&sql, rusqlite::params_from_iter(values))
+        .map_err(|e| format!("导入 {} 失败: {}", table, e))?;
+    Ok(())
+}
+
+/// 导入项目归档，返回新项目的 ID
+pub(crate) fn import_project(conn: &mut Connection, archive_path: &str) -> Result<String, String> {
+    let file = File::open(archive_path).map_err(|e| e.to_string())?;
+    let mut zip = zip::ZipArchive::new(BufReader::new(file)).map_err(|e| e.to_string())?;
+
+    let manifest: Manifest = serde_json::from_str(&read_entry(&mut zip, "manifest.json")?)
+        .map_err(|_| "不是有效的 FlashBack 项目归档".to_string())?;
+    if manifest.format != ARCHIVE_FORMAT {
+        return Err("不是有效的 FlashBack 项目归档".into());
+    }
+    if manifest.version > ARCHIVE_VERSION {
+        return Err(format!(
+            "归档版本 {} 高于当前支持的版本 {}，请升级 FlashBack",
+            manifest.version, ARCHIVE_VERSION
+        ));
+    }
+    let mut project: serde_json::Map<String, serde_json::Value> =
+        serde_json::from_str(read_entry(&mut zip, "project.json")?.trim())
+            .map_err(|e| format!("项目记录有误: {}", e))?;
+
+    // 项目 ID 冲突时重新生成，名称冲突时追加序号
+    let id_taken: bool = conn
+        .query_row(
+            "SELECT EXISTS(SELECT 1 FROM projects WHERE id = ?)",
+            [&manifest.project_id],
+            |row| row.get(0),
+        )
+        .map_err(|e| e.to_string())?;
+    let project_id = if id_taken {
+        Uuid::new_v4().to_string()
+    } else {
+        manifest.project_id.clone()
+    };
+    let name = unique_name(conn, &sanitize_name(&manifest.project_name))?;
+    let folder = project_folder(&name);
+    let root = project_folder("");
+    if folder.parent() != Some(root.as_path()) {
+        return Err(format!("项目名称无效: {}", manifest.project_name));
+    }
+
+    // 先还原项目目录，数据库写入失败时再清理
+    std::fs::create_dir_all(&folder).map_err(|e| format!("创建项目文件夹失败: {}", e))?;
+    let restore = (|| -> Result<(), String> {
+        for i in 0..zip.len() {
+            let mut entry = zip.by_index(i).map_err(|e| e.to_string())?;
+            // enclosed_name 拒绝绝对路径与 .. 等越出归档根目录的条目
+            let rel = match entry
+                .enclosed_name()
+                .and_then(|p| p.strip_prefix("folder").ok().map(Path::to_path_buf))
+            {
+                Some(p) if !entry.is_dir() && !p.as_os_str().is_empty() => p,
+                _ => continue,
+            };
+            let target = folder.join(rel);
+            if let Some(parent) = target.parent() {
+                std::fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+            }
+            let mut out = File::create(&target).map_err(|e| e.to_string())?;
+            let n = std::io::copy(&mut (&mut entry).take(MAX_ENTRY_SIZE + 1), &mut out)
+                .map_err(|e| e.to_string())?;
+            if n > MAX_ENTRY_SIZE {
+                return Err(too_large(entry.name()));
+            }
+        }
+        Ok(())
+    })();
+    if let Err(e) = restore {
+        let _ = std::fs::remove_dir_all(&folder);
+        return Err(format!("还原项目目录失败: {}", e));
+    }
+
+    let result = (|| -> Result<(), String> {
+        let tx = conn.transaction().map_err(|e| e.to_string())?;
+        let now = chrono::Local::now().format("%Y-%m-%d %H:%M:%S").to_string();
+        project.insert("id".into(), project_id.clone().into());
+        project.insert("name".into(), name.clone().into());
+        project.insert(
+            "folder_path".into(),
+            folder.to_string_lossy().to_string().into(),
+        );
+        project.insert("updated_at".into(), now.into());
+        let columns = table_columns(&tx, "projects")?;
+        let (names, values): (Vec<&str>, Vec<Value>) = project
+            .iter()
+            .filter(|(k, _)| columns.iter().any(|c| c == *k))
+            .map(|(k, v)| from_json(v).map(|v| (k.as_str(), v)))
+            .collect::<Result<Vec<_>, _>>()?
+            .into_iter()
+            .unzip();
+        tx.execute(
+            &format!(
+                "INSERT INTO projects ({}) VALUES ({})",
+                names.join(", "),
+                vec!["?"; names.len()].join(", ")
+            ),
+            rusqlite::params_from_iter(values),
+        )
+        .map_err(|e| format!("导入项目记录失败: {}", e))?;
+
+        // 只导入当前版本认识的表，归档中多出的表忽略
+        for table in archived_tables(&tx)? {
+            let mut entry = match zip.by_name(&format!("tables/{}.jsonl", table)) {
+                Ok(e) => e,
+                Err(_) => continue,
+            };
+            let columns = table_columns(&tx, table)?;
+            let name = entry.name().to_string();
+            let mut reader = BufReader::new((&mut entry).take(MAX_ENTRY_SIZE + 1));
+            let mut line = String::new();
+            let mut total = 0u64;
+            loop {
+                line.clear();
+                let n = reader.read_line(&mut line).map_err(|e| e.to_string())?;
+                if n == 0 {
+                    break;
+                }
+                total += n as u64;
+                if total > MAX_ENTRY_SIZE {
+                    return Err(too_large(&name));
+                }
+                if line.trim().is_empty() {
+                    continue;
+                }
+                let obj = serde_json::from_str(line.trim())
+                    .map_err(|e| format!("{} 数据有误: {}", table, e))?;
+                insert_row(&tx, table, &columns, obj, &project_id)?;
+            }
+        }
+        // 计划的下次执行时间来自导出时，导入后先停用，重新启用时从当前时间重新排期
+        tx.execute(
+            "UPDATE schedules SET enabled = 0 WHERE project_id = ?",
+            [&project_id],
+        )
+        .map_err(|e| e.to_string())?;
+        CollectorRegistry::builtin()
+            .reindex(&tx, &project_id)
+            .map_err(|e| e.to_string())?;
+        tx.commit().map_err(|e| e.to_string())
+    })();
+    if let Err(e) = result {
+        let _ = std::fs::remove_dir_all(&folder);
+        return Err(e);
+    }
+    Ok(project_id)
+}
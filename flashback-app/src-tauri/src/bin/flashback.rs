@@ -0,0 +1,5 @@
+//! 无界面的命令行版本，便于脚本化扫描、远程执行与持续集成
+
+fn main() -> std::process::ExitCode {
+    flashback_app_lib::cli::run()
+}
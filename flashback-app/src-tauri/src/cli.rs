@@ -0,0 +1,407 @@
+//! 命令行入口：不启动窗口，直接复用 lib 中的项目管理、扫描引擎、结果查询、导出与报告
+//! 项目参数既可以是项目名称也可以是项目 id；扫描日志与进度输出到标准错误，结果输出到标准输出
+
+use clap::{Args, Parser, Subcommand};
+use rusqlite::Connection;
+use std::io::{IsTerminal, Write};
+use std::process::ExitCode;
+use std::sync::Mutex;
+
+use crate::collectors::CollectorRegistry;
+use crate::progress::{ProgressSink, ScanEvent};
+use crate::scan;
+use crate::walk::WalkOptions;
+use crate::{DatabaseManager, Project, ProjectInput, PROJECT_COLUMNS};
+
+#[derive(Parser)]
+#[command(
+    name = "flashback",
+    version,
+    about = "FlashBack 命令行：管理项目、执行扫描并导出结果"
+)]
+struct Cli {
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// 项目管理
+    #[command(subcommand)]
+    Project(ProjectCommand),
+    /// 扫描项目，进度输出到终端
+    Scan {
+        /// 项目名称或 id
+        project: String,
+        /// 以 JSON Lines 逐行输出扫描事件
+        #[arg(long)]
+        json: bool,
+    },
+    /// 分页查看扫描结果
+    Results {
+        /// 项目名称或 id
+        project: String,
+        #[arg(long, default_value_t = 1)]
+        page: usize,
+        #[arg(long, default_value_t = 50)]
+        page_size: usize,
+        /// 按游标分页：传上一页输出的游标，空字符串为第一页；指定后忽略 --page
+        #[arg(long)]
+        cursor: Option<String>,
+        #[command(flatten)]
+        filter: ResultFilter,
+        /// 以 JSON 输出
+        #[arg(long)]
+        json: bool,
+    },
+    /// 导出扫描结果与采集数据
+    Export {
+        /// 项目名称或 id
+        project: String,
+        /// 导出格式：csv、jsonl、xlsx
+        #[arg(long, default_value = "csv")]
+        format: String,
+        /// 只导出指定的表，可重复；默认导出全部
+        #[arg(long = "table")]
+        tables: Vec<String>,
+        #[command(flatten)]
+        filter: ResultFilter,
+        /// 输出目录，默认写入项目目录
+        #[arg(long)]
+        output_dir: Option<String>,
+    },
+    /// 生成 Markdown / HTML 回顾报告
+    Report {
+        /// 项目名称或 id
+        project: String,
+    },
+}
+
+#[derive(Subcommand)]
+enum ProjectCommand {
+    /// 创建项目
+    Create {
+        name: String,
+        /// 时间范围：past_week、past_month、past_year
+        #[arg(long, default_value = "past_year")]
+        time_range: String,
+        /// 自定义扫描目录，可重复；指定后只扫描这些目录
+        #[arg(long = "folder")]
+        folders: Vec<String>,
+        /// 启用的采集器 id，可重复；默认使用默认采集器
+        #[arg(long = "collector")]
+        collectors: Vec<String>,
+        /// 时间窗口模式：rolling、frozen（固定为创建时的范围）、retain（保留移出范围的条目）
+        #[arg(long)]
+        window: Option<String>,
+        #[command(flatten)]
+        walk: WalkArgs,
+    },
+    /// 列出全部项目
+    List {
+        /// 以 JSON 输出
+        #[arg(long)]
+        json: bool,
+    },
+    /// 删除项目及其数据与项目目录
+    Delete {
+        /// 项目名称或 id
+        project: String,
+    },
+}
+
+/// scan_results 的筛选条件，与 get_results_paginated_adv 一致
+#[derive(Args)]
+struct ResultFilter {
+    /// 路径关键词
+    #[arg(short, long)]
+    q: Option<String>,
+    /// 文件类型，可重复
+    #[arg(long = "type")]
+    file_types: Vec<String>,
+}
+
+/// 目录遍历选项，与 set_walk_options 一致
+#[derive(Args)]
+struct WalkArgs {
+    /// 跟随符号链接（检测到循环时跳过）
+    #[arg(long)]
+    follow_symlinks: bool,
+    /// 不进入其他文件系统（网络盘、外接硬盘等挂载点）
+    #[arg(long)]
+    same_file_system: bool,
+    /// 最大遍历深度，根目录的直接子项深度为 1
+    #[arg(long)]
+    max_depth: Option<usize>,
+    /// 单个目录最多处理的文件数
+    #[arg(long)]
+    max_files_per_dir: Option<usize>,
+    /// 跳过以 . 开头的隐藏目录
+    #[arg(long)]
+    skip_hidden: bool,
+}
+
+impl From<WalkArgs> for WalkOptions {
+    fn from(a: WalkArgs) -> Self {
+        WalkOptions {
+            follow_symlinks: a.follow_symlinks,
+            same_file_system: a.same_file_system,
+            max_depth: a.max_depth,
+            max_files_per_dir: a.max_files_per_dir,
+            skip_hidden: a.skip_hidden,
+        }
+    }
+}
+
+impl ResultFilter {
+    fn file_types(&self) -> Option<&[String]> {
+        Some(self.file_types.as_slice()).filter(|t| !t.is_empty())
+    }
+}
+
+pub fn run() -> ExitCode {
+    let cli = Cli::parse();
+    match execute(cli.command) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("错误: {}", e);
+            ExitCode::FAILURE
+        }
+    }
+}
+
+fn execute(command: Command) -> Result<(), String> {
+    let db = DatabaseManager::new();
+    db.initialize().map_err(|e| e.to_string())?;
+    let conn = db.get_connection().map_err(|e| e.to_string())?;
+
+    match command {
+        Command::Project(ProjectCommand::Create {
+            name,
+            time_range,
+            folders,
+            collectors,
+            window,
+            walk,
+        }) => {
+            let custom = !folders.is_empty();
+            let project = crate::insert_project(
+                &conn,
+                ProjectInput {
+                    name,
+                    time_range,
+                    scan_scope: custom.then(|| "CUSTOM".to_string()),
+                    scan_folders: custom.then_some(folders),
+                    collectors: Some(collectors).filter(|c| !c.is_empty()),
+                    window_mode: window,
+                },
+            )?;
+            let walk = WalkOptions::from(walk);
+            if walk != WalkOptions::default() {
+                crate::set_project_walk_options(&conn, &project.id, &walk)?;
+            }
+            println!("已创建项目 {} ({})", project.name, project.id);
+            println!("项目目录: {}", project.folder_path);
+        }
+        Command::Project(ProjectCommand::List { json }) => {
+            let projects = crate::list_projects(&conn)?;
+            if json {
+                print_json(&projects)?;
+            } else {
+                for p in &projects {
+                    println!("{}\t{}\t{}\t{}", p.id, p.name, p.time_range, p.updated_at);
+                }
+            }
+        }
+        Command::Project(ProjectCommand::Delete { project }) => {
+            let project = find_project(&conn, &project)?;
+            crate::remove_project(&conn, &project.name)?;
+            println!("已删除项目 {}", project.name);
+        }
+        Command::Scan { project, json } => {
+            let project = find_project(&conn, &project)?;
+            let ctx = scan::ScanContext::load(&conn, &project.id)?;
+            let _lock = scan::ScanLock::acquire(&project.id)?;
+            // 扫描在工作线程中执行，事件经 channel 回到主线程统一输出
+            let (tx, rx) = std::sync::mpsc::channel();
+            let worker = std::thread::spawn(move || {
+                scan::run_scan(&tx, &ctx, &CollectorRegistry::builtin())
+            });
+            let terminal = TerminalSink::new();
+            for event in rx {
+                if json {
+                    let line = serde_json::to_string(&event).map_err(|e| e.to_string())?;
+                    println!("{}", line);
+                } else {
+                    terminal.report(event);
+                }
+            }
+            let summary = worker.join().map_err(|_| "扫描线程异常退出".to_string())?;
+            if !json {
+                terminal.finish();
+                for (collector, count) in &summary.collected {
+                    println!("{}\t{}", collector, count);
+                }
+                let issues = &summary.issues;
+                if issues.skipped + issues.failed > 0 {
+                    eprintln!("跳过 {} 个条目，失败 {} 个", issues.skipped, issues.failed);
+                    for (reason, count) in &issues.by_reason {
+                        eprintln!("  {}: {}", reason, count);
+                    }
+                }
+            }
+        }
+        Command::Results {
+            project,
+            page,
+            page_size,
+            cursor,
+            filter,
+            json,
+        } => {
+            let project = find_project(&conn, &project)?;
+            let results = crate::results::fetch(
+                &conn,
+                &project.id,
+                &crate::results::ResultQuery::simple(filter.q.as_deref(), filter.file_types()),
+                crate::results::Paging::from_args(page, cursor.as_deref()),
+                page_size,
+            )?;
+            if json {
+                print_json(&results)?;
+            } else {
+                for item in &results.items {
+                    println!(
+                        "{}\t{}\t{}\t{}",
+                        item.modified_at, item.file_type, item.size_bytes, item.file_path
+                    );
+                }
+                eprintln!(
+                    "第 {}/{} 页，共 {} 条",
+                    results.page, results.total_pages, results.total
+                );
+                if let Some(next) = &results.next_cursor {
+                    eprintln!("下一页游标: {}", next);
+                }
+            }
+        }
+        Command::Export {
+            project,
+            format,
+            tables,
+            filter,
+            output_dir,
+        } => {
+            let project = find_project(&conn, &project)?;
+            let result = crate::export::export(
+                &conn,
+                &project.id,
+                &crate::export::ExportOptions {
+                    format: &format,
+                    tables: Some(tables.as_slice()),
+                    q: filter.q.as_deref(),
+                    file_types: filter.file_types(),
+                    output_dir: output_dir.as_deref(),
+                },
+            )?;
+            for table in &result.tables {
+                eprintln!("{}: {} 行", table.table, table.rows);
+            }
+            for file in &result.files {
+                println!("{}", file);
+            }
+        }
+        Command::Report { project } => {
+            let project = find_project(&conn, &project)?;
+            let report = crate::report::generate(&conn, &project.id)?;
+            println!("{}", report.markdown_path);
+            println!("{}", report.html_path);
+        }
+    }
+    Ok(())
+}
+
+/// 按 id 或名称查找项目
+fn find_project(conn: &Connection, key: &str) -> Result<Project, String> {
+    conn.query_row(
+        &format!(
+            "SELECT {} FROM projects WHERE id = ?1 OR name = ?1",
+            PROJECT_COLUMNS
+        ),
+        [key],
+        crate::project_from_row,
+    )
+    .map_err(|_| format!("项目不存在: {}", key))
+}
+
+fn print_json<T: serde::Serialize>(value: &T) -> Result<(), String> {
+    let text = serde_json::to_string_pretty(value).map_err(|e| e.to_string())?;
+    println!("{}", text);
+    Ok(())
+}
+
+/// 终端输出：日志逐行打印；进度行（百分比、吞吐量、剩余时间）在终端中原地刷新，
+/// 重定向到文件时每 10% 打印一行
+struct TerminalSink {
+    tty: bool,
+    /// 最近一次的百分比与进度行
+    status: Mutex<(u8, String)>,
+}
+
+impl TerminalSink {
+    fn new() -> Self {
+        TerminalSink {
+            tty: std::io::stderr().is_terminal(),
+            status: Mutex::new((0, String::new())),
+        }
+    }
+
+    fn finish(&self) {
+        if self.tty {
+            eprintln!();
+        }
+    }
+}
+
+impl ProgressSink for TerminalSink {
+    fn report(&self, event: ScanEvent) {
+        let mut err = std::io::stderr().lock();
+        let mut status = match self.status.lock() {
+            Ok(s) => s,
+            Err(_) => return,
+        };
+        if let ScanEvent::Progress {
+            progress,
+            done,
+            total,
+            per_second,
+            eta_secs,
+        } = event
+        {
+            let mut line = format!(
+                "[{:>3}%] {}/{} · {:.0}/s",
+                progress, done, total, per_second
+            );
+            if let Some(eta) = eta_secs.filter(|_| progress < 100) {
+                line.push_str(&format!(" · 剩余 {}:{:02}", eta / 60, eta % 60));
+            }
+            let prev = status.0;
+            if self.tty {
+                let _ = write!(err, "\r\x1b[2K{}", line);
+                let _ = err.flush();
+            } else if progress / 10 != prev / 10 {
+                let _ = writeln!(err, "{}", line);
+            }
+            *status = (progress, line);
+            return;
+        }
+        for (_, text) in event.log_lines() {
+            if self.tty {
+                // 清掉当前的进度行，打印日志后再把进度行画回来
+                let _ = write!(err, "\r\x1b[2K{}\n{}", text, status.1);
+            } else {
+                let _ = writeln!(err, "{}", text);
+            }
+        }
+    }
+}
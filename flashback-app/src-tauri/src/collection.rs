@@ -0,0 +1,241 @@
+//! 智能集合：按项目保存的结果查询（筛选条件与排序，即 results::ResultQuery），打开时实时按条件查询
+//! 列表中的数量每次实时统计，扫描或监听更新结果后随之变化；置顶到报告的集合会在报告中单独成节
+
+#[cfg(feature = "gui")]
+use chrono::Local;
+#[cfg(feature = "gui")]
+use rusqlite::OptionalExtension;
+use rusqlite::{params, Connection};
+use serde::Serialize;
+
+use crate::results::{self, ResultQuery};
+#[cfg(feature = "gui")]
+use crate::results::{Paging, ResultQueryResponse};
+
+#[cfg(feature = "gui")]
+const TIME_FORMAT: &str = "%Y-%m-%d %H:%M:%S";
+
+pub(crate) fn init_schema(conn: &Connection) -> rusqlite::Result<()> {
+    // query 为 ResultQuery 的 JSON；pinned 表示在报告中展示
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS saved_searches (
+            id TEXT PRIMARY KEY,
+            project_id TEXT NOT NULL,
+            name TEXT NOT NULL,
+            query TEXT NOT NULL,
+            pinned INTEGER NOT NULL DEFAULT 0,
+            created_at TEXT NOT NULL,
+            updated_at TEXT NOT NULL,
+            UNIQUE(project_id, name)
+        )",
+        [],
+    )?;
+    Ok(())
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub(crate) struct SavedSearch {
+    pub id: String,
+    pub project_id: String,
+    pub name: String,
+    pub query: ResultQuery,
+    /// 置顶到报告
+    pub pinned: bool,
+    /// 当前符合条件的结果数
+    pub count: usize,
+    pub created_at: String,
+    pub updated_at: String,
+}
+
+const SAVED_SEARCH_COLUMNS: &str = "id, project_id, name, query, pinned, created_at, updated_at";
+
+fn saved_search_from_row(row: &rusqlite::Row) -> rusqlite::Result<SavedSearch> {
+    let query: String = row.get(3)?;
+    Ok(SavedSearch {
+        id: row.get(0)?,
+        project_id: row.get(1)?,
+        name: row.get(2)?,
+        query: serde_json::from_str(&query).unwrap_or_default(),
+        pinned: row.get::<_, i64>(4)? != 0,
+        count: 0,
+        created_at: row.get(5)?,
+        updated_at: row.get(6)?,
+    })
+}
+
+/// 统计集合当前的结果数
+fn with_count(conn: &Connection, mut saved: SavedSearch) -> Result<SavedSearch, String> {
+    saved.count = results::total(conn, &saved.project_id, &saved.query)?;
+    Ok(saved)
+}
+
+/// 列出项目的集合（含实时数量），按名称排序
+#[cfg(feature = "gui")]
+pub(crate) fn list(conn: &Connection, project_id: &str) -> Result<Vec<SavedSearch>, String> {
+    list_where(conn, project_id, false)
+}
+
+/// 置顶到报告的集合
+pub(crate) fn pinned(conn: &Connection, project_id: &str) -> Result<Vec<SavedSearch>, String> {
+    list_where(conn, project_id, true)
+}
+
+fn list_where(
+    conn: &Connection,
+    project_id: &str,
+    pinned_only: bool,
+) -> Result<Vec<SavedSearch>, String> {
+    let mut stmt = conn
+        .prepare(&format!(
+            "SELECT {} FROM saved_searches WHERE project_id = ?1 AND (?2 = 0 OR pinned = 1) ORDER BY name",
+            SAVED_SEARCH_COLUMNS
+        ))
+        .map_err(|e| e.to_string())?;
+    let rows = stmt
+        .query_map(
+            params![project_id, pinned_only as i64],
+            saved_search_from_row,
+        )
+        .map_err(|e| e.to_string())?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string())?;
+    rows.into_iter().map(|s| with_count(conn, s)).collect()
+}
+
+#[cfg(feature = "gui")]
+fn validate(name: &str, query: &ResultQuery) -> Result<(), String> {
+    if name.trim().is_empty() {
+        return Err("集合名称不能为空".into());
+    }
+    query.sort().map(|_| ())
+}
+
+#[cfg(feature = "gui")]
+pub(crate) fn create(
+    conn: &Connection,
+    project_id: &str,
+    name: &str,
+    query: &ResultQuery,
+    pinned: bool,
+) -> Result<SavedSearch, String> {
+    validate(name, query)?;
+    let exists: bool = conn
+        .query_row("SELECT 1 FROM projects WHERE id = ?", [project_id], |_| {
+            Ok(true)
+        })
+        .optional()
+        .map_err(|e| e.to_string())?
+        .unwrap_or(false);
+    if !exists {
+        return Err("项目不存在".into());
+    }
+    ensure_unique(conn, project_id, name, None)?;
+    let id = uuid::Uuid::new_v4().to_string();
+    let now = Local::now().format(TIME_FORMAT).to_string();
+    let query_json = serde_json::to_string(query).map_err(|e| e.to_string())?;
+    conn.execute(
+        "INSERT INTO saved_searches (id, project_id, name, query, pinned, created_at, updated_at)
+         VALUES (?, ?, ?, ?, ?, ?, ?)",
+        params![
+            id,
+            project_id,
+            name.trim(),
+            query_json,
+            pinned as i64,
+            now,
+            now
+        ],
+    )
+    .map_err(|e| e.to_string())?;
+    get(conn, &id)
+}
+
+/// 修改名称、查询条件或是否置顶，未传的字段保持不变
+#[cfg(feature = "gui")]
+pub(crate) fn update(
+    conn: &Connection,
+    id: &str,
+    name: Option<&str>,
+    query: Option<&ResultQuery>,
+    pinned: Option<bool>,
+) -> Result<SavedSearch, String> {
+    let current = get(conn, id)?;
+    let name = name.map(str::trim).unwrap_or(&current.name).to_string();
+    let query = query.unwrap_or(&current.query);
+    validate(&name, query)?;
+    ensure_unique(conn, &current.project_id, &name, Some(id))?;
+    let now = Local::now().format(TIME_FORMAT).to_string();
+    let query_json = serde_json::to_string(query).map_err(|e| e.to_string())?;
+    conn.execute(
+        "UPDATE saved_searches SET name = ?, query = ?, pinned = ?, updated_at = ? WHERE id = ?",
+        params![
+            name,
+            query_json,
+            pinned.unwrap_or(current.pinned) as i64,
+            now,
+            id
+        ],
+    )
+    .map_err(|e| e.to_string())?;
+    get(conn, id)
+}
+
+#[cfg(feature = "gui")]
+pub(crate) fn delete(conn: &Connection, id: &str) -> Result<(), String> {
+    let changed = conn
+        .execute("DELETE FROM saved_searches WHERE id = ?", [id])
+        .map_err(|e| e.to_string())?;
+    if changed == 0 {
+        return Err("集合不存在".into());
+    }
+    Ok(())
+}
+
+/// 按集合保存的条件查询结果，分页方式与 query_scan_results 相同
+#[cfg(feature = "gui")]
+pub(crate) fn open(
+    conn: &Connection,
+    id: &str,
+    paging: Paging,
+    page_size: usize,
+) -> Result<ResultQueryResponse, String> {
+    let saved = get(conn, id)?;
+    results::query(conn, &saved.project_id, &saved.query, paging, page_size)
+}
+
+#[cfg(feature = "gui")]
+fn ensure_unique(
+    conn: &Connection,
+    project_id: &str,
+    name: &str,
+    except_id: Option<&str>,
+) -> Result<(), String> {
+    let taken: bool = conn
+        .query_row(
+            "SELECT 1 FROM saved_searches WHERE project_id = ?1 AND name = ?2 AND (?3 IS NULL OR id != ?3)",
+            params![project_id, name.trim(), except_id],
+            |_| Ok(true),
+        )
+        .optional()
+        .map_err(|e| e.to_string())?
+        .unwrap_or(false);
+    if taken {
+        return Err(format!("集合名称已存在: {}", name.trim()));
+    }
+    Ok(())
+}
+
+#[cfg(feature = "gui")]
+fn get(conn: &Connection, id: &str) -> Result<SavedSearch, String> {
+    let saved = conn
+        .query_row(
+            &format!(
+                "SELECT {} FROM saved_searches WHERE id = ?",
+                SAVED_SEARCH_COLUMNS
+            ),
+            [id],
+            saved_search_from_row,
+        )
+        .map_err(|_| "集合不存在".to_string())?;
+    with_count(conn, saved)
+}
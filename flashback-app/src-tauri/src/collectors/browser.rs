@@ -0,0 +1,419 @@
+//! 浏览器历史：读取本机 Chrome / Chromium / Edge / Firefox 的历史数据库
+//! 浏览器运行时会锁住数据库，因此先复制到临时目录再读取
+
+use rusqlite::Connection;
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use uuid::Uuid;
+
+use super::{Collector, DataSource, TimelineSpec};
+use crate::scan::{home, EntryError, ScanContext, ScanRun};
+use crate::ScanSummary;
+
+/// Chrome 时间戳起点（1601-01-01）与 Unix 纪元之间的秒数
+const CHROME_EPOCH_OFFSET_SECS: i64 = 11_644_473_600;
+
+/// 项目级配置（collector_settings 中 id 为 browser_history 的一项）
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+struct BrowserSettings {
+    /// 非空时只保留这些域名（含子域名）
+    include_domains: Vec<String>,
+    /// 排除的域名（含子域名），优先于 include_domains
+    exclude_domains: Vec<String>,
+}
+
+impl BrowserSettings {
+    fn allows(&self, domain: &str) -> bool {
+        let matches = |pattern: &String| {
+            let pattern = pattern.trim().trim_start_matches('.').to_ascii_lowercase();
+            !pattern.is_empty() && (domain == pattern || domain.ends_with(&format!(".{}", pattern)))
+        };
+        if self.exclude_domains.iter().any(matches) {
+            return false;
+        }
+        self.include_domains.is_empty() || self.include_domains.iter().any(matches)
+    }
+}
+
+/// 同一 URL 在时间范围内的访问汇总
+struct UrlVisits {
+    title: String,
+    domain: String,
+    visit_times: Vec<String>,
+}
+
+pub(crate) struct BrowserHistoryCollector;
+
+impl Collector for BrowserHistoryCollector {
+    fn id(&self) -> &'static str {
+        "browser_history"
+    }
+
+    fn label(&self) -> &'static str {
+        "浏览器历史"
+    }
+
+    /// 涉及隐私，需在项目中手动启用
+    fn enabled_by_default(&self) -> bool {
+        false
+    }
+
+    fn init_schema(&self, conn: &Connection) -> rusqlite::Result<()> {
+        conn.execute(
+            "CREATE TABLE IF NOT EXISTS browser_history (
+                id TEXT PRIMARY KEY,
+                project_id TEXT NOT NULL,
+                browser TEXT NOT NULL,
+                profile TEXT NOT NULL,
+                url TEXT NOT NULL,
+                title TEXT NOT NULL,
+                domain TEXT NOT NULL,
+                visit_count INTEGER NOT NULL,
+                first_visit_at TEXT NOT NULL,
+                last_visit_at TEXT NOT NULL,
+                visit_times TEXT NOT NULL,
+                inserted_at TEXT NOT NULL,
+                UNIQUE(project_id, browser, profile, url)
+            )",
+            [],
+        )?;
+        conn.execute(
+            "CREATE INDEX IF NOT EXISTS idx_browser_history_project ON browser_history(project_id, last_visit_at)",
+            [],
+        )?;
+        Ok(())
+    }
+
+    fn project_tables(&self) -> &'static [&'static str] {
+        &["browser_history"]
+    }
+
+    fn timeline(&self) -> Option<TimelineSpec> {
+        Some(TimelineSpec {
+            table: "browser_history",
+            time_column: "last_visit_at",
+            title_expr: "CASE WHEN title = '' THEN url ELSE title END",
+            detail_expr: "domain",
+            weight_expr: "visit_count",
+        })
+    }
+
+    fn discover(&self, _ctx: &ScanContext, scan: &mut ScanRun) -> Vec<DataSource> {
+        let mut found = Vec::new();
+        for (browser, user_data) in chromium_user_data_dirs() {
+            for profile in list_dirs(&user_data, scan) {
+                let history = profile.join("History");
+                if history.is_file() {
+                    found.push(DataSource::new(history, browser));
+                }
+            }
+        }
+        for profiles in firefox_profile_dirs() {
+            for profile in list_dirs(&profiles, scan) {
+                let places = profile.join("places.sqlite");
+                if places.is_file() {
+                    found.push(DataSource::new(places, "Firefox"));
+                }
+            }
+        }
+        found
+    }
+
+    fn collect(
+        &self,
+        ctx: &ScanContext,
+        sources: &[DataSource],
+        scan: &mut ScanRun,
+        _summary: &mut ScanSummary,
+    ) -> usize {
+        let mut conn = match ctx.open_db() {
+            Some(c) => c,
+            None => return 0,
+        };
+        // 清空旧结果（本次全量重扫）
+        let _ = conn.execute(
+            "DELETE FROM browser_history WHERE project_id = ? AND archived = 0",
+            [&ctx.project_id],
+        );
+
+        let settings: BrowserSettings = ctx.collector_settings(self.id());
+        let since_micros = ctx.since().map(|t| t.timestamp_micros()).unwrap_or(0);
+        let mut total = 0usize;
+        for (idx, source) in sources.iter().enumerate() {
+            let profile = source
+                .path
+                .parent()
+                .and_then(|p| p.file_name())
+                .map(|s| s.to_string_lossy().to_string())
+                .unwrap_or_default();
+            let visits = if source.kind == "Firefox" {
+                read_history(&source.path, FIREFOX_QUERY, since_micros, |t| t)
+            } else {
+                read_history(
+                    &source.path,
+                    CHROMIUM_QUERY,
+                    since_micros + CHROME_EPOCH_OFFSET_SECS * 1_000_000,
+                    |t| t - CHROME_EPOCH_OFFSET_SECS * 1_000_000,
+                )
+            };
+            match visits {
+                Ok(visits) => {
+                    let visits: BTreeMap<String, UrlVisits> = visits
+                        .into_iter()
+                        .filter(|(_, v)| settings.allows(&v.domain))
+                        .collect();
+                    let n = save_visits(&mut conn, ctx, &source.kind, &profile, &visits);
+                    scan.file_found(&source.path, Some(n));
+                    total += n;
+                }
+                Err(e) => scan.issue(Some(&source.path), e),
+            }
+            scan.progress(idx + 1, sources.len());
+        }
+        total
+    }
+}
+
+const CHROMIUM_QUERY: &str = "SELECT u.url, u.title, v.visit_time FROM visits v JOIN urls u ON u.id = v.url WHERE v.visit_time >= ? ORDER BY v.visit_time";
+const FIREFOX_QUERY: &str = "SELECT p.url, p.title, v.visit_date FROM moz_historyvisits v JOIN moz_places p ON p.id = v.place_id WHERE v.visit_date >= ? ORDER BY v.visit_date";
+
+/// 复制历史库（连同 -wal 日志）到临时目录后读取，按 URL 聚合访问时间；
+/// to_unix_micros 把浏览器自身的时间戳换算为 Unix 微秒
+fn read_history(
+    db_path: &Path,
+    query: &str,
+    since: i64,
+    to_unix_micros: impl Fn(i64) -> i64,
+) -> Result<BTreeMap<String, UrlVisits>, EntryError> {
+    let tmp = std::env::temp_dir().join(format!("flashback-history-{}.sqlite", Uuid::new_v4()));
+    std::fs::copy(db_path, &tmp).map_err(|e| EntryError::io(&e, db_path))?;
+    let wal = PathBuf::from(format!("{}-wal", db_path.to_string_lossy()));
+    let tmp_wal = PathBuf::from(format!("{}-wal", tmp.to_string_lossy()));
+    if wal.is_file() {
+        let _ = std::fs::copy(&wal, &tmp_wal);
+    }
+
+    let result = (|| {
+        // 临时副本可写，便于 SQLite 合并 -wal 日志
+        let conn = Connection::open(&tmp)?;
+        let mut stmt = conn.prepare(query)?;
+        let rows = stmt.query_map([since], |row| {
+            Ok((
+                row.get::<_, String>(0)?,
+                row.get::<_, Option<String>>(1)?.unwrap_or_default(),
+                row.get::<_, i64>(2)?,
+            ))
+        })?;
+
+        let mut visits: BTreeMap<String, UrlVisits> = BTreeMap::new();
+        for row in rows {
+            let (url, title, ts) = row?;
+            let domain = match url_domain(&url) {
+                Some(d) => d,
+                None => continue,
+            };
+            let time = match chrono::DateTime::from_timestamp_micros(to_unix_micros(ts)) {
+                Some(t) => t
+                    .with_timezone(&chrono::Local)
+                    .format("%Y-%m-%d %H:%M:%S")
+                    .to_string(),
+                None => continue,
+            };
+            let entry = visits.entry(url).or_insert_with(|| UrlVisits {
+                title: String::new(),
+                domain,
+                visit_times: Vec::new(),
+            });
+            if !title.is_empty() {
+                entry.title = title;
+            }
+            entry.visit_times.push(time);
+        }
+        Ok(visits)
+    })();
+
+    let _ = std::fs::remove_file(&tmp);
+    let _ = std::fs::remove_file(&tmp_wal);
+    let _ = std::fs::remove_file(format!("{}-shm", tmp.to_string_lossy()));
+    result
+}
+
+fn save_visits(
+    conn: &mut Connection,
+    ctx: &ScanContext,
+    browser: &str,
+    profile: &str,
+    visits: &BTreeMap<String, UrlVisits>,
+) -> usize {
+    let tx = match conn.transaction() {
+        Ok(tx) => tx,
+        Err(_) => return 0,
+    };
+    let now_s = chrono::Local::now().format("%Y-%m-%d %H:%M:%S").to_string();
+    let mut saved = 0usize;
+    for (url, v) in visits {
+        let (first, last) = match (v.visit_times.first(), v.visit_times.last()) {
+            (Some(f), Some(l)) => (f, l),
+            _ => continue,
+        };
+        let inserted = tx.execute(
+            "INSERT INTO browser_history (id, project_id, browser, profile, url, title, domain, visit_count, first_visit_at, last_visit_at, visit_times, inserted_at)
+             VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
+             ON CONFLICT(project_id, browser, profile, url) DO UPDATE SET
+                title = excluded.title, domain = excluded.domain, visit_count = excluded.visit_count,
+                first_visit_at = excluded.first_visit_at, last_visit_at = excluded.last_visit_at,
+                visit_times = excluded.visit_times",
+            rusqlite::params![
+                Uuid::new_v4().to_string(),
+                ctx.project_id,
+                browser,
+                profile,
+                url,
+                v.title,
+                v.domain,
+                v.visit_times.len() as i64,
+                first,
+                last,
+                serde_json::to_string(&v.visit_times).unwrap_or_else(|_| "[]".into()),
+                now_s
+            ],
+        );
+        if inserted.is_ok() {
+            saved += 1;
+        }
+    }
+    let _ = tx.commit();
+    saved
+}
+
+/// 提取 http(s) URL 的主机名（小写、去端口与用户信息）；其他协议返回 None
+fn url_domain(url: &str) -> Option<String> {
+    let rest = url
+        .strip_prefix("https://")
+        .or_else(|| url.strip_prefix("http://"))?;
+    let authority = rest.split(['/', '?', '#']).next()?;
+    let host = authority.rsplit('@').next()?;
+    let host = if host.starts_with('[') {
+        host.split(']').next()?.trim_start_matches('[')
+    } else {
+        host.split(':').next()?
+    };
+    if host.is_empty() {
+        None
+    } else {
+        Some(host.to_ascii_lowercase())
+    }
+}
+
+/// 列出子目录；目录不存在（未安装该浏览器）时返回空，读取目录项失败时记录问题
+fn list_dirs(dir: &Path, scan: &mut ScanRun) -> Vec<PathBuf> {
+    let rd = match std::fs::read_dir(dir) {
+        Ok(rd) => rd,
+        Err(_) => return vec![],
+    };
+    let mut dirs = Vec::new();
+    for entry in rd {
+        match entry {
+            Ok(e) if e.path().is_dir() => dirs.push(e.path()),
+            Ok(_) => {}
+            Err(e) => scan.issue(Some(dir), EntryError::io(&e, dir)),
+        }
+    }
+    dirs
+}
+
+/// Chromium 系浏览器的 User Data 目录（其下每个子目录是一个 profile）
+fn chromium_user_data_dirs() -> Vec<(&'static str, PathBuf)> {
+    let mut dirs = Vec::new();
+    if cfg!(target_os = "macos") {
+        let base = home().join("Library/Application Support");
+        dirs.push(("Chrome", base.join("Google/Chrome")));
+        dirs.push(("Chromium", base.join("Chromium")));
+        dirs.push(("Edge", base.join("Microsoft Edge")));
+    }
+    if cfg!(target_os = "windows") {
+        if let Ok(local) = std::env::var("LOCALAPPDATA") {
+            let base = PathBuf::from(local);
+            dirs.push(("Chrome", base.join("Google/Chrome/User Data")));
+            dirs.push(("Chromium", base.join("Chromium/User Data")));
+            dirs.push(("Edge", base.join("Microsoft/Edge/User Data")));
+        }
+    }
+    if cfg!(target_os = "linux") {
+        let base = home().join(".config");
+        dirs.push(("Chrome", base.join("google-chrome")));
+        dirs.push(("Chromium", base.join("chromium")));
+        dirs.push(("Edge", base.join("microsoft-edge")));
+    }
+    dirs
+}
+
+/// Firefox 的 Profiles 目录（其下每个子目录是一个 profile）
+fn firefox_profile_dirs() -> Vec<PathBuf> {
+    let mut dirs = Vec::new();
+    if cfg!(target_os = "macos") {
+        dirs.push(home().join("Library/Application Support/Firefox/Profiles"));
+    }
+    if cfg!(target_os = "windows") {
+        if let Ok(appdata) = std::env::var("APPDATA") {
+            dirs.push(PathBuf::from(appdata).join("Mozilla/Firefox/Profiles"));
+        }
+    }
+    if cfg!(target_os = "linux") {
+        dirs.push(home().join(".mozilla/firefox"));
+    }
+    dirs
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn url_domain_extracts_host() {
+        assert_eq!(
+            url_domain("https://Docs.Example.com/a?b#c").as_deref(),
+            Some("docs.example.com")
+        );
+        assert_eq!(
+            url_domain("http://user:pw@example.com:8080/x").as_deref(),
+            Some("example.com")
+        );
+        assert_eq!(url_domain("https://[::1]:3000/").as_deref(), Some("::1"));
+        assert_eq!(
+            url_domain("https://example.com?q=a@b").as_deref(),
+            Some("example.com")
+        );
+        assert_eq!(url_domain("https://"), None);
+        assert_eq!(url_domain("file:///etc/hosts"), None);
+        assert_eq!(url_domain("chrome://settings"), None);
+    }
+
+    #[test]
+    fn domain_filter() {
+        let all = BrowserSettings::default();
+        assert!(all.allows("example.com"));
+
+        let settings = BrowserSettings {
+            include_domains: vec![".Example.com".into(), " ".into()],
+            exclude_domains: vec!["mail.example.com".into()],
+        };
+        assert!(settings.allows("example.com"));
+        assert!(settings.allows("docs.example.com"));
+        // 排除优先于包含，且对子域名生效
+        assert!(!settings.allows("mail.example.com"));
+        assert!(!settings.allows("a.mail.example.com"));
+        // 只按完整的域名段匹配
+        assert!(!settings.allows("badexample.com"));
+        assert!(!settings.allows("other.org"));
+
+        let deny_only = BrowserSettings {
+            include_domains: vec![],
+            exclude_domains: vec!["ads.net".into()],
+        };
+        assert!(deny_only.allows("example.com"));
+        assert!(!deny_only.allows("x.ads.net"));
+    }
+}
@@ -0,0 +1,875 @@
+//! 会议记录：导入 iCalendar (.ics) 文件，按时间范围展开重复事件后写入 meetings 表
+//! 来源为扫描根目录下发现的 .ics 文件，以及用户手动选择的文件（保存在采集器配置 paths 中）
+//! 带 TZID 的本地时间按本机时区处理；以 Z 结尾的 UTC 时间换算为本机时间
+
+use chrono::{Datelike, Duration, NaiveDate, NaiveDateTime, TimeZone, Weekday};
+use rusqlite::Connection;
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+use uuid::Uuid;
+
+use super::{Collector, DataSource, TimelineSpec};
+use crate::scan::{EntryError, ScanContext, ScanRun};
+use crate::walk::WalkItem;
+use crate::ScanSummary;
+
+/// 单个重复规则最多展开的次数，防止无 UNTIL/COUNT 的规则无限展开
+const MAX_OCCURRENCES: usize = 100_000;
+
+/// 项目级配置（collector_settings 中 id 为 calendar 的一项）
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[serde(default)]
+struct CalendarSettings {
+    /// 用户手动选择的 .ics 文件
+    paths: Vec<String>,
+}
+
+pub(crate) struct CalendarCollector;
+
+impl Collector for CalendarCollector {
+    fn id(&self) -> &'static str {
+        "calendar"
+    }
+
+    fn label(&self) -> &'static str {
+        "会议记录"
+    }
+
+    fn init_schema(&self, conn: &Connection) -> rusqlite::Result<()> {
+        conn.execute(
+            "CREATE TABLE IF NOT EXISTS meetings (
+                id TEXT PRIMARY KEY,
+                project_id TEXT NOT NULL,
+                uid TEXT NOT NULL,
+                title TEXT NOT NULL,
+                start_at TEXT NOT NULL,
+                end_at TEXT NOT NULL,
+                all_day INTEGER NOT NULL,
+                attendees TEXT NOT NULL,
+                location TEXT NOT NULL,
+                description TEXT NOT NULL,
+                source_path TEXT NOT NULL,
+                inserted_at TEXT NOT NULL,
+                UNIQUE(project_id, uid, start_at)
+            )",
+            [],
+        )?;
+        conn.execute(
+            "CREATE INDEX IF NOT EXISTS idx_meetings_project ON meetings(project_id, start_at)",
+            [],
+        )?;
+        Ok(())
+    }
+
+    fn project_tables(&self) -> &'static [&'static str] {
+        &["meetings"]
+    }
+
+    fn timeline(&self) -> Option<TimelineSpec> {
+        Some(TimelineSpec {
+            table: "meetings",
+            time_column: "start_at",
+            title_expr: "title",
+            detail_expr: "location",
+            weight_expr: "(julianday(end_at) - julianday(start_at)) * 1440",
+        })
+    }
+
+    /// 用户选择的文件与扫描根目录；根目录下的 .ics 在采集阶段边遍历边导入，遍历计入进度
+    fn discover(&self, ctx: &ScanContext, _scan: &mut ScanRun) -> Vec<DataSource> {
+        let settings: CalendarSettings = ctx.collector_settings(self.id());
+        let mut found: Vec<DataSource> = settings
+            .paths
+            .iter()
+            .map(PathBuf::from)
+            .filter(|p| p.is_file())
+            .map(|p| DataSource::new(p, "user"))
+            .collect();
+        for root in ctx.roots.iter().filter(|p| p.exists()) {
+            found.push(DataSource::new(root.clone(), "root"));
+        }
+        found
+    }
+
+    fn estimate(&self, _ctx: &ScanContext, sources: &[DataSource], scan: &mut ScanRun) -> usize {
+        sources
+            .iter()
+            .map(|s| match s.kind.as_str() {
+                "root" => scan.count_files(&s.path),
+                _ => 1,
+            })
+            .sum()
+    }
+
+    fn collect(
+        &self,
+        ctx: &ScanContext,
+        sources: &[DataSource],
+        scan: &mut ScanRun,
+        _summary: &mut ScanSummary,
+    ) -> usize {
+        let mut conn = match ctx.open_db() {
+            Some(c) => c,
+            None => return 0,
+        };
+        // 清空旧结果（本次全量重扫）
+        let _ = conn.execute(
+            "DELETE FROM meetings WHERE project_id = ? AND archived = 0",
+            [&ctx.project_id],
+        );
+
+        let total = self.estimate(ctx, sources, scan);
+        let mut done = 0usize;
+        let mut meetings = 0usize;
+        // 用户选择的文件可能也在扫描根目录下，只导入一次
+        let mut seen: HashSet<PathBuf> = HashSet::new();
+        let mut import = |path: &Path, scan: &mut ScanRun| {
+            if !seen.insert(path.to_path_buf()) {
+                return;
+            }
+            match import_file(&mut conn, ctx, path) {
+                Ok(n) => {
+                    scan.file_found(path, Some(n));
+                    meetings += n;
+                }
+                Err(e) => scan.issue(Some(path), e),
+            }
+        };
+        for source in sources {
+            if source.kind != "root" {
+                import(&source.path, scan);
+                done += 1;
+                scan.progress(done, total);
+                continue;
+            }
+            for item in scan.walk(&source.path) {
+                let entry = match item {
+                    WalkItem::Entry(e) => e,
+                    WalkItem::Error(e) => {
+                        let path = e.path().map(Path::to_path_buf);
+                        scan.issue(path.as_deref(), e.into());
+                        continue;
+                    }
+                    WalkItem::Pruned(rule, path) => {
+                        scan.pruned(rule, &path);
+                        continue;
+                    }
+                };
+                if entry.file_type().is_dir() {
+                    continue;
+                }
+                done += 1;
+                scan.progress(done, total);
+                if is_ics(entry.path()) {
+                    import(entry.path(), scan);
+                }
+            }
+        }
+        meetings
+    }
+}
+
+fn is_ics(path: &Path) -> bool {
+    path.extension()
+        .and_then(|s| s.to_str())
+        .map(|s| s.eq_ignore_ascii_case("ics"))
+        .unwrap_or(false)
+}
+
+/// 用户手动选择的 .ics 文件：立即导入，并记入采集器配置以便重扫时保留
+#[cfg(feature = "gui")]
+pub(crate) fn import_user_files(
+    conn: &mut Connection,
+    project_id: &str,
+    paths: &[String],
+) -> Result<usize, String> {
+    let ctx = ScanContext::load(conn, project_id)?;
+    let mut settings: CalendarSettings = ctx.collector_settings(CalendarCollector.id());
+    let mut total = 0usize;
+    for p in paths {
+        total += import_file(conn, &ctx, Path::new(p))
+            .map_err(|e| format!("导入日历失败: {} ({})", p, e))?;
+        if !settings.paths.contains(p) {
+            settings.paths.push(p.clone());
+        }
+    }
+    let now_s = chrono::Local::now().format("%Y-%m-%d %H:%M:%S").to_string();
+    conn.execute(
+        "INSERT INTO collector_settings(project_id, collector_id, settings, updated_at) VALUES(?,?,?,?)
+         ON CONFLICT(project_id, collector_id) DO UPDATE SET settings=excluded.settings, updated_at=excluded.updated_at",
+        rusqlite::params![
+            project_id,
+            CalendarCollector.id(),
+            serde_json::to_string(&settings).map_err(|e| e.to_string())?,
+            now_s
+        ],
+    )
+    .map_err(|e| e.to_string())?;
+    Ok(total)
+}
+
+/// 解析一个 .ics 文件并把时间范围内的会议写入 meetings，返回写入条数
+fn import_file(conn: &mut Connection, ctx: &ScanContext, path: &Path) -> Result<usize, EntryError> {
+    let text = std::fs::read_to_string(path).map_err(|e| EntryError::io(&e, path))?;
+    let range_end = chrono::Local::now().naive_local();
+    let range_start = ctx
+        .since()
+        .map(|t| t.naive_local())
+        .unwrap_or(NaiveDateTime::MIN);
+    let meetings = expand_events(&parse_events(&text), range_start, range_end);
+
+    let tx = conn.transaction()?;
+    let now_s = chrono::Local::now().format("%Y-%m-%d %H:%M:%S").to_string();
+    let source_path = path.to_string_lossy().to_string();
+    let mut saved = 0usize;
+    for m in &meetings {
+        let inserted = tx.execute(
+            "INSERT INTO meetings (id, project_id, uid, title, start_at, end_at, all_day, attendees, location, description, source_path, inserted_at)
+             VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
+             ON CONFLICT(project_id, uid, start_at) DO UPDATE SET
+                title = excluded.title, end_at = excluded.end_at, all_day = excluded.all_day,
+                attendees = excluded.attendees, location = excluded.location,
+                description = excluded.description, source_path = excluded.source_path",
+            rusqlite::params![
+                Uuid::new_v4().to_string(),
+                ctx.project_id,
+                m.uid,
+                m.title,
+                m.start.format("%Y-%m-%d %H:%M:%S").to_string(),
+                m.end.format("%Y-%m-%d %H:%M:%S").to_string(),
+                if m.all_day { 1i64 } else { 0i64 },
+                serde_json::to_string(&m.attendees).unwrap_or_else(|_| "[]".into()),
+                m.location,
+                m.description,
+                source_path,
+                now_s
+            ],
+        );
+        if inserted.is_ok() {
+            saved += 1;
+        }
+    }
+    tx.commit()?;
+    Ok(saved)
+}
+
+// ==================== iCalendar 解析 ====================
+
+#[derive(Debug, Clone, Default)]
+struct Event {
+    uid: String,
+    title: String,
+    start: Option<NaiveDateTime>,
+    end: Option<NaiveDateTime>,
+    duration: Option<Duration>,
+    all_day: bool,
+    attendees: Vec<String>,
+    location: String,
+    description: String,
+    rrule: Option<String>,
+    exdates: Vec<NaiveDateTime>,
+    recurrence_id: Option<NaiveDateTime>,
+}
+
+/// 展开后的一场会议
+#[derive(Debug, Clone)]
+struct Meeting {
+    uid: String,
+    title: String,
+    start: NaiveDateTime,
+    end: NaiveDateTime,
+    all_day: bool,
+    attendees: Vec<String>,
+    location: String,
+    description: String,
+}
+
+/// 折叠行还原：以空格或制表符开头的行是上一行的续行
+fn unfold_lines(text: &str) -> Vec<String> {
+    let mut lines: Vec<String> = Vec::new();
+    for raw in text.lines() {
+        let raw = raw.trim_end_matches('\r');
+        if let Some(cont) = raw.strip_prefix([' ', '\t']) {
+            if let Some(last) = lines.last_mut() {
+                last.push_str(cont);
+                continue;
+            }
+        }
+        lines.push(raw.to_string());
+    }
+    lines
+}
+
+/// 一行属性：NAME;PARAM=VALUE;...:value
+struct Property {
+    name: String,
+    params: Vec<(String, String)>,
+    value: String,
+}
+
+/// 拆分属性行（参数值可带引号）
+fn split_property(line: &str) -> Option<Property> {
+    let mut in_quotes = false;
+    let mut colon = None;
+    for (i, c) in line.char_indices() {
+        match c {
+            '"' => in_quotes = !in_quotes,
+            ':' if !in_quotes => {
+                colon = Some(i);
+                break;
+            }
+            _ => {}
+        }
+    }
+    let colon = colon?;
+    let (head, value) = (&line[..colon], &line[colon + 1..]);
+    let mut parts = head.split(';');
+    let name = parts.next()?.trim().to_ascii_uppercase();
+    let params = parts
+        .filter_map(|p| {
+            let (k, v) = p.split_once('=')?;
+            Some((
+                k.trim().to_ascii_uppercase(),
+                v.trim_matches('"').to_string(),
+            ))
+        })
+        .collect();
+    Some(Property {
+        name,
+        params,
+        value: value.to_string(),
+    })
+}
+
+fn param<'a>(params: &'a [(String, String)], key: &str) -> Option<&'a str> {
+    params
+        .iter()
+        .find(|(k, _)| k == key)
+        .map(|(_, v)| v.as_str())
+}
+
+fn unescape_text(s: &str) -> String {
+    let mut out = String::with_capacity(s.len());
+    let mut chars = s.chars();
+    while let Some(c) = chars.next() {
+        if c == '\\' {
+            match chars.next() {
+                Some('n') | Some('N') => out.push('\n'),
+                Some(other) => out.push(other),
+                None => {}
+            }
+        } else {
+            out.push(c);
+        }
+    }
+    out
+}
+
+/// 解析日期/时间，返回 (本地时间, 是否全天)
+fn parse_datetime(value: &str, params: &[(String, String)]) -> Option<(NaiveDateTime, bool)> {
+    let value = value.trim();
+    if param(params, "VALUE") == Some("DATE") || value.len() == 8 {
+        let d = NaiveDate::parse_from_str(value, "%Y%m%d").ok()?;
+        return Some((d.and_hms_opt(0, 0, 0)?, true));
+    }
+    if let Some(utc) = value.strip_suffix('Z') {
+        let naive = NaiveDateTime::parse_from_str(utc, "%Y%m%dT%H%M%S").ok()?;
+        let local = chrono::Utc
+            .from_utc_datetime(&naive)
+            .with_timezone(&chrono::Local);
+        return Some((local.naive_local(), false));
+    }
+    let naive = NaiveDateTime::parse_from_str(value, "%Y%m%dT%H%M%S").ok()?;
+    Some((naive, false))
+}
+
+/// 解析 ISO 8601 时长，如 PT1H30M、P1D、P2W
+fn parse_duration(value: &str) -> Option<Duration> {
+    let (negative, value) = match value.strip_prefix('-') {
+        Some(v) => (true, v),
+        None => (false, value.strip_prefix('+').unwrap_or(value)),
+    };
+    let mut rest = value.strip_prefix('P')?;
+    let mut total = Duration::zero();
+    let mut in_time = false;
+    while !rest.is_empty() {
+        if let Some(r) = rest.strip_prefix('T') {
+            in_time = true;
+            rest = r;
+            continue;
+        }
+        let digits = rest.chars().take_while(|c| c.is_ascii_digit()).count();
+        let n: i64 = rest[..digits].parse().ok()?;
+        let unit = rest[digits..].chars().next()?;
+        total += match (unit, in_time) {
+            ('W', false) => Duration::weeks(n),
+            ('D', false) => Duration::days(n),
+            ('H', true) => Duration::hours(n),
+            ('M', true) => Duration::minutes(n),
+            ('S', true) => Duration::seconds(n),
+            _ => return None,
+        };
+        rest = &rest[digits + unit.len_utf8()..];
+    }
+    Some(if negative { -total } else { total })
+}
+
+fn parse_events(text: &str) -> Vec<Event> {
+    let mut events = Vec::new();
+    let mut current: Option<Event> = None;
+    // VEVENT 内嵌的 VALARM 等子组件直接跳过
+    let mut nested = 0usize;
+    for line in unfold_lines(text) {
+        let Property {
+            name,
+            params,
+            value,
+        } = match split_property(&line) {
+            Some(p) => p,
+            None => continue,
+        };
+        match (name.as_str(), value.trim().to_ascii_uppercase().as_str()) {
+            ("BEGIN", "VEVENT") => {
+                current = Some(Event::default());
+                nested = 0;
+                continue;
+            }
+            ("END", "VEVENT") => {
+                if let Some(ev) = current.take() {
+                    if ev.start.is_some() {
+                        events.push(ev);
+                    }
+                }
+                continue;
+            }
+            ("BEGIN", _) if current.is_some() => {
+                nested += 1;
+                continue;
+            }
+            ("END", _) if current.is_some() => {
+                nested = nested.saturating_sub(1);
+                continue;
+            }
+            _ => {}
+        }
+        let ev = match current.as_mut() {
+            Some(ev) if nested == 0 => ev,
+            _ => continue,
+        };
+        match name.as_str() {
+            "UID" => ev.uid = value.trim().to_string(),
+            "SUMMARY" => ev.title = unescape_text(&value),
+            "LOCATION" => ev.location = unescape_text(&value),
+            "DESCRIPTION" => ev.description = unescape_text(&value),
+            "DTSTART" => {
+                if let Some((t, all_day)) = parse_datetime(&value, &params) {
+                    ev.start = Some(t);
+                    ev.all_day = all_day;
+                }
+            }
+            "DTEND" => ev.end = parse_datetime(&value, &params).map(|(t, _)| t),
+            "DURATION" => ev.duration = parse_duration(value.trim()),
+            "RRULE" => ev.rrule = Some(value.trim().to_string()),
+            "EXDATE" => ev.exdates.extend(
+                value
+                    .split(',')
+                    .filter_map(|v| parse_datetime(v, &params).map(|(t, _)| t)),
+            ),
+            "RECURRENCE-ID" => ev.recurrence_id = parse_datetime(&value, &params).map(|(t, _)| t),
+            "ATTENDEE" => {
+                let who = param(&params, "CN")
+                    .map(|s| s.to_string())
+                    .unwrap_or_else(|| {
+                        let v = value.trim();
+                        v.strip_prefix("mailto:")
+                            .or_else(|| v.strip_prefix("MAILTO:"))
+                            .unwrap_or(v)
+                            .to_string()
+                    });
+                if !who.is_empty() {
+                    ev.attendees.push(who);
+                }
+            }
+            _ => {}
+        }
+    }
+    events
+}
+
+// ==================== 重复规则展开 ====================
+
+#[derive(Debug, Default)]
+struct RRule {
+    freq: String,
+    interval: i64,
+    count: Option<usize>,
+    until: Option<NaiveDateTime>,
+    /// (序号, 星期)，序号 0 表示不限，负数表示倒数；序号限制在 -5..=5（一个月最多 5 个同名星期）
+    by_day: Vec<(i64, Weekday)>,
+}
+
+fn parse_weekday(s: &str) -> Option<Weekday> {
+    Some(match s {
+        "MO" => Weekday::Mon,
+        "TU" => Weekday::Tue,
+        "WE" => Weekday::Wed,
+        "TH" => Weekday::Thu,
+        "FR" => Weekday::Fri,
+        "SA" => Weekday::Sat,
+        "SU" => Weekday::Sun,
+        _ => return None,
+    })
+}
+
+fn parse_rrule(rule: &str) -> Option<RRule> {
+    let mut r = RRule {
+        interval: 1,
+        ..Default::default()
+    };
+    for part in rule.split(';') {
+        let (k, v) = match part.split_once('=') {
+            Some(kv) => kv,
+            None => continue,
+        };
+        match k.to_ascii_uppercase().as_str() {
+            "FREQ" => r.freq = v.to_ascii_uppercase(),
+            "INTERVAL" => r.interval = v.parse().unwrap_or(1).max(1),
+            "COUNT" => r.count = v.parse().ok(),
+            "UNTIL" => r.until = parse_datetime(v, &[]).map(|(t, _)| t),
+            "BYDAY" => {
+                r.by_day = v
+                    .split(',')
+                    .filter_map(|d| {
+                        let d = d.trim().to_ascii_uppercase();
+                        let split = d.len().checked_sub(2)?;
+                        // 非 ASCII 的值按字节切分会落在字符中间，用 get 而不是下标
+                        let n = if split == 0 {
+                            0
+                        } else {
+                            d.get(..split)?.parse::<i64>().ok()?.clamp(-5, 5)
+                        };
+                        Some((n, parse_weekday(d.get(split..)?)?))
+                    })
+                    .collect()
+            }
+            _ => {}
+        }
+    }
+    if r.freq.is_empty() {
+        None
+    } else {
+        Some(r)
+    }
+}
+
+fn add_months(t: NaiveDateTime, months: i64) -> Option<NaiveDateTime> {
+    let total = t.year() as i64 * 12 + t.month0() as i64 + months;
+    let (year, month0) = (total.div_euclid(12) as i32, total.rem_euclid(12) as u32);
+    NaiveDate::from_ymd_opt(year, month0 + 1, t.day()).map(|d| d.and_time(t.time()))
+}
+
+/// 某月第 n 个（负数为倒数第 n 个）星期 weekday
+fn nth_weekday_of_month(year: i32, month: u32, n: i64, weekday: Weekday) -> Option<NaiveDate> {
+    if n > 0 {
+        NaiveDate::from_weekday_of_month_opt(year, month, weekday, n as u8)
+    } else {
+        let (ny, nm) = if month == 12 {
+            (year + 1, 1)
+        } else {
+            (year, month + 1)
+        };
+        let mut d = NaiveDate::from_ymd_opt(ny, nm, 1)?.pred_opt()?;
+        while d.weekday() != weekday {
+            d = d.pred_opt()?;
+        }
+        d.checked_sub_days(chrono::Days::new(((-n - 1) * 7) as u64))
+            .filter(|d| d.month() == month)
+    }
+}
+
+/// 按规则生成某一周期内的候选开始时间（已排序）
+fn period_candidates(rule: &RRule, dtstart: NaiveDateTime, period: i64) -> Vec<NaiveDateTime> {
+    let step = period * rule.interval;
+    let mut out: Vec<NaiveDateTime> = match rule.freq.as_str() {
+        "DAILY" => vec![dtstart + Duration::days(step)],
+        "WEEKLY" if !rule.by_day.is_empty() => {
+            let week_start = dtstart.date()
+                - Duration::days(dtstart.weekday().num_days_from_monday() as i64)
+                + Duration::weeks(step);
+            rule.by_day
+                .iter()
+                .map(|(_, wd)| {
+                    (week_start + Duration::days(wd.num_days_from_monday() as i64))
+                        .and_time(dtstart.time())
+                })
+                .collect()
+        }
+        "WEEKLY" => vec![dtstart + Duration::weeks(step)],
+        "MONTHLY" if !rule.by_day.is_empty() => {
+            let first = match add_months(
+                NaiveDate::from_ymd_opt(dtstart.year(), dtstart.month(), 1)
+                    .map(|d| d.and_time(dtstart.time()))
+                    .unwrap_or(dtstart),
+                step,
+            ) {
+                Some(t) => t,
+                None => return vec![],
+            };
+            // 不带序号的星期展开为当月所有该星期
+            rule.by_day
+                .iter()
+                .flat_map(|&(n, wd)| {
+                    let nths = if n == 0 { vec![1, 2, 3, 4, 5] } else { vec![n] };
+                    nths.into_iter().map(move |nth| (nth, wd))
+                })
+                .filter_map(|(nth, wd)| {
+                    nth_weekday_of_month(first.year(), first.month(), nth, wd)
+                        .map(|d| d.and_time(dtstart.time()))
+                })
+                .collect()
+        }
+        "MONTHLY" => add_months(dtstart, step).into_iter().collect(),
+        "YEARLY" => add_months(dtstart, step * 12).into_iter().collect(),
+        _ => vec![],
+    };
+    out.sort();
+    out.dedup();
+    out.into_iter().filter(|t| *t >= dtstart).collect()
+}
+
+/// 展开事件在 [range_start, range_end] 内的所有开始时间
+fn occurrences(
+    ev: &Event,
+    start: NaiveDateTime,
+    range_start: NaiveDateTime,
+    range_end: NaiveDateTime,
+) -> Vec<NaiveDateTime> {
+    let in_range = |t: &NaiveDateTime| *t >= range_start && *t <= range_end;
+    let rule = match ev.rrule.as_deref().and_then(parse_rrule) {
+        Some(r) => r,
+        None => {
+            return if in_range(&start) {
+                vec![start]
+            } else {
+                vec![]
+            }
+        }
+    };
+    let mut out = Vec::new();
+    let mut generated = 0usize;
+    let mut period = 0i64;
+    'outer: while generated < MAX_OCCURRENCES {
+        let candidates = period_candidates(&rule, start, period);
+        period += 1;
+        if candidates.is_empty() {
+            // 如 31 号在短月份不存在，跳过该周期
+            if period as usize > MAX_OCCURRENCES {
+                break;
+            }
+            continue;
+        }
+        for t in candidates {
+            if t > range_end || rule.until.map(|u| t > u).unwrap_or(false) {
+                break 'outer;
+            }
+            if rule.count.map(|c| generated >= c).unwrap_or(false) {
+                break 'outer;
+            }
+            generated += 1;
+            if in_range(&t) && !ev.exdates.contains(&t) {
+                out.push(t);
+            }
+        }
+    }
+    out
+}
+
+fn expand_events(
+    events: &[Event],
+    range_start: NaiveDateTime,
+    range_end: NaiveDateTime,
+) -> Vec<Meeting> {
+    // 被 RECURRENCE-ID 单独修改过的实例，从主规则中剔除
+    let overridden: HashSet<(String, NaiveDateTime)> = events
+        .iter()
+        .filter_map(|e| e.recurrence_id.map(|r| (e.uid.clone(), r)))
+        .collect();
+    let mut meetings = Vec::new();
+    for ev in events {
+        let start = match ev.start {
+            Some(s) => s,
+            None => continue,
+        };
+        let length = ev
+            .end
+            .map(|end| end - start)
+            .or(ev.duration)
+            .unwrap_or_else(|| {
+                if ev.all_day {
+                    Duration::days(1)
+                } else {
+                    Duration::zero()
+                }
+            });
+        let uid = if ev.uid.is_empty() {
+            format!("{}@{}", ev.title, start)
+        } else {
+            ev.uid.clone()
+        };
+        for t in occurrences(ev, start, range_start, range_end) {
+            if ev.recurrence_id.is_none() && overridden.contains(&(ev.uid.clone(), t)) {
+                continue;
+            }
+            meetings.push(Meeting {
+                uid: uid.clone(),
+                title: ev.title.clone(),
+                start: t,
+                end: t + length,
+                all_day: ev.all_day,
+                attendees: ev.attendees.clone(),
+                location: ev.location.clone(),
+                description: ev.description.clone(),
+            });
+        }
+    }
+    meetings
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_rrule_by_day() {
+        let r = parse_rrule("FREQ=MONTHLY;INTERVAL=2;BYDAY=1MO,-1FR,WE").unwrap();
+        assert_eq!(r.freq, "MONTHLY");
+        assert_eq!(r.interval, 2);
+        assert_eq!(
+            r.by_day,
+            vec![(1, Weekday::Mon), (-1, Weekday::Fri), (0, Weekday::Wed)]
+        );
+    }
+
+    #[test]
+    fn parse_rrule_malformed() {
+        // 非 ASCII、过短或无法解析的 BYDAY 值被忽略，不会 panic
+        let r = parse_rrule("FREQ=WEEKLY;BYDAY=É1,É,1É,MOÉ,X,,+MO,TU").unwrap();
+        assert_eq!(r.by_day, vec![(0, Weekday::Tue)]);
+        let r = parse_rrule("FREQ=DAILY;INTERVAL=0;COUNT=x;UNTIL=garbage;BYDAY").unwrap();
+        assert_eq!(r.interval, 1);
+        assert_eq!(r.count, None);
+        assert_eq!(r.until, None);
+        assert!(r.by_day.is_empty());
+        assert!(parse_rrule("INTERVAL=2;BYDAY=MO").is_none());
+        assert!(parse_rrule("").is_none());
+        assert!(parse_rrule("ÉÉ;=;;").is_none());
+    }
+
+    fn at(s: &str) -> NaiveDateTime {
+        NaiveDateTime::parse_from_str(s, "%Y-%m-%d %H:%M").unwrap()
+    }
+
+    fn starts(ics: &str, from: &str, to: &str) -> Vec<(String, NaiveDateTime)> {
+        let mut out: Vec<_> = expand_events(&parse_events(ics), at(from), at(to))
+            .into_iter()
+            .map(|m| (m.title, m.start))
+            .collect();
+        out.sort_by_key(|(_, t)| *t);
+        out
+    }
+
+    #[test]
+    fn parse_rrule_clamps_ordinal() {
+        let r = parse_rrule("FREQ=MONTHLY;BYDAY=-9999MO,7FR").unwrap();
+        assert_eq!(r.by_day, vec![(-5, Weekday::Mon), (5, Weekday::Fri)]);
+        // 超出范围的序号按 -5 处理（倒数第 5 个周一），不会溢出
+        let ev = Event {
+            start: Some(at("2024-01-01 09:00")),
+            rrule: Some("FREQ=MONTHLY;COUNT=2;BYDAY=-9223372036854775808MO".into()),
+            ..Default::default()
+        };
+        assert_eq!(
+            occurrences(
+                &ev,
+                at("2024-01-01 09:00"),
+                at("2024-01-01 00:00"),
+                at("2024-12-31 00:00")
+            ),
+            vec![at("2024-01-01 09:00"), at("2024-04-01 09:00")]
+        );
+    }
+
+    #[test]
+    fn expand_exdate() {
+        let ics = "BEGIN:VEVENT\r\nUID:a\r\nSUMMARY:Standup\r\nDTSTART:20240101T090000\r\n\
+                   DURATION:PT15M\r\nRRULE:FREQ=DAILY;COUNT=4\r\nEXDATE:20240102T090000,20240103T090000\r\n\
+                   END:VEVENT\r\n";
+        let meetings = expand_events(
+            &parse_events(ics),
+            at("2024-01-01 00:00"),
+            at("2024-02-01 00:00"),
+        );
+        let got: Vec<_> = meetings.iter().map(|m| (m.start, m.end)).collect();
+        assert_eq!(
+            got,
+            vec![
+                (at("2024-01-01 09:00"), at("2024-01-01 09:15")),
+                (at("2024-01-04 09:00"), at("2024-01-04 09:15")),
+            ]
+        );
+    }
+
+    #[test]
+    fn expand_recurrence_override() {
+        let ics = "BEGIN:VEVENT\nUID:w\nSUMMARY:Weekly\nDTSTART:20240101T100000\n\
+                   RRULE:FREQ=WEEKLY;COUNT=3\nEND:VEVENT\n\
+                   BEGIN:VEVENT\nUID:w\nSUMMARY:Moved\nRECURRENCE-ID:20240108T100000\n\
+                   DTSTART:20240109T140000\nEND:VEVENT\n";
+        assert_eq!(
+            starts(ics, "2024-01-01 00:00", "2024-02-01 00:00"),
+            vec![
+                ("Weekly".to_string(), at("2024-01-01 10:00")),
+                ("Moved".to_string(), at("2024-01-09 14:00")),
+                ("Weekly".to_string(), at("2024-01-15 10:00")),
+            ]
+        );
+    }
+
+    #[test]
+    fn expand_monthly_by_day() {
+        // 每月第一个周一与最后一个周五
+        let ics = "BEGIN:VEVENT\nUID:m\nSUMMARY:Review\nDTSTART:20240101T090000\n\
+                   RRULE:FREQ=MONTHLY;BYDAY=1MO,-1FR\nEND:VEVENT\n";
+        let got: Vec<_> = starts(ics, "2024-01-01 00:00", "2024-03-01 00:00")
+            .into_iter()
+            .map(|(_, t)| t)
+            .collect();
+        assert_eq!(
+            got,
+            vec![
+                at("2024-01-01 09:00"),
+                at("2024-01-26 09:00"),
+                at("2024-02-05 09:00"),
+                at("2024-02-23 09:00"),
+            ]
+        );
+        // 不带序号时展开当月所有该星期
+        let ics = "BEGIN:VEVENT\nUID:t\nSUMMARY:Tue\nDTSTART:20240201T090000\n\
+                   RRULE:FREQ=MONTHLY;BYDAY=TU\nEND:VEVENT\n";
+        let got: Vec<_> = starts(ics, "2024-02-01 00:00", "2024-03-10 00:00")
+            .into_iter()
+            .map(|(_, t)| t)
+            .collect();
+        assert_eq!(
+            got,
+            vec![
+                at("2024-02-06 09:00"),
+                at("2024-02-13 09:00"),
+                at("2024-02-20 09:00"),
+                at("2024-02-27 09:00"),
+                at("2024-03-05 09:00"),
+            ]
+        );
+    }
+}
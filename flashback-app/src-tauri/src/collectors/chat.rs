@@ -0,0 +1,102 @@
+//! 聊天数据位置：定位微信 / 企业微信 / 钉钉的本地数据目录
+
+use std::path::PathBuf;
+
+use super::{Collector, DataSource};
+use crate::scan::{home, ScanContext, ScanRun};
+use crate::{ChatLocation, ScanSummary};
+
+pub(crate) struct ChatLocationCollector;
+
+impl Collector for ChatLocationCollector {
+    fn id(&self) -> &'static str {
+        "chat_locations"
+    }
+
+    fn label(&self) -> &'static str {
+        "聊天数据位置"
+    }
+
+    fn discover(&self, _ctx: &ScanContext, _scan: &mut ScanRun) -> Vec<DataSource> {
+        detect_chat_locations()
+            .into_iter()
+            .map(|c| DataSource::new(PathBuf::from(c.path), c.app))
+            .collect()
+    }
+
+    fn collect(
+        &self,
+        _ctx: &ScanContext,
+        sources: &[DataSource],
+        scan: &mut ScanRun,
+        summary: &mut ScanSummary,
+    ) -> usize {
+        for (idx, s) in sources.iter().enumerate() {
+            scan.file_found(&s.path, None);
+            summary.chat_locations.push(ChatLocation {
+                app: s.kind.clone(),
+                path: s.path.to_string_lossy().to_string(),
+            });
+            scan.progress(idx + 1, sources.len());
+        }
+        sources.len()
+    }
+}
+
+pub(crate) fn detect_chat_locations() -> Vec<ChatLocation> {
+    let mut found = Vec::new();
+    let push_if_exists = |v: &mut Vec<ChatLocation>, app: &str, p: PathBuf| {
+        if p.exists() {
+            v.push(ChatLocation {
+                app: app.to_string(),
+                path: p.to_string_lossy().to_string(),
+            });
+        }
+    };
+    if cfg!(target_os = "macos") {
+        push_if_exists(
+            &mut found,
+            "WeChat",
+            home().join("Library/Containers/com.tencent.xinWeChat/Data/Library/Application Support/com.tencent.xinWeChat"),
+        );
+        push_if_exists(
+            &mut found,
+            "WeCom",
+            home().join(
+                "Library/Containers/com.tencent.WeWorkMac/Data/Library/Application Support/WXWork",
+            ),
+        );
+        push_if_exists(
+            &mut found,
+            "DingTalk",
+            home().join(
+                "Library/Containers/com.alibaba.DingTalkMac/Data/Library/Application Support",
+            ),
+        );
+    }
+    if cfg!(target_os = "windows") {
+        if let Ok(userprofile) = std::env::var("USERPROFILE") {
+            push_if_exists(
+                &mut found,
+                "WeChat",
+                PathBuf::from(&userprofile).join("Documents/WeChat Files"),
+            );
+            push_if_exists(
+                &mut found,
+                "WeChat",
+                PathBuf::from(&userprofile).join("AppData/Roaming/Tencent/WeChat"),
+            );
+            push_if_exists(
+                &mut found,
+                "WeCom",
+                PathBuf::from(&userprofile).join("AppData/Roaming/WXWork"),
+            );
+            push_if_exists(
+                &mut found,
+                "DingTalk",
+                PathBuf::from(&userprofile).join("AppData/Roaming/DingTalk"),
+            );
+        }
+    }
+    found
+}
@@ -0,0 +1,267 @@
+//! 文档采集：遍历扫描根目录，把时间范围内的办公/文本/图片等文件写入 scan_results，
+//! 并提取照片、Office 文档与 PDF 内嵌的元数据（见 metadata 模块）
+//! 重新扫描时修改时间、大小与文件标识都未变的文件沿用原条目，不再写库
+
+use rusqlite::Connection;
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::path::Path;
+use uuid::Uuid;
+
+use super::{Collector, DataSource, TimelineSpec};
+use crate::identity::{self, Identities};
+use crate::metadata;
+use crate::scan::{
+    classify_source_from_path, format_time, is_doc, EntryError, ScanContext, ScanRun,
+};
+use crate::walk::WalkItem;
+use crate::{ScanSummary, SourceKind};
+
+/// 项目级配置（collector_settings 中 id 为 documents 的一项）
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(default)]
+pub(crate) struct DocumentSettings {
+    /// 提取内嵌的元数据（EXIF、Office 文档属性、PDF 文档信息）
+    pub metadata: bool,
+    /// 同时提取照片的 GPS 位置
+    pub gps: bool,
+    /// 时间范围按内嵌日期判断（照片的拍摄时间、文档属性中的修改时间），没有内嵌日期的文件仍按修改时间
+    pub embedded_dates: bool,
+}
+
+impl Default for DocumentSettings {
+    fn default() -> Self {
+        DocumentSettings {
+            metadata: true,
+            gps: false,
+            embedded_dates: false,
+        }
+    }
+}
+
+impl DocumentSettings {
+    pub fn of(ctx: &ScanContext) -> Self {
+        ctx.collector_settings(DocumentCollector.id())
+    }
+}
+
+pub(crate) struct DocumentCollector;
+
+impl Collector for DocumentCollector {
+    fn id(&self) -> &'static str {
+        "documents"
+    }
+
+    fn label(&self) -> &'static str {
+        "文档"
+    }
+
+    fn init_schema(&self, conn: &Connection) -> rusqlite::Result<()> {
+        metadata::init_schema(conn)
+    }
+
+    fn project_tables(&self) -> &'static [&'static str] {
+        &["scan_results", "result_metadata"]
+    }
+
+    fn timeline(&self) -> Option<TimelineSpec> {
+        Some(TimelineSpec {
+            table: "scan_results",
+            time_column: "modified_at",
+            title_expr: "file_path",
+            detail_expr: "file_type",
+            weight_expr: "size_bytes",
+        })
+    }
+
+    fn discover(&self, ctx: &ScanContext, _scan: &mut ScanRun) -> Vec<DataSource> {
+        ctx.roots
+            .iter()
+            .filter(|p| p.exists())
+            .map(|p| DataSource::new(p.clone(), "root"))
+            .collect()
+    }
+
+    fn estimate(&self, _ctx: &ScanContext, sources: &[DataSource], scan: &mut ScanRun) -> usize {
+        sources.iter().map(|s| scan.count_files(&s.path)).sum()
+    }
+
+    fn collect(
+        &self,
+        ctx: &ScanContext,
+        sources: &[DataSource],
+        scan: &mut ScanRun,
+        summary: &mut ScanSummary,
+    ) -> usize {
+        let mut conn = match ctx.open_db() {
+            Some(c) => c,
+            None => return 0,
+        };
+        let settings = DocumentSettings::of(ctx);
+        // 记下已有条目：未变化的文件保留原条目不再写库，同一路径沿用原 id，改名或移动的文件找回原条目；
+        // 遍历结束后仍未认领的（已删除、移出时间范围或不再可读的）从结果中移除
+        let mut ids = Identities::load(&conn, &ctx.project_id).unwrap_or_default();
+
+        let total: usize = sources.iter().map(|s| scan.count_files(&s.path)).sum();
+        let mut walked = 0usize;
+        let mut documents = 0usize;
+        // 按内嵌日期筛选时，不在范围内的文件也解析过元数据，保留缓存供下次扫描判断
+        let mut filtered = HashSet::new();
+        if let Ok(tx) = conn.transaction() {
+            for root in sources {
+                scan.file_found(&root.path, None);
+                for item in scan.walk(&root.path) {
+                    let entry = match item {
+                        WalkItem::Entry(e) => e,
+                        WalkItem::Error(e) => {
+                            let path = e.path().map(Path::to_path_buf);
+                            scan.issue(path.as_deref(), e.into());
+                            continue;
+                        }
+                        WalkItem::Pruned(rule, path) => {
+                            scan.pruned(rule, &path);
+                            continue;
+                        }
+                    };
+                    if entry.file_type().is_dir() {
+                        continue;
+                    }
+                    walked += 1;
+                    scan.progress(walked, total);
+                    let path = entry.path();
+                    if !is_doc(path) {
+                        continue;
+                    }
+                    match insert_document(&tx, ctx, &settings, path, &mut ids) {
+                        Ok(true) => documents += 1,
+                        Ok(false) if settings.embedded_dates => {
+                            filtered.insert(path.to_string_lossy().to_string());
+                        }
+                        Ok(false) => {}
+                        Err(e) => scan.issue(Some(path), e),
+                    }
+                }
+            }
+            let gone = ids.unclaimed();
+            let _ = ids.follow_moves(&tx, &ctx.project_id);
+            for path in gone {
+                let _ = tx.execute(
+                    "DELETE FROM scan_results WHERE project_id = ? AND file_path = ? AND archived = 0",
+                    [&ctx.project_id, &path],
+                );
+            }
+            if settings.metadata {
+                let _ = metadata::remove_orphans(&tx, &ctx.project_id, &filtered);
+            } else {
+                let _ = tx.execute(
+                    "DELETE FROM result_metadata WHERE project_id = ?",
+                    [&ctx.project_id],
+                );
+            }
+            let _ = tx.commit();
+        }
+
+        summary.documents = documents;
+        documents
+    }
+}
+
+/// 文档在时间范围内时写入（与已有条目相同时不写）并返回 true，不在范围内返回 false；
+/// ids 用于沿用该路径原有的 id，并记录新出现的路径供 follow_moves 匹配
+pub(crate) fn insert_document(
+    conn: &Connection,
+    ctx: &ScanContext,
+    settings: &DocumentSettings,
+    path: &Path,
+    ids: &mut Identities,
+) -> Result<bool, EntryError> {
+    let meta = std::fs::metadata(path).map_err(|e| EntryError::io(&e, path))?;
+    let size = meta.len() as i64;
+    let modified_s = meta.modified().ok().map(format_time).unwrap_or_default();
+    // 无法获取时间时不过滤
+    let file_time = meta
+        .modified()
+        .ok()
+        .map(chrono::DateTime::<chrono::Local>::from);
+    // 按文件时间筛选时先过滤，范围外的文件不必解析元数据
+    if !settings.embedded_dates && file_time.is_some_and(|t| !ctx.contains(t)) {
+        return Ok(false);
+    }
+    let embedded = if settings.metadata {
+        metadata::load_or_extract(conn, &ctx.project_id, path, &modified_s, size, settings.gps)?
+    } else {
+        None
+    };
+    if settings.embedded_dates {
+        let time = embedded
+            .as_ref()
+            .and_then(|m| m.content_time())
+            .or(file_time);
+        if time.is_some_and(|t| !ctx.contains(t)) {
+            return Ok(false);
+        }
+    }
+    let path_s = path.to_string_lossy().to_string();
+    let known = ids.claim(&path_s, &meta);
+    let source = if ctx.is_custom() {
+        SourceKind::CustomSpecified.as_str()
+    } else {
+        classify_source_from_path(&path.to_string_lossy())
+    };
+    // 增量：修改时间、大小、文件标识与来源都未变时保留原条目，不再写库
+    if let Some(k) = &known {
+        if !k.archived
+            && k.modified_at == modified_s
+            && k.size_bytes == size
+            && k.file_key == identity::file_key(&meta)
+            && k.source == source
+        {
+            return Ok(true);
+        }
+    }
+    let created_s = meta
+        .created()
+        .ok()
+        .map(format_time)
+        .unwrap_or_else(|| modified_s.clone());
+    let file_type = path
+        .extension()
+        .and_then(|s| s.to_str())
+        .unwrap_or("")
+        .to_ascii_lowercase();
+    let now_s = chrono::Local::now().format("%Y-%m-%d %H:%M:%S").to_string();
+    // 有人工整理数据的文件保留内容哈希，内容未变时沿用上次的结果
+    let content_hash = match &known {
+        Some(k) if k.annotated => k
+            .content_hash
+            .clone()
+            .filter(|_| k.size_bytes == size && k.modified_at == modified_s)
+            .or_else(|| identity::content_hash(path)),
+        _ => None,
+    };
+    let (id, inserted_s) = match known {
+        Some(k) => (k.id, k.inserted_at),
+        None => (Uuid::new_v4().to_string(), now_s.clone()),
+    };
+
+    conn.execute(
+        "INSERT OR REPLACE INTO scan_results (id, project_id, file_path, file_type, source, created_at, modified_at, size_bytes, is_valid, inserted_at, updated_at, file_key, content_hash)
+         VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)",
+        rusqlite::params![
+            id,
+            ctx.project_id,
+            path_s,
+            file_type,
+            source,
+            created_s,
+            modified_s,
+            size,
+            1i64,
+            inserted_s,
+            now_s,
+            identity::file_key(&meta),
+            content_hash
+        ],
+    )?;
+    Ok(true)
+}
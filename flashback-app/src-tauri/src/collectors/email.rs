@@ -0,0 +1,515 @@
+//! 邮件归档：解析 .mbox / .eml 文件，把时间范围内的邮件写入 emails 表，并建立全文索引供搜索
+//! 来源为扫描根目录下发现的归档文件，以及用户手动选择的文件或目录（保存在采集器配置 paths 中）
+//! 全文索引使用 FTS5 trigram 分词，中文可按子串检索；少于 3 个字符的关键词退化为 LIKE 匹配
+//! 结果查询（results::query）带关键词时同样检索邮件，与文档结果一并返回
+
+use chrono::{Local, TimeZone};
+use mail_parser::mailbox::mbox::MessageIterator;
+use mail_parser::{Address, Message, MessageParser, MimeHeaders};
+use rusqlite::Connection;
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::io::BufReader;
+use std::path::{Path, PathBuf};
+use uuid::Uuid;
+
+use super::{Collector, DataSource, TimelineSpec};
+use crate::scan::{EntryError, IssueReason, ScanContext, ScanRun};
+use crate::walk::WalkItem;
+#[cfg(feature = "gui")]
+use crate::ProjectListResponseLike;
+use crate::ScanSummary;
+
+/// 正文入库的最大字符数，超长邮件（如日志、报表）截断
+const MAX_BODY_CHARS: usize = 20_000;
+
+/// 项目级配置（collector_settings 中 id 为 email 的一项）
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[serde(default)]
+struct EmailSettings {
+    /// 用户手动选择的邮件归档文件或目录
+    paths: Vec<String>,
+}
+
+pub(crate) struct EmailCollector;
+
+impl Collector for EmailCollector {
+    fn id(&self) -> &'static str {
+        "email"
+    }
+
+    fn label(&self) -> &'static str {
+        "邮件归档"
+    }
+
+    /// 涉及隐私，需在项目中手动启用
+    fn enabled_by_default(&self) -> bool {
+        false
+    }
+
+    fn init_schema(&self, conn: &Connection) -> rusqlite::Result<()> {
+        conn.execute(
+            "CREATE TABLE IF NOT EXISTS emails (
+                id TEXT PRIMARY KEY,
+                project_id TEXT NOT NULL,
+                message_id TEXT NOT NULL,
+                sender TEXT NOT NULL,
+                recipients TEXT NOT NULL,
+                subject TEXT NOT NULL,
+                sent_at TEXT NOT NULL,
+                body_text TEXT NOT NULL,
+                attachments TEXT NOT NULL,
+                source_path TEXT NOT NULL,
+                inserted_at TEXT NOT NULL,
+                UNIQUE(project_id, message_id)
+            )",
+            [],
+        )?;
+        conn.execute(
+            "CREATE INDEX IF NOT EXISTS idx_emails_project ON emails(project_id, sent_at)",
+            [],
+        )?;
+        conn.execute(
+            "CREATE VIRTUAL TABLE IF NOT EXISTS emails_fts USING fts5(
+                email_id UNINDEXED,
+                project_id UNINDEXED,
+                subject,
+                sender,
+                recipients,
+                body_text,
+                attachments,
+                tokenize = 'trigram'
+            )",
+            [],
+        )?;
+        Ok(())
+    }
+
+    fn project_tables(&self) -> &'static [&'static str] {
+        &["emails", "emails_fts"]
+    }
+
+    fn reindex(&self, conn: &Connection, project_id: &str) -> rusqlite::Result<()> {
+        conn.execute("DELETE FROM emails_fts WHERE project_id = ?", [project_id])?;
+        // 与 import_file 写入索引时一致：收件人、附件以逗号拼接
+        conn.execute(
+            "INSERT INTO emails_fts (email_id, project_id, subject, sender, recipients, body_text, attachments)
+             SELECT e.id, e.project_id, e.subject, e.sender,
+                    COALESCE((SELECT group_concat(value, ', ') FROM json_each(e.recipients)), ''),
+                    e.body_text,
+                    COALESCE((SELECT group_concat(value, ', ') FROM json_each(e.attachments)), '')
+             FROM emails e WHERE e.project_id = ?",
+            [project_id],
+        )?;
+        Ok(())
+    }
+
+    fn timeline(&self) -> Option<TimelineSpec> {
+        Some(TimelineSpec {
+            table: "emails",
+            time_column: "sent_at",
+            title_expr: "subject",
+            detail_expr: "sender",
+            weight_expr: "0",
+        })
+    }
+
+    /// 用户指定的文件不看扩展名（如 Thunderbird 的 Inbox 无扩展名），kind 为归档类型；
+    /// 用户指定的目录与扫描根目录（kind 为 dir）在采集阶段边遍历边按扩展名导入，遍历计入进度
+    fn discover(&self, ctx: &ScanContext, _scan: &mut ScanRun) -> Vec<DataSource> {
+        let settings: EmailSettings = ctx.collector_settings(self.id());
+        let mut found = Vec::new();
+        for p in settings.paths.iter().map(PathBuf::from) {
+            if p.is_file() {
+                let kind = archive_kind(&p).unwrap_or("mbox");
+                found.push(DataSource::new(p, kind));
+            } else if p.is_dir() {
+                found.push(DataSource::new(p, "dir"));
+            }
+        }
+        for root in ctx.roots.iter().filter(|p| p.exists()) {
+            found.push(DataSource::new(root.clone(), "dir"));
+        }
+        found
+    }
+
+    fn estimate(&self, _ctx: &ScanContext, sources: &[DataSource], scan: &mut ScanRun) -> usize {
+        sources
+            .iter()
+            .map(|s| match s.kind.as_str() {
+                "dir" => scan.count_files(&s.path),
+                _ => 1,
+            })
+            .sum()
+    }
+
+    fn collect(
+        &self,
+        ctx: &ScanContext,
+        sources: &[DataSource],
+        scan: &mut ScanRun,
+        _summary: &mut ScanSummary,
+    ) -> usize {
+        let mut conn = match ctx.open_db() {
+            Some(c) => c,
+            None => return 0,
+        };
+        // 清空旧结果（本次全量重扫）
+        // 归档的邮件（retain 模式下移出时间范围的）及其索引保留
+        let _ = conn.execute(
+            "DELETE FROM emails_fts WHERE project_id = ?1
+             AND email_id IN (SELECT id FROM emails WHERE project_id = ?1 AND archived = 0)",
+            [&ctx.project_id],
+        );
+        let _ = conn.execute(
+            "DELETE FROM emails WHERE project_id = ? AND archived = 0",
+            [&ctx.project_id],
+        );
+
+        let total = self.estimate(ctx, sources, scan);
+        let mut done = 0usize;
+        let mut emails = 0usize;
+        // 同一文件可能既被用户指定、又在扫描根目录下，只导入一次
+        let mut seen: HashSet<PathBuf> = HashSet::new();
+        let mut import = |path: &Path, kind: &str, scan: &mut ScanRun| {
+            if !seen.insert(path.to_path_buf()) {
+                return;
+            }
+            match import_file(&mut conn, ctx, path, kind, scan) {
+                Ok(n) => {
+                    if n > 0 {
+                        scan.file_found(path, Some(n));
+                    }
+                    emails += n;
+                }
+                Err(e) => scan.issue(Some(path), e),
+            }
+        };
+        for source in sources {
+            if source.kind != "dir" {
+                import(&source.path, &source.kind, scan);
+                done += 1;
+                scan.progress(done, total);
+                continue;
+            }
+            for item in scan.walk(&source.path) {
+                let entry = match item {
+                    WalkItem::Entry(e) => e,
+                    WalkItem::Error(e) => {
+                        let path = e.path().map(Path::to_path_buf);
+                        scan.issue(path.as_deref(), e.into());
+                        continue;
+                    }
+                    WalkItem::Pruned(rule, path) => {
+                        scan.pruned(rule, &path);
+                        continue;
+                    }
+                };
+                if entry.file_type().is_dir() {
+                    continue;
+                }
+                done += 1;
+                scan.progress(done, total);
+                if let Some(kind) = archive_kind(entry.path()) {
+                    import(entry.path(), kind, scan);
+                }
+            }
+        }
+        emails
+    }
+}
+
+/// 按扩展名识别邮件归档类型
+fn archive_kind(path: &Path) -> Option<&'static str> {
+    let ext = path.extension()?.to_str()?.to_ascii_lowercase();
+    match ext.as_str() {
+        "eml" => Some("eml"),
+        "mbox" | "mbx" => Some("mbox"),
+        _ => None,
+    }
+}
+
+/// 解析后的一封邮件
+struct Email {
+    message_id: String,
+    sender: String,
+    recipients: Vec<String>,
+    subject: String,
+    sent_at: chrono::DateTime<Local>,
+    body_text: String,
+    attachments: Vec<String>,
+}
+
+/// 解析一个归档文件并把时间范围内的邮件写入 emails / emails_fts，返回写入条数
+/// mbox 逐封解析、逐封写入，不把整个归档读入内存；无法解析的邮件记录为问题后继续
+fn import_file(
+    conn: &mut Connection,
+    ctx: &ScanContext,
+    path: &Path,
+    kind: &str,
+    scan: &mut ScanRun,
+) -> Result<usize, EntryError> {
+    let fallback_time = std::fs::metadata(path)
+        .and_then(|m| m.modified())
+        .map(chrono::DateTime::<Local>::from)
+        .unwrap_or_else(|_| Local::now());
+    let source_path = path.to_string_lossy().to_string();
+    let parser = MessageParser::default();
+    let malformed = |idx: usize| {
+        EntryError::new(
+            IssueReason::ParseError,
+            format!("第 {} 封邮件无法解析，已跳过", idx + 1),
+        )
+    };
+
+    let since = ctx.since();
+    let tx = conn.transaction()?;
+    let now_s = Local::now().format("%Y-%m-%d %H:%M:%S").to_string();
+    let mut saved = 0usize;
+    if kind == "eml" {
+        let raw = std::fs::read(path).map_err(|e| EntryError::io(&e, path))?;
+        match parser.parse(&raw) {
+            Some(m) => {
+                let email = to_email(&m, fallback_time, &source_path, 0);
+                saved += save_email(&tx, ctx, since, &email, &source_path, &now_s)? as usize;
+            }
+            None => scan.issue(Some(path), malformed(0)),
+        }
+    } else {
+        let file = std::fs::File::open(path).map_err(|e| EntryError::io(&e, path))?;
+        for (idx, item) in MessageIterator::new(BufReader::new(file)).enumerate() {
+            let item = match item {
+                Ok(item) => item,
+                // 读取出错后文件位置不再前进，继续读只会重复同一个错误；已读出的邮件照常保存
+                Err(e) => {
+                    scan.issue(Some(path), EntryError::io(&e, path));
+                    break;
+                }
+            };
+            // mbox 分隔行中的投递时间作为缺少 Date 头时的后备
+            let delivered = Local
+                .timestamp_opt(item.internal_date() as i64, 0)
+                .single()
+                .filter(|_| item.internal_date() > 0)
+                .unwrap_or(fallback_time);
+            match parser.parse(item.contents()) {
+                Some(m) => {
+                    let email = to_email(&m, delivered, &source_path, idx);
+                    saved += save_email(&tx, ctx, since, &email, &source_path, &now_s)? as usize;
+                }
+                None => scan.issue(Some(path), malformed(idx)),
+            }
+        }
+    }
+    tx.commit()?;
+    Ok(saved)
+}
+
+/// 写入一封时间范围内的邮件及其索引；不在范围内或已存在（同一封邮件出现在多个归档中，只保留第一份）时返回 false
+fn save_email(
+    tx: &rusqlite::Transaction,
+    ctx: &ScanContext,
+    since: Option<chrono::DateTime<Local>>,
+    m: &Email,
+    source_path: &str,
+    now_s: &str,
+) -> Result<bool, EntryError> {
+    if since.map(|s| m.sent_at < s).unwrap_or(false) {
+        return Ok(false);
+    }
+    let id = Uuid::new_v4().to_string();
+    let inserted = tx.execute(
+        "INSERT OR IGNORE INTO emails (id, project_id, message_id, sender, recipients, subject, sent_at, body_text, attachments, source_path, inserted_at)
+         VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)",
+        rusqlite::params![
+            id,
+            ctx.project_id,
+            m.message_id,
+            m.sender,
+            serde_json::to_string(&m.recipients).unwrap_or_else(|_| "[]".into()),
+            m.subject,
+            m.sent_at.format("%Y-%m-%d %H:%M:%S").to_string(),
+            m.body_text,
+            serde_json::to_string(&m.attachments).unwrap_or_else(|_| "[]".into()),
+            source_path,
+            now_s
+        ],
+    )?;
+    if inserted == 0 {
+        return Ok(false);
+    }
+    tx.execute(
+        "INSERT INTO emails_fts (email_id, project_id, subject, sender, recipients, body_text, attachments)
+         VALUES (?, ?, ?, ?, ?, ?, ?)",
+        rusqlite::params![
+            id,
+            ctx.project_id,
+            m.subject,
+            m.sender,
+            m.recipients.join(", "),
+            m.body_text,
+            m.attachments.join(", ")
+        ],
+    )?;
+    Ok(true)
+}
+
+fn to_email(
+    msg: &Message,
+    fallback_time: chrono::DateTime<Local>,
+    source_path: &str,
+    index: usize,
+) -> Email {
+    let sent_at = msg
+        .date()
+        .and_then(|d| Local.timestamp_opt(d.to_timestamp(), 0).single())
+        .unwrap_or(fallback_time);
+    // 缺少 Message-ID 时用归档路径与序号生成，保证重扫结果稳定
+    let message_id = msg
+        .message_id()
+        .map(|s| s.to_string())
+        .unwrap_or_else(|| format!("{}#{}", source_path, index));
+    let mut recipients = Vec::new();
+    for addr in [msg.to(), msg.cc(), msg.bcc()].into_iter().flatten() {
+        recipients.extend(format_addresses(addr));
+    }
+    let mut body_text: String = msg.body_text(0).map(|s| s.into_owned()).unwrap_or_default();
+    if let Some((cut, _)) = body_text.char_indices().nth(MAX_BODY_CHARS) {
+        body_text.truncate(cut);
+    }
+    Email {
+        message_id,
+        sender: msg
+            .from()
+            .map(|a| format_addresses(a).join(", "))
+            .unwrap_or_default(),
+        recipients,
+        subject: msg.subject().unwrap_or("").to_string(),
+        sent_at,
+        body_text,
+        attachments: msg
+            .attachments()
+            .filter_map(|p| p.attachment_name())
+            .map(|s| s.to_string())
+            .collect(),
+    }
+}
+
+/// `名称 <地址>` 形式，缺少名称时只保留地址
+fn format_addresses(addr: &Address) -> Vec<String> {
+    addr.iter()
+        .filter_map(|a| match (a.name(), a.address()) {
+            (Some(name), Some(address)) if !name.is_empty() => {
+                Some(format!("{} <{}>", name, address))
+            }
+            (_, Some(address)) => Some(address.to_string()),
+            (Some(name), None) => Some(name.to_string()),
+            (None, None) => None,
+        })
+        .collect()
+}
+
+#[cfg(feature = "gui")]
+#[derive(Debug, Clone, Serialize)]
+pub(crate) struct EmailItem {
+    id: String,
+    message_id: String,
+    sender: String,
+    recipients: Vec<String>,
+    subject: String,
+    sent_at: String,
+    /// 正文摘要（全文检索时为命中片段）
+    snippet: String,
+    attachments: Vec<String>,
+    source_path: String,
+}
+
+/// 分页检索项目邮件；q 为空时按时间倒序列出全部
+#[cfg(feature = "gui")]
+pub(crate) fn search(
+    conn: &Connection,
+    project_id: &str,
+    q: Option<&str>,
+    page: usize,
+    page_size: usize,
+) -> Result<ProjectListResponseLike<EmailItem>, String> {
+    let page = if page == 0 { 1 } else { page };
+    let page_size = page_size.max(1);
+    let offset = (page - 1) * page_size;
+    let query = q.map(str::trim).filter(|s| !s.is_empty());
+
+    // trigram 分词至少需要 3 个字符，更短的关键词对各字段做 LIKE 匹配
+    let (where_sql, snippet_sql, keyword) = match query {
+        Some(k) if k.chars().count() >= 3 => (
+            "e.id IN (SELECT email_id FROM emails_fts WHERE emails_fts MATCH ?2 AND project_id = ?1)",
+            "(SELECT snippet(emails_fts, 5, '', '', '…', 32) FROM emails_fts WHERE email_id = e.id AND emails_fts MATCH ?2)",
+            // 整体作为短语匹配，避免用户输入中的 FTS 语法字符出错
+            Some(format!("\"{}\"", k.replace('"', "\"\""))),
+        ),
+        Some(k) => (
+            "(e.subject LIKE ?2 ESCAPE '\\' OR e.sender LIKE ?2 ESCAPE '\\' OR e.recipients LIKE ?2 ESCAPE '\\' \
+             OR e.body_text LIKE ?2 ESCAPE '\\' OR e.attachments LIKE ?2 ESCAPE '\\')",
+            "substr(e.body_text, 1, 200)",
+            Some(format!(
+                "%{}%",
+                k.replace('\\', "\\\\")
+                    .replace('%', "\\%")
+                    .replace('_', "\\_")
+            )),
+        ),
+        None => ("?2 IS NULL", "substr(e.body_text, 1, 200)", None),
+    };
+
+    let total: usize = conn
+        .query_row(
+            &format!(
+                "SELECT COUNT(*) FROM emails e WHERE e.project_id = ?1 AND {}",
+                where_sql
+            ),
+            rusqlite::params![project_id, keyword],
+            |row| row.get(0),
+        )
+        .map_err(|e| e.to_string())?;
+    let total_pages = if total == 0 {
+        1
+    } else {
+        total.div_ceil(page_size)
+    };
+
+    let mut stmt = conn
+        .prepare(&format!(
+            "SELECT e.id, e.message_id, e.sender, e.recipients, e.subject, e.sent_at, {}, e.attachments, e.source_path
+             FROM emails e WHERE e.project_id = ?1 AND {} ORDER BY e.sent_at DESC LIMIT ?3 OFFSET ?4",
+            snippet_sql, where_sql
+        ))
+        .map_err(|e| e.to_string())?;
+    let rows = stmt
+        .query_map(
+            rusqlite::params![project_id, keyword, page_size as i64, offset as i64],
+            |row| {
+                let recipients: String = row.get(3)?;
+                let attachments: String = row.get(7)?;
+                Ok(EmailItem {
+                    id: row.get(0)?,
+                    message_id: row.get(1)?,
+                    sender: row.get(2)?,
+                    recipients: serde_json::from_str(&recipients).unwrap_or_default(),
+                    subject: row.get(4)?,
+                    sent_at: row.get(5)?,
+                    snippet: row.get::<_, Option<String>>(6)?.unwrap_or_default(),
+                    attachments: serde_json::from_str(&attachments).unwrap_or_default(),
+                    source_path: row.get(8)?,
+                })
+            },
+        )
+        .map_err(|e| e.to_string())?;
+    let items = rows.collect::<Result<_, _>>().map_err(|e| e.to_string())?;
+
+    Ok(ProjectListResponseLike {
+        items,
+        total,
+        page,
+        total_pages,
+        next_cursor: None,
+    })
+}
@@ -0,0 +1,209 @@
+//! Git 仓库识别：在扫描根目录下查找含 .git 的仓库，并把时间范围内的提交写入 git_commits
+
+use chrono::{Local, TimeZone};
+use rusqlite::Connection;
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+use uuid::Uuid;
+
+use super::{Collector, DataSource, TimelineSpec};
+use crate::scan::{EntryError, ScanContext, ScanRun};
+use crate::walk::WalkItem;
+use crate::ScanSummary;
+
+pub(crate) struct GitRepoCollector;
+
+impl Collector for GitRepoCollector {
+    fn id(&self) -> &'static str {
+        "git_repos"
+    }
+
+    fn label(&self) -> &'static str {
+        "Git 仓库"
+    }
+
+    fn init_schema(&self, conn: &Connection) -> rusqlite::Result<()> {
+        conn.execute(
+            "CREATE TABLE IF NOT EXISTS git_commits (
+                id TEXT PRIMARY KEY,
+                project_id TEXT NOT NULL,
+                repo_path TEXT NOT NULL,
+                hash TEXT NOT NULL,
+                author_name TEXT NOT NULL,
+                author_email TEXT NOT NULL,
+                summary TEXT NOT NULL,
+                committed_at TEXT NOT NULL,
+                files_changed INTEGER NOT NULL,
+                insertions INTEGER NOT NULL,
+                deletions INTEGER NOT NULL,
+                inserted_at TEXT NOT NULL,
+                UNIQUE(project_id, repo_path, hash)
+            )",
+            [],
+        )?;
+        conn.execute(
+            "CREATE INDEX IF NOT EXISTS idx_git_commits_project ON git_commits(project_id, committed_at)",
+            [],
+        )?;
+        Ok(())
+    }
+
+    fn project_tables(&self) -> &'static [&'static str] {
+        &["git_commits"]
+    }
+
+    fn timeline(&self) -> Option<TimelineSpec> {
+        Some(TimelineSpec {
+            table: "git_commits",
+            time_column: "committed_at",
+            title_expr: "summary",
+            detail_expr: "repo_path",
+            weight_expr: "insertions + deletions",
+        })
+    }
+
+    fn discover(&self, ctx: &ScanContext, _scan: &mut ScanRun) -> Vec<DataSource> {
+        ctx.roots
+            .iter()
+            .filter(|p| p.exists())
+            .map(|p| DataSource::new(p.clone(), "root"))
+            .collect()
+    }
+
+    fn estimate(&self, _ctx: &ScanContext, sources: &[DataSource], scan: &mut ScanRun) -> usize {
+        sources.iter().map(|s| scan.count_files(&s.path)).sum()
+    }
+
+    fn collect(
+        &self,
+        ctx: &ScanContext,
+        sources: &[DataSource],
+        scan: &mut ScanRun,
+        summary: &mut ScanSummary,
+    ) -> usize {
+        let mut repos: Vec<PathBuf> = Vec::new();
+        let mut seen: HashSet<PathBuf> = HashSet::new();
+        // 前一半进度用于查找仓库（按预计数的文件数），后一半用于读取提交
+        let total: usize = sources.iter().map(|s| scan.count_files(&s.path)).sum();
+        let mut walked = 0usize;
+        for root in sources {
+            let mut it = scan.walk(&root.path);
+            while let Some(item) = it.next() {
+                let entry = match item {
+                    WalkItem::Entry(e) => e,
+                    WalkItem::Error(e) => {
+                        let path = e.path().map(Path::to_path_buf);
+                        scan.issue(path.as_deref(), e.into());
+                        continue;
+                    }
+                    WalkItem::Pruned(rule, path) => {
+                        scan.pruned(rule, &path);
+                        continue;
+                    }
+                };
+                if !entry.file_type().is_dir() {
+                    walked += 1;
+                    scan.progress(walked, total * 2);
+                    continue;
+                }
+                if entry.file_name() == ".git" {
+                    if let Some(repo_root) = entry.path().parent() {
+                        if git2::Repository::open(repo_root).is_ok()
+                            && seen.insert(repo_root.to_path_buf())
+                        {
+                            scan.file_found(repo_root, None);
+                            repos.push(repo_root.to_path_buf());
+                        }
+                    }
+                    it.skip_current_dir();
+                }
+            }
+        }
+        scan.progress(1, 2);
+        summary.git_repos = repos.len();
+
+        if let Some(mut conn) = ctx.open_db() {
+            // 清空旧结果（本次全量重扫）
+            let _ = conn.execute(
+                "DELETE FROM git_commits WHERE project_id = ? AND archived = 0",
+                [&ctx.project_id],
+            );
+            for (idx, repo) in repos.iter().enumerate() {
+                match save_commits(&mut conn, ctx, repo) {
+                    Ok(n) if n > 0 => scan.file_found(repo, Some(n)),
+                    Ok(_) => {}
+                    Err(e) => scan.issue(Some(repo), e),
+                }
+                scan.progress(repos.len() + idx + 1, repos.len() * 2);
+            }
+        }
+        repos.len()
+    }
+}
+
+/// 遍历所有本地分支，把时间范围内的提交及改动统计写入 git_commits，返回写入条数
+fn save_commits(
+    conn: &mut Connection,
+    ctx: &ScanContext,
+    repo_path: &Path,
+) -> Result<usize, EntryError> {
+    let repo = git2::Repository::open(repo_path)?;
+    let mut walk = repo.revwalk()?;
+    walk.set_sorting(git2::Sort::TIME)?;
+    // 空仓库没有任何分支，直接返回
+    if walk.push_glob("refs/heads/*").is_err() {
+        return Ok(0);
+    }
+    let since = ctx.since().map(|t| t.timestamp());
+    let repo_s = repo_path.to_string_lossy().to_string();
+    let now_s = Local::now().format("%Y-%m-%d %H:%M:%S").to_string();
+
+    let tx = conn.transaction()?;
+    let mut saved = 0usize;
+    for oid in walk {
+        let oid = oid?;
+        let commit = repo.find_commit(oid)?;
+        let seconds = commit.time().seconds();
+        // 多分支时提交时间不严格递减，不能遇到第一个过早的提交就停止
+        if since.map(|s| seconds < s).unwrap_or(false) {
+            continue;
+        }
+        let committed_at = match Local.timestamp_opt(seconds, 0).single() {
+            Some(t) => t.format("%Y-%m-%d %H:%M:%S").to_string(),
+            None => continue,
+        };
+        // 合并提交与第一个父提交比较
+        let stats = commit
+            .tree()
+            .ok()
+            .and_then(|tree| {
+                let parent_tree = commit.parent(0).ok().and_then(|p| p.tree().ok());
+                repo.diff_tree_to_tree(parent_tree.as_ref(), Some(&tree), None)
+                    .ok()
+            })
+            .and_then(|diff| diff.stats().ok());
+        let author = commit.author();
+        tx.execute(
+            "INSERT OR IGNORE INTO git_commits (id, project_id, repo_path, hash, author_name, author_email, summary, committed_at, files_changed, insertions, deletions, inserted_at)
+             VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)",
+            rusqlite::params![
+                Uuid::new_v4().to_string(),
+                ctx.project_id,
+                repo_s,
+                oid.to_string(),
+                author.name().unwrap_or(""),
+                author.email().unwrap_or(""),
+                commit.summary().unwrap_or(""),
+                committed_at,
+                stats.as_ref().map(|s| s.files_changed() as i64).unwrap_or(0),
+                stats.as_ref().map(|s| s.insertions() as i64).unwrap_or(0),
+                stats.as_ref().map(|s| s.deletions() as i64).unwrap_or(0),
+                now_s
+            ],
+        )
+        ?;
+        saved += 1;
+    }
+    tx.commit()?;
+    Ok(saved)
+}
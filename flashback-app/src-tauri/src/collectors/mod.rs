@@ -0,0 +1,216 @@
+//! 数据源采集器
+//! 每种数据源（文档、Git 仓库、聊天数据位置、浏览器历史、会议记录、终端历史、邮件……）实现 Collector 并在 CollectorRegistry 中注册；
+//! 项目可按采集器 id 单独启用/停用，扫描引擎只执行已启用的采集器
+
+use rusqlite::Connection;
+#[cfg(feature = "gui")]
+use serde::Serialize;
+use std::path::PathBuf;
+
+use crate::scan::{ScanContext, ScanRun};
+use crate::ScanSummary;
+
+mod browser;
+mod calendar;
+mod chat;
+mod documents;
+mod email;
+mod git;
+mod shell;
+
+#[cfg(feature = "gui")]
+pub(crate) use calendar::import_user_files as import_calendar_files;
+#[cfg(feature = "gui")]
+pub(crate) use documents::insert_document;
+pub(crate) use documents::DocumentSettings;
+#[cfg(feature = "gui")]
+pub(crate) use email::{search as search_emails, EmailItem};
+#[cfg(feature = "gui")]
+pub(crate) use shell::{history_groups as shell_history_groups, ShellHistoryGroup};
+
+/// 发现阶段定位到的一个数据源（目录、文件或数据库路径）
+#[derive(Debug, Clone)]
+pub(crate) struct DataSource {
+    pub path: PathBuf,
+    /// 数据源细分类型，如聊天应用名、浏览器名，由采集器自行约定
+    pub kind: String,
+}
+
+impl DataSource {
+    pub fn new(path: PathBuf, kind: impl Into<String>) -> Self {
+        DataSource {
+            path,
+            kind: kind.into(),
+        }
+    }
+}
+
+/// 采集器数据在时间线中的映射：表中每行是一个带时间的条目
+/// 各表达式均为该表上的 SQL 表达式，时间列格式为 `%Y-%m-%d %H:%M:%S`
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct TimelineSpec {
+    pub table: &'static str,
+    pub time_column: &'static str,
+    /// 条目标题
+    pub title_expr: &'static str,
+    /// 条目补充信息（路径、仓库、发件人等）
+    pub detail_expr: &'static str,
+    /// 选取每个时间段代表条目时的排序权重，越大越靠前
+    pub weight_expr: &'static str,
+}
+
+pub(crate) trait Collector: Send + Sync {
+    /// 唯一标识，持久化在 projects.collectors 中
+    fn id(&self) -> &'static str;
+
+    /// 展示名称
+    fn label(&self) -> &'static str;
+
+    /// 项目未显式配置采集器时是否启用
+    fn enabled_by_default(&self) -> bool {
+        true
+    }
+
+    /// 建表（数据库初始化时调用，需幂等）
+    fn init_schema(&self, _conn: &Connection) -> rusqlite::Result<()> {
+        Ok(())
+    }
+
+    /// 按 project_id 存放数据的表，删除项目时一并清理
+    fn project_tables(&self) -> &'static [&'static str] {
+        &[]
+    }
+
+    /// 数据表被直接写入后（如导入项目归档）重建派生数据，例如全文索引
+    fn reindex(&self, _conn: &Connection, _project_id: &str) -> rusqlite::Result<()> {
+        Ok(())
+    }
+
+    /// 参与时间线聚合的数据；只记录数据源位置、没有时间信息的采集器返回 None
+    fn timeline(&self) -> Option<TimelineSpec> {
+        None
+    }
+
+    /// 发现阶段：定位本次要采集的数据源，不写库
+    fn discover(&self, ctx: &ScanContext, scan: &mut ScanRun) -> Vec<DataSource>;
+
+    /// 预估采集阶段的工作量，作为整体进度中本采集器的权重；默认每个数据源计 1，
+    /// 需要遍历目录的采集器可通过 scan.count_files 按文件数预估
+    fn estimate(&self, _ctx: &ScanContext, sources: &[DataSource], _scan: &mut ScanRun) -> usize {
+        sources.len()
+    }
+
+    /// 采集阶段：处理 discover 返回的数据源并写库/写摘要，返回采集到的条目数；
+    /// 期间通过 scan.progress(done, total) 汇报本采集器内的进度
+    fn collect(
+        &self,
+        ctx: &ScanContext,
+        sources: &[DataSource],
+        scan: &mut ScanRun,
+        summary: &mut ScanSummary,
+    ) -> usize;
+}
+
+#[cfg(feature = "gui")]
+#[derive(Debug, Clone, Serialize)]
+pub(crate) struct CollectorInfo {
+    id: &'static str,
+    label: &'static str,
+    enabled_by_default: bool,
+}
+
+pub(crate) struct CollectorRegistry {
+    collectors: Vec<Box<dyn Collector>>,
+}
+
+impl CollectorRegistry {
+    /// 内置采集器，注册顺序即执行顺序
+    pub fn builtin() -> Self {
+        CollectorRegistry {
+            collectors: vec![
+                Box::new(chat::ChatLocationCollector),
+                Box::new(git::GitRepoCollector),
+                Box::new(documents::DocumentCollector),
+                Box::new(browser::BrowserHistoryCollector),
+                Box::new(calendar::CalendarCollector),
+                Box::new(shell::ShellHistoryCollector),
+                Box::new(email::EmailCollector),
+            ],
+        }
+    }
+
+    pub fn all(&self) -> impl Iterator<Item = &dyn Collector> {
+        self.collectors.iter().map(|c| c.as_ref())
+    }
+
+    pub fn contains(&self, id: &str) -> bool {
+        self.all().any(|c| c.id() == id)
+    }
+
+    /// 项目启用的采集器；enabled 为 None 表示未配置，使用各采集器的默认值
+    pub fn enabled_for<'a>(&'a self, enabled: Option<&'a [String]>) -> Vec<&'a dyn Collector> {
+        self.all()
+            .filter(|c| match enabled {
+                Some(ids) => ids.iter().any(|id| id == c.id()),
+                None => c.enabled_by_default(),
+            })
+            .collect()
+    }
+
+    #[cfg(feature = "gui")]
+    pub fn info(&self) -> Vec<CollectorInfo> {
+        self.all()
+            .map(|c| CollectorInfo {
+                id: c.id(),
+                label: c.label(),
+                enabled_by_default: c.enabled_by_default(),
+            })
+            .collect()
+    }
+
+    pub fn init_schema(&self, conn: &Connection) -> rusqlite::Result<()> {
+        for c in self.all() {
+            c.init_schema(conn)?;
+        }
+        // 时间线表统一带 archived 列：retain 模式下移出时间范围的条目保留并标记为归档
+        for spec in self.all().filter_map(|c| c.timeline()) {
+            let has_archived = conn
+                .prepare(&format!("PRAGMA table_info({})", spec.table))?
+                .query_map([], |row| row.get::<_, String>(1))?
+                .filter_map(Result::ok)
+                .any(|c| c == "archived");
+            if !has_archived {
+                conn.execute(
+                    &format!(
+                        "ALTER TABLE {} ADD COLUMN archived INTEGER NOT NULL DEFAULT 0",
+                        spec.table
+                    ),
+                    [],
+                )?;
+            }
+        }
+        Ok(())
+    }
+
+    /// 可参与时间线聚合的采集器及其映射
+    pub fn timeline_specs(&self) -> Vec<(&'static str, &'static str, TimelineSpec)> {
+        self.all()
+            .filter_map(|c| c.timeline().map(|spec| (c.id(), c.label(), spec)))
+            .collect()
+    }
+
+    #[cfg(feature = "gui")]
+    pub fn reindex(&self, conn: &Connection, project_id: &str) -> rusqlite::Result<()> {
+        for c in self.all() {
+            c.reindex(conn, project_id)?;
+        }
+        Ok(())
+    }
+
+    /// 所有采集器按 project_id 存放数据的表
+    pub fn project_tables(&self) -> Vec<&'static str> {
+        self.all()
+            .flat_map(|c| c.project_tables().iter().copied())
+            .collect()
+    }
+}
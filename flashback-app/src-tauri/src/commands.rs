@@ -0,0 +1,868 @@
+//! Tauri 命令与桌面端入口，仅在启用 gui 特性（默认启用）时编译；
+//! 命令行版本可用 --no-default-features 构建，不依赖 tauri 与系统 WebView
+//! 前端按后端参数名（snake_case）传参，新增命令须标注 rename_all = "snake_case"（tauri 2 默认要求 camelCase）
+
+use std::sync::Mutex;
+use tauri::Manager;
+
+use super::*;
+use collection::SavedSearch;
+use collectors::{CollectorInfo, EmailItem, ShellHistoryGroup};
+use schedule::{Schedule, ScheduleRun};
+use thumbnail::ThumbnailService;
+use watch::{WatchState, WatchStatus};
+
+#[tauri::command]
+fn init_database(db: tauri::State<DatabaseManager>) -> Result<String, String> {
+    db.initialize().map_err(|e| e.to_string())?;
+    Ok("Database initialized successfully".to_string())
+}
+
+#[tauri::command]
+fn create_project(
+    db: tauri::State<DatabaseManager>,
+    project_input: ProjectInput,
+) -> Result<Project, String> {
+    let conn = db.get_connection().map_err(|e| e.to_string())?;
+    insert_project(&conn, project_input)
+}
+
+#[tauri::command]
+fn get_projects(db: tauri::State<DatabaseManager>) -> Result<Vec<Project>, String> {
+    let conn = db.get_connection().map_err(|e| e.to_string())?;
+    list_projects(&conn)
+}
+
+#[tauri::command]
+fn get_projects_paginated(
+    db: tauri::State<DatabaseManager>,
+    page: usize,
+    page_size: usize,
+) -> Result<ProjectListResponse, String> {
+    println!(
+        "[DEBUG] get_projects_paginated called: page={}, page_size={}",
+        page, page_size
+    );
+
+    let conn = db.get_connection().map_err(|e| {
+        println!("[DEBUG] get_connection error: {}", e);
+        e.to_string()
+    })?;
+
+    // 确保 page 至少为 1
+    let page = if page == 0 { 1 } else { page };
+
+    // 计算偏移量
+    let offset = (page - 1) * page_size;
+    println!("[DEBUG] offset={}", offset);
+
+    // 获取总数
+    let total: usize = conn
+        .query_row("SELECT COUNT(*) FROM projects", [], |row| {
+            let count: usize = row.get(0)?;
+            println!("[DEBUG] total projects count: {}", count);
+            Ok(count)
+        })
+        .map_err(|e| {
+            println!("[DEBUG] COUNT query error: {}", e);
+            e.to_string()
+        })?;
+
+    // 计算总页数
+    let total_pages = if total == 0 {
+        1
+    } else {
+        (total + page_size - 1) / page_size
+    };
+
+    // 获取分页数据
+    let mut stmt = conn
+        .prepare(&format!(
+            "SELECT {} FROM projects ORDER BY updated_at DESC LIMIT ? OFFSET ?",
+            PROJECT_COLUMNS
+        ))
+        .map_err(|e| {
+            println!("[DEBUG] prepare error: {}", e);
+            e.to_string()
+        })?;
+
+    let params: &[&dyn rusqlite::ToSql] = &[&(page_size as i64), &(offset as i64)];
+    println!(
+        "[DEBUG] query params: page_size={}, offset={}",
+        page_size as i64, offset as i64
+    );
+
+    let projects = stmt.query_map(params, project_from_row).map_err(|e| {
+        println!("[DEBUG] query_map error: {}", e);
+        e.to_string()
+    })?;
+
+    let result: Vec<Project> = projects
+        .map(|p| p.map_err(|e| e.to_string()))
+        .collect::<Result<_, _>>()?;
+
+    println!("[DEBUG] returning {} projects", result.len());
+
+    Ok(ProjectListResponse {
+        projects: result,
+        total,
+        page,
+        total_pages,
+    })
+}
+
+#[tauri::command]
+fn get_project_by_name(
+    db: tauri::State<DatabaseManager>,
+    name: String,
+) -> Result<Option<Project>, String> {
+    let conn = db.get_connection().map_err(|e| e.to_string())?;
+
+    let mut stmt = conn
+        .prepare(&format!(
+            "SELECT {} FROM projects WHERE name = ?",
+            PROJECT_COLUMNS
+        ))
+        .map_err(|e| e.to_string())?;
+
+    let mut rows = stmt.query(&[&name]).map_err(|e| e.to_string())?;
+
+    if let Some(row) = rows.next().map_err(|e| e.to_string())? {
+        Ok(Some(project_from_row(row).map_err(|e| e.to_string())?))
+    } else {
+        Ok(None)
+    }
+}
+
+#[tauri::command]
+fn get_project_by_id(
+    db: tauri::State<DatabaseManager>,
+    id: String,
+) -> Result<Option<Project>, String> {
+    let conn = db.get_connection().map_err(|e| e.to_string())?;
+    let mut stmt = conn
+        .prepare(&format!(
+            "SELECT {} FROM projects WHERE id = ?",
+            PROJECT_COLUMNS
+        ))
+        .map_err(|e| e.to_string())?;
+    let mut rows = stmt.query(&[&id]).map_err(|e| e.to_string())?;
+    if let Some(row) = rows.next().map_err(|e| e.to_string())? {
+        Ok(Some(project_from_row(row).map_err(|e| e.to_string())?))
+    } else {
+        Ok(None)
+    }
+}
+
+#[tauri::command]
+fn set_config(db: tauri::State<DatabaseManager>, key: String, value: String) -> Result<(), String> {
+    let conn = db.get_connection().map_err(|e| e.to_string())?;
+    conn.execute("INSERT INTO app_config(key,value) VALUES(?,?) ON CONFLICT(key) DO UPDATE SET value=excluded.value", &[&key, &value])
+        .map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+#[tauri::command]
+fn get_config(db: tauri::State<DatabaseManager>, key: String) -> Result<Option<String>, String> {
+    let conn = db.get_connection().map_err(|e| e.to_string())?;
+    let mut stmt = conn
+        .prepare("SELECT value FROM app_config WHERE key = ?")
+        .map_err(|e| e.to_string())?;
+    let mut rows = stmt.query(&[&key]).map_err(|e| e.to_string())?;
+    if let Some(row) = rows.next().map_err(|e| e.to_string())? {
+        let v: String = row.get(0).map_err(|e| e.to_string())?;
+        Ok(Some(v))
+    } else {
+        Ok(None)
+    }
+}
+
+#[tauri::command]
+fn set_current_project(
+    db: tauri::State<DatabaseManager>,
+    project_id: String,
+) -> Result<(), String> {
+    set_config(db, CFG_CURRENT_PROJECT.into(), project_id)
+}
+
+#[tauri::command]
+fn get_current_project(db: tauri::State<DatabaseManager>) -> Result<Option<Project>, String> {
+    if let Some(pid) = get_config(db.clone(), CFG_CURRENT_PROJECT.into())? {
+        if let Some(p) = get_project_by_id(db, pid)? {
+            return Ok(Some(p));
+        }
+    }
+    Ok(None)
+}
+
+/**
+ * 删除项目及其所有关联数据
+ *
+ * @param db 数据库管理器
+ * @param name 项目名称
+ * @return Result<(), String>
+ */
+#[tauri::command]
+fn delete_project(
+    db: tauri::State<DatabaseManager>,
+    watch: tauri::State<WatchState>,
+    name: String,
+) -> Result<(), String> {
+    let conn = db.get_connection().map_err(|e| e.to_string())?;
+    // 正在监听的项目先停止监听，避免继续写入已删除项目的结果
+    let project_id: Option<String> = conn
+        .query_row("SELECT id FROM projects WHERE name = ?", [&name], |row| {
+            row.get(0)
+        })
+        .ok();
+    if let Some(id) = project_id {
+        watch.stop_project(&id);
+    }
+    remove_project(&conn, &name)
+}
+
+#[tauri::command]
+fn start_scan(
+    window: tauri::Window,
+    project_name: String,
+    time_range: String,
+) -> Result<(), String> {
+    let db = window.state::<DatabaseManager>();
+    let conn = db.get_connection().map_err(|e| e.to_string())?;
+    let project_id = ensure_project(&conn, project_name, time_range)?;
+    let ctx = scan::ScanContext::load(&conn, &project_id)?;
+    scan::spawn_scan(window, ctx)
+}
+
+#[tauri::command]
+fn start_scan_by_id(window: tauri::Window, project_id: String) -> Result<(), String> {
+    let db = window.state::<DatabaseManager>();
+    let conn = db.get_connection().map_err(|e| e.to_string())?;
+    let ctx = scan::ScanContext::load(&conn, &project_id)?;
+    scan::spawn_scan(window, ctx)
+}
+
+#[tauri::command(rename_all = "snake_case")]
+fn list_collectors() -> Vec<CollectorInfo> {
+    CollectorRegistry::builtin().info()
+}
+
+#[tauri::command(rename_all = "snake_case")]
+fn set_project_collectors(
+    db: tauri::State<DatabaseManager>,
+    project_id: String,
+    collectors: Vec<String>,
+) -> Result<(), String> {
+    let collectors_json = validate_collectors(collectors)?;
+    let conn = db.get_connection().map_err(|e| e.to_string())?;
+    let changed = conn
+        .execute(
+            "UPDATE projects SET collectors = ? WHERE id = ?",
+            [&collectors_json, &project_id],
+        )
+        .map_err(|e| e.to_string())?;
+    if changed == 0 {
+        return Err("项目不存在".into());
+    }
+    Ok(())
+}
+
+/// 设置项目的时间窗口模式：rolling（按当前时间滚动）、frozen（固定为创建时的范围）、
+/// retain（滚动并保留移出范围的条目，标记为归档）
+#[tauri::command(rename_all = "snake_case")]
+fn set_project_window_mode(
+    db: tauri::State<DatabaseManager>,
+    project_id: String,
+    window_mode: String,
+) -> Result<(), String> {
+    let conn = db.get_connection().map_err(|e| e.to_string())?;
+    update_window_mode(&conn, &project_id, &window_mode)
+}
+
+#[tauri::command(rename_all = "snake_case")]
+fn get_collector_settings(
+    db: tauri::State<DatabaseManager>,
+    project_id: String,
+    collector_id: String,
+) -> Result<Option<serde_json::Value>, String> {
+    let conn = db.get_connection().map_err(|e| e.to_string())?;
+    let json: Option<String> = conn
+        .query_row(
+            "SELECT settings FROM collector_settings WHERE project_id = ? AND collector_id = ?",
+            [&project_id, &collector_id],
+            |row| row.get(0),
+        )
+        .ok();
+    match json {
+        Some(s) => serde_json::from_str(&s)
+            .map(Some)
+            .map_err(|e| e.to_string()),
+        None => Ok(None),
+    }
+}
+
+#[tauri::command(rename_all = "snake_case")]
+fn set_collector_settings(
+    db: tauri::State<DatabaseManager>,
+    project_id: String,
+    collector_id: String,
+    settings: serde_json::Value,
+) -> Result<(), String> {
+    if !CollectorRegistry::builtin().contains(&collector_id) {
+        return Err(format!("未知的采集器: {}", collector_id));
+    }
+    let conn = db.get_connection().map_err(|e| e.to_string())?;
+    let now = chrono::Local::now().format("%Y-%m-%d %H:%M:%S").to_string();
+    conn.execute(
+        "INSERT INTO collector_settings(project_id, collector_id, settings, updated_at) VALUES(?,?,?,?)
+         ON CONFLICT(project_id, collector_id) DO UPDATE SET settings=excluded.settings, updated_at=excluded.updated_at",
+        [&project_id, &collector_id, &settings.to_string(), &now],
+    )
+    .map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+/// 项目的目录遍历选项（符号链接、文件系统边界、深度、单目录文件数、隐藏目录），未设置时为默认值
+#[tauri::command(rename_all = "snake_case")]
+fn get_walk_options(
+    db: tauri::State<DatabaseManager>,
+    project_id: String,
+) -> Result<WalkOptions, String> {
+    let conn = db.get_connection().map_err(|e| e.to_string())?;
+    let json: Option<String> = conn
+        .query_row(
+            "SELECT walk_options FROM projects WHERE id = ?",
+            [&project_id],
+            |row| row.get(0),
+        )
+        .map_err(|_| "项目不存在".to_string())?;
+    match json {
+        Some(s) => serde_json::from_str(&s).map_err(|e| e.to_string()),
+        None => Ok(WalkOptions::default()),
+    }
+}
+
+#[tauri::command(rename_all = "snake_case")]
+fn set_walk_options(
+    db: tauri::State<DatabaseManager>,
+    project_id: String,
+    options: WalkOptions,
+) -> Result<(), String> {
+    let conn = db.get_connection().map_err(|e| e.to_string())?;
+    set_project_walk_options(&conn, &project_id, &options)
+}
+
+/// 导入用户手动选择的 .ics 文件，返回写入的会议条数
+#[tauri::command(rename_all = "snake_case")]
+fn import_calendar_files(
+    db: tauri::State<DatabaseManager>,
+    project_id: String,
+    paths: Vec<String>,
+) -> Result<usize, String> {
+    let mut conn = db.get_connection().map_err(|e| e.to_string())?;
+    collectors::import_calendar_files(&mut conn, &project_id, &paths)
+}
+
+/// 终端命令按工作目录与 Git 仓库分组统计
+#[tauri::command(rename_all = "snake_case")]
+fn get_shell_history_groups(
+    db: tauri::State<DatabaseManager>,
+    project_id: String,
+) -> Result<Vec<ShellHistoryGroup>, String> {
+    let conn = db.get_connection().map_err(|e| e.to_string())?;
+    collectors::shell_history_groups(&conn, &project_id)
+}
+
+/// 检索项目邮件（主题、收发件人、正文、附件名全文匹配）
+#[tauri::command(rename_all = "snake_case")]
+fn search_emails(
+    db: tauri::State<DatabaseManager>,
+    project_id: String,
+    q: Option<String>,
+    page: usize,
+    page_size: usize,
+) -> Result<ProjectListResponseLike<EmailItem>, String> {
+    let conn = db.get_connection().map_err(|e| e.to_string())?;
+    collectors::search_emails(&conn, &project_id, q.as_deref(), page, page_size)
+}
+
+/// 按日 / 周 / 月聚合项目各来源的数据，granularity 取 day、week、month
+#[tauri::command(rename_all = "snake_case")]
+fn get_timeline(
+    db: tauri::State<DatabaseManager>,
+    project_id: String,
+    granularity: String,
+    sources: Option<Vec<String>>,
+    top_n: Option<usize>,
+) -> Result<timeline::Timeline, String> {
+    let conn = db.get_connection().map_err(|e| e.to_string())?;
+    timeline::build(&conn, &project_id, &granularity, sources.as_deref(), top_n)
+}
+
+/// 生成回顾报告（Markdown + HTML），写入项目目录
+#[tauri::command(rename_all = "snake_case")]
+fn generate_report(
+    db: tauri::State<DatabaseManager>,
+    project_id: String,
+) -> Result<report::GeneratedReport, String> {
+    let conn = db.get_connection().map_err(|e| e.to_string())?;
+    report::generate(&conn, &project_id)
+}
+
+/// 把内置报告模板写入自定义模板目录，返回目录路径
+#[tauri::command(rename_all = "snake_case")]
+fn init_report_templates() -> Result<String, String> {
+    report::init_templates()
+}
+
+/// 分页获取扫描结果；传 cursor 时按游标分页（空字符串为第一页），见 results 模块
+#[tauri::command(rename_all = "snake_case")]
+fn get_results_paginated(
+    db: tauri::State<DatabaseManager>,
+    project_id: String,
+    page: usize,
+    page_size: usize,
+    cursor: Option<String>,
+) -> Result<ProjectListResponseLike<ScanResultItem>, String> {
+    let conn = db.get_connection().map_err(|e| e.to_string())?;
+    results::fetch(
+        &conn,
+        &project_id,
+        &results::ResultQuery::default(),
+        results::Paging::from_args(page, cursor.as_deref()),
+        page_size,
+    )
+}
+
+/// 多条件筛选、排序并附带分面计数的结果查询，见 results 模块
+#[tauri::command(rename_all = "snake_case")]
+fn query_scan_results(
+    db: tauri::State<DatabaseManager>,
+    project_id: String,
+    query: Option<results::ResultQuery>,
+    page: usize,
+    page_size: usize,
+    cursor: Option<String>,
+) -> Result<results::ResultQueryResponse, String> {
+    let conn = db.get_connection().map_err(|e| e.to_string())?;
+    results::query(
+        &conn,
+        &project_id,
+        &query.unwrap_or_default(),
+        results::Paging::from_args(page, cursor.as_deref()),
+        page_size,
+    )
+}
+
+#[tauri::command(rename_all = "snake_case")]
+fn get_results_paginated_adv(
+    db: tauri::State<DatabaseManager>,
+    project_id: String,
+    page: usize,
+    page_size: usize,
+    q: Option<String>,
+    file_types: Option<Vec<String>>,
+    cursor: Option<String>,
+) -> Result<ProjectListResponseLike<ScanResultItem>, String> {
+    let conn = db.get_connection().map_err(|e| e.to_string())?;
+    results::fetch(
+        &conn,
+        &project_id,
+        &results::ResultQuery::simple(q.as_deref(), file_types.as_deref()),
+        results::Paging::from_args(page, cursor.as_deref()),
+        page_size,
+    )
+}
+
+/// 导出项目结果，format 取 csv、jsonl、xlsx；q / file_types 与 get_results_paginated_adv 一致
+#[tauri::command(rename_all = "snake_case")]
+fn export_results(
+    db: tauri::State<DatabaseManager>,
+    project_id: String,
+    format: String,
+    tables: Option<Vec<String>>,
+    q: Option<String>,
+    file_types: Option<Vec<String>>,
+    output_dir: Option<String>,
+) -> Result<export::ExportResult, String> {
+    let conn = db.get_connection().map_err(|e| e.to_string())?;
+    export::export(
+        &conn,
+        &project_id,
+        &export::ExportOptions {
+            format: &format,
+            tables: tables.as_deref(),
+            q: q.as_deref(),
+            file_types: file_types.as_deref(),
+            output_dir: output_dir.as_deref(),
+        },
+    )
+}
+
+/// 导出项目归档（项目记录、各数据表与项目目录），返回归档路径
+#[tauri::command(rename_all = "snake_case")]
+fn export_project(
+    db: tauri::State<DatabaseManager>,
+    project_id: String,
+    output_path: Option<String>,
+) -> Result<String, String> {
+    let conn = db.get_connection().map_err(|e| e.to_string())?;
+    archive::export_project(&conn, &project_id, output_path.as_deref())
+}
+
+/// 导入项目归档；ID 或名称冲突时自动改名，返回导入后的项目
+#[tauri::command(rename_all = "snake_case")]
+fn import_project(
+    db: tauri::State<DatabaseManager>,
+    archive_path: String,
+) -> Result<Project, String> {
+    let mut conn = db.get_connection().map_err(|e| e.to_string())?;
+    let project_id = archive::import_project(&mut conn, &archive_path)?;
+    conn.query_row(
+        &format!("SELECT {} FROM projects WHERE id = ?", PROJECT_COLUMNS),
+        [&project_id],
+        project_from_row,
+    )
+    .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+fn get_project_scan_roots(
+    db: tauri::State<DatabaseManager>,
+    project_id: String,
+) -> Result<Vec<String>, String> {
+    let conn = db.get_connection().map_err(|e| e.to_string())?;
+    let mut stmt = conn
+        .prepare("SELECT folder_path, scan_scope, scan_folders FROM projects WHERE id = ?")
+        .map_err(|e| e.to_string())?;
+    let mut rows = stmt.query(&[&project_id]).map_err(|e| e.to_string())?;
+    if let Some(row) = rows.next().map_err(|e| e.to_string())? {
+        let project_folder: String = row.get(0).map_err(|e| e.to_string())?;
+        let scan_scope: Option<String> = row.get(1).ok();
+        let scan_folders_json: Option<String> = row.get(2).ok();
+        let roots = scan::resolve_scan_roots(
+            &project_folder,
+            scan_scope.as_deref(),
+            scan_folders_json.as_deref(),
+        )
+        .into_iter()
+        .map(|p| p.to_string_lossy().to_string())
+        .collect();
+        return Ok(roots);
+    }
+    Ok(vec![])
+}
+
+#[tauri::command]
+fn get_scan_summary(state: tauri::State<Mutex<Option<ScanSummary>>>) -> Option<ScanSummary> {
+    state.lock().ok().and_then(|s| s.clone())
+}
+
+/// 项目的扫描运行记录，最近的在前；limit 默认 20
+#[tauri::command(rename_all = "snake_case")]
+fn get_scan_runs(
+    db: tauri::State<DatabaseManager>,
+    project_id: String,
+    limit: Option<usize>,
+) -> Result<Vec<ScanRunRecord>, String> {
+    let conn = db.get_connection().map_err(|e| e.to_string())?;
+    let mut stmt = conn
+        .prepare(
+            "SELECT id, project_id, started_at, finished_at, duration_ms, skipped, failed, summary
+             FROM scan_runs WHERE project_id = ? ORDER BY started_at DESC LIMIT ?",
+        )
+        .map_err(|e| e.to_string())?;
+    let rows = stmt
+        .query_map(
+            rusqlite::params![project_id, limit.unwrap_or(20) as i64],
+            |row| {
+                let summary: String = row.get(7)?;
+                Ok(ScanRunRecord {
+                    id: row.get(0)?,
+                    project_id: row.get(1)?,
+                    started_at: row.get(2)?,
+                    finished_at: row.get(3)?,
+                    duration_ms: row.get(4)?,
+                    skipped: row.get(5)?,
+                    failed: row.get(6)?,
+                    summary: serde_json::from_str(&summary).unwrap_or(serde_json::Value::Null),
+                })
+            },
+        )
+        .map_err(|e| e.to_string())?;
+    rows.collect::<Result<_, _>>().map_err(|e| e.to_string())
+}
+
+/// 监听项目的扫描根目录，变更实时同步到 scan_results 并推送 results-changed；会替换正在进行的监听
+#[tauri::command(rename_all = "snake_case")]
+fn start_watch(
+    app: tauri::AppHandle,
+    db: tauri::State<DatabaseManager>,
+    watch: tauri::State<WatchState>,
+    project_id: String,
+) -> Result<WatchStatus, String> {
+    let conn = db.get_connection().map_err(|e| e.to_string())?;
+    let ctx = scan::ScanContext::load(&conn, &project_id)?;
+    watch.start(app, ctx)
+}
+
+#[tauri::command(rename_all = "snake_case")]
+fn stop_watch(watch: tauri::State<WatchState>) -> bool {
+    watch.stop()
+}
+
+#[tauri::command(rename_all = "snake_case")]
+fn get_watch_status(watch: tauri::State<WatchState>) -> Option<WatchStatus> {
+    watch.status()
+}
+
+/// 列出定时重扫计划；不传 project_id 时列出全部项目的计划
+#[tauri::command(rename_all = "snake_case")]
+fn list_schedules(
+    db: tauri::State<DatabaseManager>,
+    project_id: Option<String>,
+) -> Result<Vec<Schedule>, String> {
+    let conn = db.get_connection().map_err(|e| e.to_string())?;
+    schedule::list(&conn, project_id.as_deref())
+}
+
+/// 新建计划，cron 为五段式表达式（分 时 日 月 周）或 @daily 等简写
+#[tauri::command(rename_all = "snake_case")]
+fn create_schedule(
+    db: tauri::State<DatabaseManager>,
+    project_id: String,
+    cron: String,
+) -> Result<Schedule, String> {
+    let conn = db.get_connection().map_err(|e| e.to_string())?;
+    schedule::create(&conn, &project_id, &cron)
+}
+
+#[tauri::command(rename_all = "snake_case")]
+fn update_schedule(
+    db: tauri::State<DatabaseManager>,
+    id: String,
+    cron: Option<String>,
+    enabled: Option<bool>,
+) -> Result<Schedule, String> {
+    let conn = db.get_connection().map_err(|e| e.to_string())?;
+    schedule::update(&conn, &id, cron.as_deref(), enabled)
+}
+
+#[tauri::command(rename_all = "snake_case")]
+fn delete_schedule(db: tauri::State<DatabaseManager>, id: String) -> Result<(), String> {
+    let conn = db.get_connection().map_err(|e| e.to_string())?;
+    schedule::delete(&conn, &id)
+}
+
+/// 计划的执行记录，最近的在前；limit 默认 20
+#[tauri::command(rename_all = "snake_case")]
+fn get_schedule_runs(
+    db: tauri::State<DatabaseManager>,
+    schedule_id: String,
+    limit: Option<usize>,
+) -> Result<Vec<ScheduleRun>, String> {
+    let conn = db.get_connection().map_err(|e| e.to_string())?;
+    schedule::runs(&conn, &schedule_id, limit.unwrap_or(20))
+}
+
+/// 列出项目保存的查询（智能集合），count 为当前结果数
+#[tauri::command(rename_all = "snake_case")]
+fn list_saved_searches(
+    db: tauri::State<DatabaseManager>,
+    project_id: String,
+) -> Result<Vec<SavedSearch>, String> {
+    let conn = db.get_connection().map_err(|e| e.to_string())?;
+    collection::list(&conn, &project_id)
+}
+
+/// 保存查询，query 与 query_scan_results 的条件相同；pinned 为 true 时在报告中展示
+#[tauri::command(rename_all = "snake_case")]
+fn create_saved_search(
+    db: tauri::State<DatabaseManager>,
+    project_id: String,
+    name: String,
+    query: results::ResultQuery,
+    pinned: Option<bool>,
+) -> Result<SavedSearch, String> {
+    let conn = db.get_connection().map_err(|e| e.to_string())?;
+    collection::create(&conn, &project_id, &name, &query, pinned.unwrap_or(false))
+}
+
+#[tauri::command(rename_all = "snake_case")]
+fn update_saved_search(
+    db: tauri::State<DatabaseManager>,
+    id: String,
+    name: Option<String>,
+    query: Option<results::ResultQuery>,
+    pinned: Option<bool>,
+) -> Result<SavedSearch, String> {
+    let conn = db.get_connection().map_err(|e| e.to_string())?;
+    collection::update(&conn, &id, name.as_deref(), query.as_ref(), pinned)
+}
+
+#[tauri::command(rename_all = "snake_case")]
+fn delete_saved_search(db: tauri::State<DatabaseManager>, id: String) -> Result<(), String> {
+    let conn = db.get_connection().map_err(|e| e.to_string())?;
+    collection::delete(&conn, &id)
+}
+
+/// 打开集合：按保存的条件查询结果，分页参数与 query_scan_results 相同
+#[tauri::command(rename_all = "snake_case")]
+fn open_saved_search(
+    db: tauri::State<DatabaseManager>,
+    id: String,
+    page: usize,
+    page_size: usize,
+    cursor: Option<String>,
+) -> Result<results::ResultQueryResponse, String> {
+    let conn = db.get_connection().map_err(|e| e.to_string())?;
+    collection::open(
+        &conn,
+        &id,
+        results::Paging::from_args(page, cursor.as_deref()),
+        page_size,
+    )
+}
+
+/// 批量给结果加标签、备注、星标或排除出报告，按文件路径指定；返回处理的文件数
+#[tauri::command(rename_all = "snake_case")]
+fn annotate_results(
+    db: tauri::State<DatabaseManager>,
+    project_id: String,
+    file_paths: Vec<String>,
+    patch: curation::AnnotationPatch,
+) -> Result<usize, String> {
+    let mut conn = db.get_connection().map_err(|e| e.to_string())?;
+    curation::annotate(&mut conn, &project_id, &file_paths, &patch)
+}
+
+/// 项目中使用过的标签及文件数
+#[tauri::command(rename_all = "snake_case")]
+fn list_result_tags(
+    db: tauri::State<DatabaseManager>,
+    project_id: String,
+) -> Result<Vec<curation::TagCount>, String> {
+    let conn = db.get_connection().map_err(|e| e.to_string())?;
+    curation::list_tags(&conn, &project_id)
+}
+
+/// 文件内嵌的元数据（EXIF、Office 文档属性、PDF 文档信息），未提取过或不支持的类型返回 null
+#[tauri::command(rename_all = "snake_case")]
+fn get_result_metadata(
+    db: tauri::State<DatabaseManager>,
+    project_id: String,
+    file_path: String,
+) -> Result<Option<metadata::FileMetadata>, String> {
+    let conn = db.get_connection().map_err(|e| e.to_string())?;
+    metadata::get(&conn, &project_id, &file_path)
+}
+
+/// 获取图片或 PDF 首页的缩略图：已缓存时返回文件路径；否则在后台生成并返回 null，生成后推送 thumbnail-ready
+#[tauri::command(rename_all = "snake_case")]
+fn get_thumbnail(
+    db: tauri::State<DatabaseManager>,
+    thumbnails: tauri::State<ThumbnailService>,
+    project_id: String,
+    file_path: String,
+    size: Option<u32>,
+) -> Result<Option<String>, String> {
+    let conn = db.get_connection().map_err(|e| e.to_string())?;
+    let target = thumbnail::locate(&conn, &project_id, &file_path, size)?;
+    Ok(thumbnails
+        .request(target)?
+        .map(|p| p.to_string_lossy().to_string()))
+}
+
+/// 读取缩略图的 PNG 字节，未缓存时当场生成（不在主线程执行）
+#[tauri::command(async, rename_all = "snake_case")]
+fn get_thumbnail_bytes(
+    db: tauri::State<DatabaseManager>,
+    project_id: String,
+    file_path: String,
+    size: Option<u32>,
+) -> Result<tauri::ipc::Response, String> {
+    let conn = db.get_connection().map_err(|e| e.to_string())?;
+    let target = thumbnail::locate(&conn, &project_id, &file_path, size)?;
+    let path = thumbnail::ensure(&target)?;
+    let bytes = std::fs::read(path).map_err(|e| e.to_string())?;
+    Ok(tauri::ipc::Response::new(bytes))
+}
+
+#[cfg_attr(mobile, tauri::mobile_entry_point)]
+pub fn run() {
+    let db_manager = DatabaseManager::new();
+
+    // 初始化数据库（忽略错误，允许首次运行失败）
+    let _ = db_manager.initialize();
+
+    tauri::Builder::default()
+        .manage(db_manager)
+        .manage::<Mutex<Option<ScanSummary>>>(Mutex::new(None))
+        .manage(WatchState::default())
+        .plugin(tauri_plugin_dialog::init())
+        .plugin(tauri_plugin_opener::init())
+        .plugin(tauri_plugin_fs::init())
+        .setup(|app| {
+            // 定时重扫在后台线程中运行，启动时先补跑错过的计划
+            schedule::start(app.handle().clone());
+            app.manage(ThumbnailService::start(app.handle().clone()));
+            Ok(())
+        })
+        .invoke_handler(tauri::generate_handler![
+            init_database,
+            create_project,
+            get_projects,
+            get_projects_paginated,
+            get_project_by_name,
+            get_project_by_id,
+            delete_project,
+            start_scan,
+            start_scan_by_id,
+            list_collectors,
+            set_project_collectors,
+            set_project_window_mode,
+            get_collector_settings,
+            set_collector_settings,
+            get_walk_options,
+            set_walk_options,
+            import_calendar_files,
+            get_shell_history_groups,
+            search_emails,
+            get_timeline,
+            generate_report,
+            init_report_templates,
+            get_results_paginated,
+            get_results_paginated_adv,
+            query_scan_results,
+            export_results,
+            export_project,
+            import_project,
+            get_project_scan_roots,
+            get_scan_summary,
+            get_scan_runs,
+            start_watch,
+            stop_watch,
+            get_watch_status,
+            list_schedules,
+            create_schedule,
+            update_schedule,
+            delete_schedule,
+            get_schedule_runs,
+            list_saved_searches,
+            create_saved_search,
+            update_saved_search,
+            delete_saved_search,
+            open_saved_search,
+            annotate_results,
+            list_result_tags,
+            get_result_metadata,
+            get_thumbnail,
+            get_thumbnail_bytes,
+            set_config,
+            get_config,
+            set_current_project,
+            get_current_project
+        ])
+        .run(tauri::generate_context!())
+        .expect("error while running tauri application");
+}
@@ -0,0 +1,219 @@
+//! 人工整理：给扫描结果加标签、备注、星标等级与“不写入报告”标记
+//! 保存在独立的表中、按 (project_id, file_path) 关联，重新扫描删除并重建 scan_results 时不受影响，
+//! 文件改名或移动时由 identity 模块迁移到新路径；
+//! 结果查询可按这些字段筛选，报告会跳过排除的文件并单独列出星标文件
+
+#[cfg(feature = "gui")]
+use chrono::Local;
+#[cfg(feature = "gui")]
+use rusqlite::params;
+use rusqlite::Connection;
+#[cfg(feature = "gui")]
+use serde::{Deserialize, Serialize};
+
+#[cfg(feature = "gui")]
+use crate::identity;
+
+#[cfg(feature = "gui")]
+const TIME_FORMAT: &str = "%Y-%m-%d %H:%M:%S";
+
+/// 星标最高等级，0 为未标记
+#[cfg(feature = "gui")]
+pub(crate) const MAX_STAR: u8 = 3;
+
+/// 标签在 group_concat 中的分隔符，不会出现在标签里
+const TAG_SEPARATOR: char = '\u{1f}';
+
+/// scan_results 行对应的备注记录（用于相关子查询，外层查询须为不带别名的 scan_results）
+const ANNOTATION_OF_ROW: &str =
+    "a.project_id = scan_results.project_id AND a.file_path = scan_results.file_path";
+const TAG_OF_ROW: &str =
+    "t.project_id = scan_results.project_id AND t.file_path = scan_results.file_path";
+
+pub(crate) fn init_schema(conn: &Connection) -> rusqlite::Result<()> {
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS result_annotations (
+            project_id TEXT NOT NULL,
+            file_path TEXT NOT NULL,
+            note TEXT NOT NULL DEFAULT '',
+            star INTEGER NOT NULL DEFAULT 0,
+            excluded INTEGER NOT NULL DEFAULT 0,
+            updated_at TEXT NOT NULL,
+            PRIMARY KEY (project_id, file_path)
+        )",
+        [],
+    )?;
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS result_tags (
+            project_id TEXT NOT NULL,
+            file_path TEXT NOT NULL,
+            tag TEXT NOT NULL,
+            created_at TEXT NOT NULL,
+            PRIMARY KEY (project_id, file_path, tag)
+        )",
+        [],
+    )?;
+    conn.execute(
+        "CREATE INDEX IF NOT EXISTS idx_result_tags_tag ON result_tags(project_id, tag)",
+        [],
+    )?;
+    Ok(())
+}
+
+/// 查询 scan_results 时附带的整理字段（依次为 note、star、excluded、tags）
+pub(crate) fn select_columns() -> String {
+    format!(
+        "(SELECT note FROM result_annotations a WHERE {0}), \
+         (SELECT star FROM result_annotations a WHERE {0}), \
+         (SELECT excluded FROM result_annotations a WHERE {0}), \
+         (SELECT group_concat(tag, char(31)) FROM (SELECT tag FROM result_tags t WHERE {1} ORDER BY tag))",
+        ANNOTATION_OF_ROW, TAG_OF_ROW
+    )
+}
+
+/// select_columns 中 tags 列的值拆分为标签列表
+pub(crate) fn split_tags(tags: Option<String>) -> Vec<String> {
+    tags.map(|t| t.split(TAG_SEPARATOR).map(str::to_string).collect())
+        .unwrap_or_default()
+}
+
+/// 带任一标签的 scan_results 行的条件
+pub(crate) fn has_any_tag_sql(count: usize) -> String {
+    format!(
+        "EXISTS (SELECT 1 FROM result_tags t WHERE {} AND t.tag IN ({}))",
+        TAG_OF_ROW,
+        vec!["?"; count].join(",")
+    )
+}
+
+/// scan_results 行的某个整理字段（无记录时取默认值），用于筛选条件
+pub(crate) fn annotation_expr(column: &str) -> String {
+    let default = if column == "note" { "''" } else { "0" };
+    format!(
+        "COALESCE((SELECT {} FROM result_annotations a WHERE {}), {})",
+        column, ANNOTATION_OF_ROW, default
+    )
+}
+
+/// 未排除出报告的 scan_results 行的条件
+pub(crate) fn included_sql() -> String {
+    format!("{} = 0", annotation_expr("excluded"))
+}
+
+/// 批量整理的修改内容，未传的字段保持不变
+#[cfg(feature = "gui")]
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub(crate) struct AnnotationPatch {
+    /// 备注，空字符串清除
+    pub note: Option<String>,
+    /// 星标等级 0–3
+    pub star: Option<u8>,
+    /// 不写入报告
+    pub excluded: Option<bool>,
+    pub add_tags: Vec<String>,
+    pub remove_tags: Vec<String>,
+}
+
+#[cfg(feature = "gui")]
+#[derive(Debug, Clone, Serialize)]
+pub(crate) struct TagCount {
+    pub tag: String,
+    pub count: usize,
+}
+
+#[cfg(feature = "gui")]
+fn normalize_tags(tags: &[String]) -> Vec<String> {
+    let mut out: Vec<String> = tags
+        .iter()
+        .map(|t| t.trim().replace(TAG_SEPARATOR, ""))
+        .filter(|t| !t.is_empty())
+        .collect();
+    out.sort();
+    out.dedup();
+    out
+}
+
+/// 对一批文件应用修改，返回处理的文件数
+#[cfg(feature = "gui")]
+pub(crate) fn annotate(
+    conn: &mut Connection,
+    project_id: &str,
+    file_paths: &[String],
+    patch: &AnnotationPatch,
+) -> Result<usize, String> {
+    if patch.star.is_some_and(|s| s > MAX_STAR) {
+        return Err(format!("星标等级应为 0–{}", MAX_STAR));
+    }
+    let add_tags = normalize_tags(&patch.add_tags);
+    let remove_tags = normalize_tags(&patch.remove_tags);
+    let now = Local::now().format(TIME_FORMAT).to_string();
+    let tx = conn.transaction().map_err(|e| e.to_string())?;
+    for path in file_paths {
+        if patch.note.is_some() || patch.star.is_some() || patch.excluded.is_some() {
+            tx.execute(
+                "INSERT INTO result_annotations (project_id, file_path, note, star, excluded, updated_at)
+                 VALUES (?1, ?2, COALESCE(?3, ''), COALESCE(?4, 0), COALESCE(?5, 0), ?6)
+                 ON CONFLICT(project_id, file_path) DO UPDATE SET
+                    note = COALESCE(?3, note),
+                    star = COALESCE(?4, star),
+                    excluded = COALESCE(?5, excluded),
+                    updated_at = ?6",
+                params![
+                    project_id,
+                    path,
+                    patch.note.as_deref().map(str::trim),
+                    patch.star,
+                    patch.excluded.map(|e| e as i64),
+                    now
+                ],
+            )
+            .map_err(|e| e.to_string())?;
+            // 全部恢复默认的记录不再保留
+            tx.execute(
+                "DELETE FROM result_annotations WHERE project_id = ? AND file_path = ?
+                 AND note = '' AND star = 0 AND excluded = 0",
+                params![project_id, path],
+            )
+            .map_err(|e| e.to_string())?;
+        }
+        for tag in &add_tags {
+            tx.execute(
+                "INSERT OR IGNORE INTO result_tags (project_id, file_path, tag, created_at) VALUES (?, ?, ?, ?)",
+                params![project_id, path, tag, now],
+            )
+            .map_err(|e| e.to_string())?;
+        }
+        for tag in &remove_tags {
+            tx.execute(
+                "DELETE FROM result_tags WHERE project_id = ? AND file_path = ? AND tag = ?",
+                params![project_id, path, tag],
+            )
+            .map_err(|e| e.to_string())?;
+        }
+        // 记下内容哈希，文件之后被移动时整理数据能跟过去
+        identity::remember_hash(&tx, project_id, path).map_err(|e| e.to_string())?;
+    }
+    tx.commit().map_err(|e| e.to_string())?;
+    Ok(file_paths.len())
+}
+
+/// 项目中使用过的标签及文件数，按数量倒序
+#[cfg(feature = "gui")]
+pub(crate) fn list_tags(conn: &Connection, project_id: &str) -> Result<Vec<TagCount>, String> {
+    let mut stmt = conn
+        .prepare(
+            "SELECT tag, COUNT(*) FROM result_tags WHERE project_id = ?
+             GROUP BY tag ORDER BY COUNT(*) DESC, tag",
+        )
+        .map_err(|e| e.to_string())?;
+    let rows = stmt
+        .query_map([project_id], |row| {
+            Ok(TagCount {
+                tag: row.get(0)?,
+                count: row.get::<_, i64>(1)? as usize,
+            })
+        })
+        .map_err(|e| e.to_string())?;
+    rows.collect::<Result<_, _>>().map_err(|e| e.to_string())
+}
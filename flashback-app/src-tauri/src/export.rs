@@ -0,0 +1,277 @@
+//! 结果导出：把项目的 scan_results 及各采集器的数据表导出为 CSV / JSON Lines / Excel
+//! scan_results 使用与 get_results_paginated_adv 相同的筛选条件；逐行读取逐行写出，不在内存中缓存整表
+//! CSV 与 JSONL 每张表一个文件，Excel 每张表一个工作表，超过单表行数上限时续写到新工作表
+
+use rusqlite::types::ValueRef;
+use rusqlite::Connection;
+use serde::Serialize;
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::{Path, PathBuf};
+
+use crate::collectors::CollectorRegistry;
+
+/// Excel 单个工作表最多 1048576 行，扣除表头
+const XLSX_MAX_ROWS: u32 = 1_048_575;
+/// Excel 单元格最多 32767 个字符
+const XLSX_MAX_CELL_CHARS: usize = 32_767;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ExportFormat {
+    Csv,
+    Jsonl,
+    Xlsx,
+}
+
+impl ExportFormat {
+    fn parse(s: &str) -> Result<Self, String> {
+        match s {
+            "csv" => Ok(ExportFormat::Csv),
+            "jsonl" => Ok(ExportFormat::Jsonl),
+            "xlsx" => Ok(ExportFormat::Xlsx),
+            _ => Err(format!("不支持的导出格式: {}", s)),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub(crate) struct ExportedTable {
+    pub(crate) table: String,
+    pub(crate) rows: usize,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub(crate) struct ExportResult {
+    /// 写出的文件
+    pub(crate) files: Vec<String>,
+    pub(crate) tables: Vec<ExportedTable>,
+}
+
+/// 导出选项：tables 为空表示全部表；q / file_types 只作用于 scan_results；
+/// output_dir 为空时写入项目目录
+pub(crate) struct ExportOptions<'a> {
+    pub format: &'a str,
+    pub tables: Option<&'a [String]>,
+    pub q: Option<&'a str>,
+    pub file_types: Option<&'a [String]>,
+    pub output_dir: Option<&'a str>,
+}
+
+/// 可导出的表：scan_results 加各采集器的表，不含全文索引等虚拟表
+pub(crate) fn exportable_tables(conn: &Connection) -> Result<Vec<&'static str>, String> {
+    let mut tables = vec!["scan_results"];
+    for table in CollectorRegistry::builtin().project_tables() {
+        let is_virtual: bool = conn
+            .query_row(
+                "SELECT COALESCE(sql LIKE 'CREATE VIRTUAL TABLE%', 0) FROM sqlite_master WHERE type = 'table' AND name = ?",
+                [table],
+                |row| row.get(0),
+            )
+            .map_err(|e| e.to_string())?;
+        if !is_virtual && !tables.contains(&table) {
+            tables.push(table);
+        }
+    }
+    Ok(tables)
+}
+
+pub(crate) fn export(
+    conn: &Connection,
+    project_id: &str,
+    opts: &ExportOptions,
+) -> Result<ExportResult, String> {
+    let format = ExportFormat::parse(opts.format)?;
+    let all = exportable_tables(conn)?;
+    let tables: Vec<&str> = match opts.tables.filter(|t| !t.is_empty()) {
+        Some(wanted) => {
+            if let Some(unknown) = wanted.iter().find(|t| !all.contains(&t.as_str())) {
+                return Err(format!("不支持导出的表: {}", unknown));
+            }
+            all.into_iter()
+                .filter(|t| wanted.iter().any(|w| w == t))
+                .collect()
+        }
+        None => all,
+    };
+
+    let dir = match opts.output_dir.filter(|s| !s.trim().is_empty()) {
+        Some(d) => PathBuf::from(d),
+        None => conn
+            .query_row(
+                "SELECT folder_path FROM projects WHERE id = ?",
+                [project_id],
+                |row| row.get::<_, String>(0),
+            )
+            .map(PathBuf::from)
+            .map_err(|_| "项目不存在".to_string())?,
+    };
+    std::fs::create_dir_all(&dir).map_err(|e| e.to_string())?;
+
+    let mut files = Vec::new();
+    let mut exported = Vec::new();
+    let mut workbook = rust_xlsxwriter::Workbook::new();
+    for table in &tables {
+        let (where_sql, params) = if *table == "scan_results" {
+            crate::results_filter(project_id, opts.q, opts.file_types)
+        } else {
+            crate::results_filter(project_id, None, None)
+        };
+        let sql = format!("SELECT * FROM {} WHERE {} ORDER BY rowid", table, where_sql);
+        let mut stmt = conn.prepare(&sql).map_err(|e| e.to_string())?;
+        let columns: Vec<String> = stmt.column_names().iter().map(|c| c.to_string()).collect();
+        let mut rows = stmt
+            .query(rusqlite::params_from_iter(params.iter().map(|b| &**b)))
+            .map_err(|e| e.to_string())?;
+
+        let count = match format {
+            ExportFormat::Csv => {
+                let path = dir.join(format!("flashback-{}.csv", table));
+                let n = write_csv(&path, &columns, &mut rows)?;
+                files.push(path.to_string_lossy().to_string());
+                n
+            }
+            ExportFormat::Jsonl => {
+                let path = dir.join(format!("flashback-{}.jsonl", table));
+                let n = write_jsonl(&path, &columns, &mut rows)?;
+                files.push(path.to_string_lossy().to_string());
+                n
+            }
+            ExportFormat::Xlsx => write_sheets(&mut workbook, table, &columns, &mut rows)?,
+        };
+        exported.push(ExportedTable {
+            table: table.to_string(),
+            rows: count,
+        });
+    }
+
+    if format == ExportFormat::Xlsx {
+        let path = dir.join("flashback-export.xlsx");
+        workbook.save(&path).map_err(|e| e.to_string())?;
+        files.push(path.to_string_lossy().to_string());
+    }
+
+    Ok(ExportResult {
+        files,
+        tables: exported,
+    })
+}
+
+fn value_to_string(v: ValueRef) -> String {
+    match v {
+        ValueRef::Null => String::new(),
+        ValueRef::Integer(i) => i.to_string(),
+        ValueRef::Real(f) => f.to_string(),
+        ValueRef::Text(t) => String::from_utf8_lossy(t).to_string(),
+        ValueRef::Blob(b) => format!("<{} bytes>", b.len()),
+    }
+}
+
+fn value_to_json(v: ValueRef) -> serde_json::Value {
+    match v {
+        ValueRef::Null => serde_json::Value::Null,
+        ValueRef::Integer(i) => i.into(),
+        ValueRef::Real(f) => f.into(),
+        ValueRef::Text(t) => String::from_utf8_lossy(t).to_string().into(),
+        ValueRef::Blob(b) => format!("<{} bytes>", b.len()).into(),
+    }
+}
+
+fn write_csv(path: &Path, columns: &[String], rows: &mut rusqlite::Rows) -> Result<usize, String> {
+    let file = File::create(path).map_err(|e| e.to_string())?;
+    let mut out = BufWriter::new(file);
+    // 写入 UTF-8 BOM，Excel 直接打开时中文不乱码
+    out.write_all("\u{feff}".as_bytes())
+        .map_err(|e| e.to_string())?;
+    let mut writer = csv::Writer::from_writer(out);
+    writer.write_record(columns).map_err(|e| e.to_string())?;
+    let mut count = 0usize;
+    while let Some(row) = rows.next().map_err(|e| e.to_string())? {
+        let mut record = Vec::with_capacity(columns.len());
+        for i in 0..columns.len() {
+            record.push(value_to_string(row.get_ref(i).map_err(|e| e.to_string())?));
+        }
+        writer.write_record(&record).map_err(|e| e.to_string())?;
+        count += 1;
+    }
+    writer.flush().map_err(|e| e.to_string())?;
+    Ok(count)
+}
+
+fn write_jsonl(
+    path: &Path,
+    columns: &[String],
+    rows: &mut rusqlite::Rows,
+) -> Result<usize, String> {
+    let file = File::create(path).map_err(|e| e.to_string())?;
+    let mut out = BufWriter::new(file);
+    let mut count = 0usize;
+    while let Some(row) = rows.next().map_err(|e| e.to_string())? {
+        let mut obj = serde_json::Map::with_capacity(columns.len());
+        for (i, name) in columns.iter().enumerate() {
+            obj.insert(
+                name.clone(),
+                value_to_json(row.get_ref(i).map_err(|e| e.to_string())?),
+            );
+        }
+        serde_json::to_writer(&mut out, &obj).map_err(|e| e.to_string())?;
+        out.write_all(b"\n").map_err(|e| e.to_string())?;
+        count += 1;
+    }
+    out.flush().map_err(|e| e.to_string())?;
+    Ok(count)
+}
+
+/// 写入一张表；超出单表行数时续写到 `<表名> (2)` 等工作表
+fn write_sheets(
+    workbook: &mut rust_xlsxwriter::Workbook,
+    table: &str,
+    columns: &[String],
+    rows: &mut rusqlite::Rows,
+) -> Result<usize, String> {
+    let header = rust_xlsxwriter::Format::new().set_bold();
+    let mut count = 0usize;
+    let mut part = 1usize;
+    loop {
+        let name = if part == 1 {
+            table.to_string()
+        } else {
+            format!("{} ({})", table, part)
+        };
+        // 常量内存模式：按行顺序写出并及时落盘
+        let sheet = workbook.add_worksheet_with_constant_memory();
+        sheet.set_name(&name).map_err(|e| e.to_string())?;
+        for (col, name) in columns.iter().enumerate() {
+            sheet
+                .write_string_with_format(0, col as u16, name, &header)
+                .map_err(|e| e.to_string())?;
+        }
+        sheet.set_freeze_panes(1, 0).map_err(|e| e.to_string())?;
+
+        let mut row_idx = 1u32;
+        while row_idx <= XLSX_MAX_ROWS {
+            let row = match rows.next().map_err(|e| e.to_string())? {
+                Some(r) => r,
+                None => return Ok(count),
+            };
+            for col in 0..columns.len() {
+                let c = col as u16;
+                match row.get_ref(col).map_err(|e| e.to_string())? {
+                    ValueRef::Null => continue,
+                    ValueRef::Integer(i) => sheet.write_number(row_idx, c, i as f64),
+                    ValueRef::Real(f) => sheet.write_number(row_idx, c, f),
+                    v => {
+                        let mut text = value_to_string(v);
+                        if let Some((cut, _)) = text.char_indices().nth(XLSX_MAX_CELL_CHARS) {
+                            text.truncate(cut);
+                        }
+                        sheet.write_string(row_idx, c, text)
+                    }
+                }
+                .map_err(|e| e.to_string())?;
+            }
+            row_idx += 1;
+            count += 1;
+        }
+        part += 1;
+    }
+}
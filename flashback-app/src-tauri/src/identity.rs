@@ -0,0 +1,288 @@
+//! 结果条目的稳定标识：重新扫描时同一路径沿用原来的 id 与首次入库时间；
+//! 文件改名或移动后，按文件标识（Unix 下为设备号 + inode）加大小，或按内容哈希找回原条目，
+//! 新路径沿用原 id，人工整理的备注、星标、标签也随之迁移到新路径
+//! 内容哈希只为有人工整理数据的文件计算（整理时与每次扫描时），其余文件只靠 inode 匹配
+
+use rusqlite::{params, Connection};
+use serde::Serialize;
+use std::collections::{HashMap, HashSet};
+use std::fs::Metadata;
+use std::io::Read;
+use std::path::Path;
+#[cfg(feature = "gui")]
+use std::path::PathBuf;
+
+/// 已入库的条目
+#[derive(Debug, Clone)]
+pub(crate) struct KnownFile {
+    pub id: String,
+    pub path: String,
+    pub inserted_at: String,
+    pub modified_at: String,
+    pub size_bytes: i64,
+    pub file_key: Option<String>,
+    pub content_hash: Option<String>,
+    pub archived: bool,
+    /// 有人工整理数据（备注、星标、标签等）
+    pub annotated: bool,
+    pub source: String,
+}
+
+/// 本次新出现的路径
+#[derive(Debug, Clone)]
+struct AddedFile {
+    path: String,
+    size_bytes: i64,
+    file_key: Option<String>,
+}
+
+/// 一次扫描（或一批监听事件）期间的条目标识：先按路径认领已有条目，结束时为消失的条目寻找新路径
+#[derive(Debug, Default)]
+pub(crate) struct Identities {
+    known: HashMap<String, KnownFile>,
+    added: Vec<AddedFile>,
+}
+
+/// 文件改名或移动
+#[derive(Debug, Clone, Serialize)]
+pub(crate) struct FileMove {
+    pub id: String,
+    pub from: String,
+    pub to: String,
+}
+
+const KNOWN_COLUMNS: &str = "id, file_path, inserted_at, modified_at, size_bytes, file_key, content_hash, archived,
+    EXISTS (SELECT 1 FROM result_annotations a WHERE a.project_id = scan_results.project_id AND a.file_path = scan_results.file_path)
+    OR EXISTS (SELECT 1 FROM result_tags t WHERE t.project_id = scan_results.project_id AND t.file_path = scan_results.file_path),
+    source";
+
+fn known_from_row(row: &rusqlite::Row) -> rusqlite::Result<KnownFile> {
+    Ok(KnownFile {
+        id: row.get(0)?,
+        path: row.get(1)?,
+        inserted_at: row.get(2)?,
+        modified_at: row.get(3)?,
+        size_bytes: row.get(4)?,
+        file_key: row.get(5)?,
+        content_hash: row.get(6)?,
+        archived: row.get::<_, i64>(7)? == 1,
+        annotated: row.get::<_, i64>(8)? == 1,
+        source: row.get(9)?,
+    })
+}
+
+impl Identities {
+    /// 读取项目的全部条目（重新扫描前）
+    pub fn load(conn: &Connection, project_id: &str) -> rusqlite::Result<Self> {
+        let mut stmt = conn.prepare(&format!(
+            "SELECT {} FROM scan_results WHERE project_id = ?",
+            KNOWN_COLUMNS
+        ))?;
+        let rows = stmt.query_map([project_id], known_from_row)?;
+        let mut ids = Identities::default();
+        for row in rows {
+            let file = row?;
+            ids.known.insert(file.path.clone(), file);
+        }
+        Ok(ids)
+    }
+
+    /// 读取若干路径本身及其下的条目（监听时只涉及发生变化的路径）
+    #[cfg(feature = "gui")]
+    pub fn load_paths(
+        conn: &Connection,
+        project_id: &str,
+        paths: &[PathBuf],
+    ) -> rusqlite::Result<Self> {
+        let mut stmt = conn.prepare(&format!(
+            "SELECT {} FROM scan_results WHERE project_id = ?1
+             AND (file_path = ?2 OR substr(file_path, 1, length(?3)) = ?3)",
+            KNOWN_COLUMNS
+        ))?;
+        let mut ids = Identities::default();
+        for path in paths {
+            let path_s = path.to_string_lossy().to_string();
+            let prefix = format!("{}{}", path_s, std::path::MAIN_SEPARATOR);
+            let rows = stmt.query_map(params![project_id, path_s, prefix], known_from_row)?;
+            for row in rows {
+                let file = row?;
+                ids.known.insert(file.path.clone(), file);
+            }
+        }
+        Ok(ids)
+    }
+
+    /// 写入路径前调用：返回该路径原有的条目并记为仍然存在；没有则记为新出现的路径
+    pub fn claim(&mut self, path: &str, meta: &Metadata) -> Option<KnownFile> {
+        let known = self.known.remove(path);
+        if known.is_none() {
+            self.added.push(AddedFile {
+                path: path.to_string(),
+                size_bytes: meta.len() as i64,
+                file_key: file_key(meta),
+            });
+        }
+        known
+    }
+
+    /// 未被认领的条目路径（不含归档条目），即本次未再写入的文件；须在 follow_moves 之前取得
+    pub fn unclaimed(&self) -> Vec<String> {
+        self.known
+            .values()
+            .filter(|k| !k.archived)
+            .map(|k| k.path.clone())
+            .collect()
+    }
+
+    /// 结束时调用：为消失的条目在新出现的路径中寻找同一文件，迁移 id 与人工整理数据
+    pub fn follow_moves(
+        self,
+        conn: &Connection,
+        project_id: &str,
+    ) -> rusqlite::Result<Vec<FileMove>> {
+        // retain 模式下保留的归档条目不算消失
+        let gone: Vec<KnownFile> = self.known.into_values().filter(|k| !k.archived).collect();
+        if gone.is_empty() || self.added.is_empty() {
+            return Ok(Vec::new());
+        }
+        let mut by_key: HashMap<(&str, i64), usize> = HashMap::new();
+        for (i, a) in self.added.iter().enumerate() {
+            if let Some(key) = a.file_key.as_deref() {
+                by_key.insert((key, a.size_bytes), i);
+            }
+        }
+        let mut taken: HashSet<usize> = HashSet::new();
+        let mut hashes: HashMap<usize, Option<String>> = HashMap::new();
+        let mut moves = Vec::new();
+        for old in &gone {
+            let by_inode = old
+                .file_key
+                .as_deref()
+                .and_then(|key| by_key.get(&(key, old.size_bytes)))
+                .copied()
+                .filter(|i| !taken.contains(i));
+            let matched = by_inode.or_else(|| {
+                let hash = old.content_hash.as_deref()?;
+                self.added.iter().enumerate().position(|(i, a)| {
+                    !taken.contains(&i)
+                        && a.size_bytes == old.size_bytes
+                        && hashes
+                            .entry(i)
+                            .or_insert_with(|| content_hash(Path::new(&a.path)))
+                            .as_deref()
+                            == Some(hash)
+                })
+            });
+            if let Some(i) = matched {
+                taken.insert(i);
+                let to = &self.added[i].path;
+                move_item(conn, project_id, old, to)?;
+                moves.push(FileMove {
+                    id: old.id.clone(),
+                    from: old.path.clone(),
+                    to: to.clone(),
+                });
+            }
+        }
+        Ok(moves)
+    }
+}
+
+/// 新路径的条目沿用原 id 与首次入库时间，人工整理数据与元数据迁移到新路径（新路径已有的数据优先）
+fn move_item(
+    conn: &Connection,
+    project_id: &str,
+    old: &KnownFile,
+    to: &str,
+) -> rusqlite::Result<()> {
+    conn.execute(
+        "DELETE FROM scan_results WHERE project_id = ? AND file_path = ?",
+        params![project_id, old.path],
+    )?;
+    conn.execute(
+        "UPDATE scan_results SET id = ?, inserted_at = ?, content_hash = COALESCE(content_hash, ?)
+         WHERE project_id = ? AND file_path = ?",
+        params![old.id, old.inserted_at, old.content_hash, project_id, to],
+    )?;
+    for table in ["result_annotations", "result_tags", "result_metadata"] {
+        conn.execute(
+            &format!(
+                "UPDATE OR IGNORE {} SET file_path = ?3 WHERE project_id = ?1 AND file_path = ?2",
+                table
+            ),
+            params![project_id, old.path, to],
+        )?;
+        conn.execute(
+            &format!(
+                "DELETE FROM {} WHERE project_id = ? AND file_path = ?",
+                table
+            ),
+            params![project_id, old.path],
+        )?;
+    }
+    Ok(())
+}
+
+/// 文件标识：Unix 下为设备号与 inode，改名、同盘移动后不变
+#[cfg(unix)]
+pub(crate) fn file_key(meta: &Metadata) -> Option<String> {
+    use std::os::unix::fs::MetadataExt;
+    Some(format!("{}:{}", meta.dev(), meta.ino()))
+}
+
+#[cfg(not(unix))]
+pub(crate) fn file_key(_meta: &Metadata) -> Option<String> {
+    None
+}
+
+/// 64 位 FNV-1a 哈希，结果跨版本稳定，可持久化
+pub(crate) struct Fnv1a(u64);
+
+impl Fnv1a {
+    pub fn new() -> Self {
+        Fnv1a(0xcbf2_9ce4_8422_2325)
+    }
+
+    pub fn write(&mut self, bytes: &[u8]) {
+        for b in bytes {
+            self.0 ^= u64::from(*b);
+            self.0 = self.0.wrapping_mul(0x0100_0000_01b3);
+        }
+    }
+
+    /// 十六进制表示
+    pub fn finish_hex(&self) -> String {
+        format!("{:016x}", self.0)
+    }
+}
+
+/// 文件内容的 FNV-1a 哈希（十六进制）
+pub(crate) fn content_hash(path: &Path) -> Option<String> {
+    let mut file = std::fs::File::open(path).ok()?;
+    let mut hash = Fnv1a::new();
+    let mut buf = [0u8; 64 * 1024];
+    loop {
+        let n = file.read(&mut buf).ok()?;
+        if n == 0 {
+            break;
+        }
+        hash.write(&buf[..n]);
+    }
+    Some(hash.finish_hex())
+}
+
+/// 为加了人工整理数据的文件记下内容哈希，之后跨盘移动（inode 改变）也能找回
+#[cfg(feature = "gui")]
+pub(crate) fn remember_hash(
+    conn: &Connection,
+    project_id: &str,
+    file_path: &str,
+) -> rusqlite::Result<()> {
+    if let Some(hash) = content_hash(Path::new(file_path)) {
+        conn.execute(
+            "UPDATE scan_results SET content_hash = ? WHERE project_id = ? AND file_path = ?",
+            params![hash, project_id, file_path],
+        )?;
+    }
+    Ok(())
+}
@@ -1,14 +1,39 @@
 //! FlashBack Tauri 后端
 //! 功能：项目管理、本地扫描（Git 仓库、文档、聊天数据库路径），事件推送到前端
 
-use rayon::prelude::*;
 use rusqlite::{Connection, Result as SqlResult};
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 use std::path::{Path, PathBuf};
 use std::sync::Mutex;
-use tauri::{Emitter, Manager};
 use uuid::Uuid;
-use walkdir::{DirEntry, WalkDir};
+
+#[cfg(feature = "gui")]
+mod archive;
+pub mod cli;
+mod collection;
+mod collectors;
+#[cfg(feature = "gui")]
+mod commands;
+mod curation;
+mod export;
+mod identity;
+mod metadata;
+mod progress;
+mod report;
+mod results;
+mod scan;
+mod schedule;
+#[cfg(feature = "gui")]
+mod thumbnail;
+mod timeline;
+mod walk;
+#[cfg(feature = "gui")]
+mod watch;
+
+use collectors::CollectorRegistry;
+use scan::WindowMode;
+use walk::WalkOptions;
 
 // ==================== 数据结构定义 ====================
 
@@ -26,6 +51,16 @@ struct Project {
     scan_summary: Option<String>,
     created_at: String,
     updated_at: String,
+    // 启用的采集器 id 列表（JSON 字符串序列化），为空表示使用默认采集器
+    #[serde(default)]
+    collectors: Option<String>,
+    // 时间范围的计算方式（rolling / frozen / retain，为空按 rolling），frozen 时附带固定的起止时间
+    #[serde(default)]
+    window_mode: Option<String>,
+    #[serde(default)]
+    window_start: Option<String>,
+    #[serde(default)]
+    window_end: Option<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -36,8 +71,13 @@ struct ProjectInput {
     scan_scope: Option<String>,
     #[serde(default)]
     scan_folders: Option<Vec<String>>,
+    #[serde(default)]
+    collectors: Option<Vec<String>>,
+    #[serde(default)]
+    window_mode: Option<String>,
 }
 
+#[cfg(feature = "gui")]
 #[derive(Debug, Clone, Serialize, Deserialize)]
 struct ProjectListResponse {
     projects: Vec<Project>,
@@ -93,6 +133,15 @@ struct ScanResultItem {
     is_valid: bool,
     inserted_at: String,
     updated_at: String,
+    /// retain 模式下已移出时间范围的条目
+    archived: bool,
+    /// 以下为人工整理的字段，见 curation 模块
+    note: String,
+    /// 星标等级，0 为未标记
+    star: u8,
+    /// 不写入报告
+    excluded: bool,
+    tags: Vec<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Default)]
@@ -100,6 +149,39 @@ struct ScanSummary {
     git_repos: usize,
     documents: usize,
     chat_locations: Vec<ChatLocation>,
+    // 各采集器本次采集到的条目数（key 为采集器 id）
+    collected: BTreeMap<String, usize>,
+    // frozen 模式下采集后因晚于窗口终点而移除的条目数（key 为采集器 id，按表的行计）
+    trimmed: BTreeMap<String, usize>,
+    // 扫描起止时间与耗时
+    started_at: String,
+    finished_at: String,
+    duration_ms: u64,
+    // 文档结果按来源（如 DOWNLOADS）与文件类型统计
+    by_source: BTreeMap<String, usize>,
+    by_type: BTreeMap<String, usize>,
+    // 跳过与处理失败的条目
+    issues: ScanIssues,
+}
+
+#[derive(Debug, Clone, Serialize, Default)]
+struct ScanIssues {
+    // 无法访问而跳过的条目数（权限不足、链接失效等）
+    skipped: usize,
+    // 已读取但处理失败的条目数（解析失败、写库失败）
+    failed: usize,
+    // 按原因统计（key 如 permission_denied、broken_symlink、db_error）
+    by_reason: BTreeMap<String, usize>,
+    // 每种原因保留的前几条样例
+    samples: Vec<IssueSample>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+struct IssueSample {
+    stage: String,
+    reason: String,
+    path: Option<String>,
+    message: String,
 }
 
 #[derive(Debug, Clone, Serialize)]
@@ -108,11 +190,16 @@ struct ChatLocation {
     path: String,
 }
 
+#[cfg(feature = "gui")]
 #[derive(Debug, Clone, Serialize)]
 struct ProgressPayload {
     progress: u8,
+    // 每秒处理的文件数与预计剩余秒数
+    per_second: f64,
+    eta_secs: Option<u64>,
 }
 
+#[cfg(feature = "gui")]
 #[derive(Debug, Clone, Serialize)]
 struct LogPayload {
     icon: &'static str,
@@ -123,24 +210,31 @@ struct LogPayload {
 
 struct DatabaseManager {
     conn: Mutex<Option<Connection>>,
+    path: PathBuf,
 }
 
 impl DatabaseManager {
     fn new() -> Self {
+        Self::at(Self::get_db_path())
+    }
+
+    /// 使用指定位置的数据库（如测试时的临时目录），项目文件夹建在数据库所在目录下
+    fn at(path: PathBuf) -> Self {
         DatabaseManager {
             conn: Mutex::new(None),
+            path,
         }
     }
 
     fn initialize(&self) -> SqlResult<()> {
-        let db_path = Self::get_db_path();
+        let db_path = &self.path;
 
         // 确保 FlashBack 目录存在
         if let Some(parent) = db_path.parent() {
             std::fs::create_dir_all(parent).ok();
         }
 
-        let conn = Connection::open(&db_path)?;
+        let conn = Connection::open(db_path)?;
 
         // 检查表是否存在
         let table_exists: bool = conn
@@ -163,7 +257,12 @@ impl DatabaseManager {
                     scan_folders TEXT,
                     scan_summary TEXT,
                     created_at TEXT NOT NULL,
-                    updated_at TEXT NOT NULL
+                    updated_at TEXT NOT NULL,
+                    collectors TEXT,
+                    walk_options TEXT,
+                    window_mode TEXT,
+                    window_start TEXT,
+                    window_end TEXT
                 )",
                 [],
             )?;
@@ -182,6 +281,9 @@ impl DatabaseManager {
             let has_scan_summary = columns.iter().any(|c| c == "scan_summary");
             let has_scan_scope = columns.iter().any(|c| c == "scan_scope");
             let has_scan_folders = columns.iter().any(|c| c == "scan_folders");
+            let has_collectors = columns.iter().any(|c| c == "collectors");
+            let has_walk_options = columns.iter().any(|c| c == "walk_options");
+            let has_window = columns.iter().any(|c| c == "window_mode");
 
             // 渐进式迁移：缺哪个列就补哪个列，避免 DROP/重建
             if !has_time_range {
@@ -196,6 +298,20 @@ impl DatabaseManager {
             if !has_scan_folders {
                 let _ = conn.execute("ALTER TABLE projects ADD COLUMN scan_folders TEXT", []);
             }
+            if !has_collectors {
+                let _ = conn.execute("ALTER TABLE projects ADD COLUMN collectors TEXT", []);
+            }
+            if !has_walk_options {
+                let _ = conn.execute("ALTER TABLE projects ADD COLUMN walk_options TEXT", []);
+            }
+            if !has_window {
+                for column in ["window_mode", "window_start", "window_end"] {
+                    let _ = conn.execute(
+                        &format!("ALTER TABLE projects ADD COLUMN {} TEXT", column),
+                        [],
+                    );
+                }
+            }
         }
 
         // 创建索引
@@ -222,10 +338,73 @@ impl DatabaseManager {
             )",
             [],
         )?;
+        // 条目标识列：file_key 为设备号与 inode，content_hash 只为有人工整理数据的文件计算
+        let scan_result_columns: Vec<String> = conn
+            .prepare("PRAGMA table_info(scan_results)")?
+            .query_map([], |row| row.get::<_, String>(1))?
+            .filter_map(|r| r.ok())
+            .collect();
+        for column in ["file_key", "content_hash"] {
+            if !scan_result_columns.iter().any(|c| c == column) {
+                let _ = conn.execute(
+                    &format!("ALTER TABLE scan_results ADD COLUMN {} TEXT", column),
+                    [],
+                );
+            }
+        }
         conn.execute(
             "CREATE INDEX IF NOT EXISTS idx_scan_results_project ON scan_results(project_id)",
             [],
         )?;
+        // 结果页按任意列排序、游标分页时走 (project_id, 排序列, id) 索引
+        for column in results::SORTABLE_COLUMNS {
+            conn.execute(
+                &format!(
+                    "CREATE INDEX IF NOT EXISTS idx_scan_results_{0} ON scan_results(project_id, {0}, id)",
+                    column
+                ),
+                [],
+            )?;
+        }
+
+        // 扫描运行记录：每次扫描一条，summary 为当次的完整摘要（JSON）
+        conn.execute(
+            "CREATE TABLE IF NOT EXISTS scan_runs (
+                id TEXT PRIMARY KEY,
+                project_id TEXT NOT NULL,
+                started_at TEXT NOT NULL,
+                finished_at TEXT NOT NULL,
+                duration_ms INTEGER NOT NULL,
+                skipped INTEGER NOT NULL,
+                failed INTEGER NOT NULL,
+                summary TEXT NOT NULL
+            )",
+            [],
+        )?;
+        conn.execute(
+            "CREATE INDEX IF NOT EXISTS idx_scan_runs_project ON scan_runs(project_id, started_at)",
+            [],
+        )?;
+
+        // 采集器的项目级配置（JSON），如域名过滤、脱敏规则
+        conn.execute(
+            "CREATE TABLE IF NOT EXISTS collector_settings (
+                project_id TEXT NOT NULL,
+                collector_id TEXT NOT NULL,
+                settings TEXT NOT NULL,
+                updated_at TEXT NOT NULL,
+                PRIMARY KEY(project_id, collector_id)
+            )",
+            [],
+        )?;
+
+        // 各采集器自有的表
+        CollectorRegistry::builtin().init_schema(&conn)?;
+
+        // 定时重扫计划及其执行记录
+        schedule::init_schema(&conn)?;
+        collection::init_schema(&conn)?;
+        curation::init_schema(&conn)?;
 
         // 应用配置表（k-v）
         conn.execute(
@@ -247,34 +426,54 @@ impl DatabaseManager {
         home.join("FlashBack").join("flashback.db")
     }
 
+    /// 连接所打开的数据库文件；内存数据库取默认位置
+    fn path_of(conn: &Connection) -> PathBuf {
+        conn.path()
+            .filter(|p| !p.is_empty())
+            .map(PathBuf::from)
+            .unwrap_or_else(Self::get_db_path)
+    }
+
     fn get_connection(&self) -> SqlResult<Connection> {
         let _guard = self.conn.lock().unwrap();
         // 直接打开新连接，避免借用问题
-        Connection::open(Self::get_db_path())
+        Connection::open(&self.path)
     }
 }
 
 // ==================== 数据库操作 ====================
 
-#[tauri::command]
-fn init_database(db: tauri::State<DatabaseManager>) -> Result<String, String> {
-    db.initialize().map_err(|e| e.to_string())?;
-    Ok("Database initialized successfully".to_string())
-}
+const PROJECT_COLUMNS: &str = "id, name, folder_path, time_range, scan_scope, scan_folders, scan_summary, created_at, updated_at, collectors, window_mode, window_start, window_end";
 
-#[tauri::command]
-fn create_project(
-    db: tauri::State<DatabaseManager>,
-    project_input: ProjectInput,
-) -> Result<Project, String> {
-    let conn = db.get_connection().map_err(|e| e.to_string())?;
+fn project_from_row(row: &rusqlite::Row) -> SqlResult<Project> {
+    Ok(Project {
+        id: row.get(0)?,
+        name: row.get(1)?,
+        folder_path: row.get(2)?,
+        time_range: row.get(3)?,
+        scan_scope: row.get(4).ok(),
+        scan_folders: row.get(5).ok(),
+        scan_summary: row.get(6).ok(),
+        created_at: row.get(7)?,
+        updated_at: row.get(8)?,
+        collectors: row.get(9).ok().flatten(),
+        window_mode: row.get(10).ok().flatten(),
+        window_start: row.get(11).ok().flatten(),
+        window_end: row.get(12).ok().flatten(),
+    })
+}
 
+/// 创建项目文件夹并插入项目记录（create_project 与 start_scan 共用）
+fn insert_project(conn: &Connection, project_input: ProjectInput) -> Result<Project, String> {
     // 生成 UUID
     let id = Uuid::new_v4().to_string();
 
-    // 创建项目文件夹
-    let home = dirs::home_dir().unwrap_or_else(|| PathBuf::from("."));
-    let project_folder = home.join("FlashBack").join(&project_input.name);
+    // 创建项目文件夹（与数据库同在 FlashBack 目录下）
+    let root = DatabaseManager::path_of(conn)
+        .parent()
+        .map(Path::to_path_buf)
+        .unwrap_or_else(|| PathBuf::from("."));
+    let project_folder = root.join(&project_input.name);
 
     std::fs::create_dir_all(&project_folder).map_err(|e| format!("创建项目文件夹失败: {}", e))?;
 
@@ -287,9 +486,27 @@ fn create_project(
         .unwrap_or_else(|| "ALL".into());
     let scan_folders_json = serde_json::to_string(&project_input.scan_folders.unwrap_or_default())
         .unwrap_or_else(|_| "[]".into());
+    let collectors_json = match project_input.collectors {
+        Some(ids) => Some(validate_collectors(ids)?),
+        None => None,
+    };
+    let window_mode = project_input
+        .window_mode
+        .as_deref()
+        .map(validate_window_mode)
+        .transpose()?;
+    // frozen：以创建时间为终点固定范围
+    let (window_start, window_end) = match window_mode {
+        Some(WindowMode::Frozen) => {
+            let (start, end) = scan::frozen_bounds(&project_input.time_range, chrono::Local::now());
+            (start, Some(end))
+        }
+        _ => (None, None),
+    };
+    let window_mode = window_mode.map(|m| m.as_str().to_string());
     conn.execute(
-        "INSERT INTO projects (id, name, folder_path, time_range, scan_scope, scan_folders, scan_summary, created_at, updated_at) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?)",
-        &[&id, &project_input.name, &project_folder.to_string_lossy().to_string(), &project_input.time_range, &scan_scope, &scan_folders_json, &empty_string, &now, &now],
+        "INSERT INTO projects (id, name, folder_path, time_range, scan_scope, scan_folders, scan_summary, created_at, updated_at, collectors, window_mode, window_start, window_end) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)",
+        rusqlite::params![id, project_input.name, project_folder.to_string_lossy().to_string(), project_input.time_range, scan_scope, scan_folders_json, empty_string, now, now, collectors_json, window_mode, window_start, window_end],
     ).map_err(|e| e.to_string())?;
 
     Ok(Project {
@@ -302,243 +519,89 @@ fn create_project(
         scan_folders: Some(scan_folders_json),
         created_at: now.clone(),
         updated_at: now,
+        collectors: collectors_json,
+        window_mode,
+        window_start,
+        window_end,
     })
 }
 
-#[tauri::command]
-fn get_projects(db: tauri::State<DatabaseManager>) -> Result<Vec<Project>, String> {
-    let conn = db.get_connection().map_err(|e| e.to_string())?;
-
-    let mut stmt = conn.prepare("SELECT id, name, folder_path, time_range, scan_scope, scan_folders, scan_summary, created_at, updated_at FROM projects ORDER BY updated_at DESC")
-        .map_err(|e| e.to_string())?;
-
-    let projects = stmt
-        .query_map([], |row| {
-            Ok(Project {
-                id: row.get(0)?,
-                name: row.get(1)?,
-                folder_path: row.get(2)?,
-                time_range: row.get(3)?,
-                scan_scope: row.get(4).ok(),
-                scan_folders: row.get(5).ok(),
-                scan_summary: row.get(6).ok(),
-                created_at: row.get(7)?,
-                updated_at: row.get(8)?,
-            })
-        })
-        .map_err(|e| e.to_string())?;
-
-    let result: Vec<Project> = projects
-        .map(|p| p.map_err(|e| e.to_string()))
-        .collect::<Result<_, _>>()?;
-
-    Ok(result)
+fn validate_window_mode(mode: &str) -> Result<WindowMode, String> {
+    let parsed = WindowMode::parse(Some(mode));
+    if parsed.as_str() != mode {
+        return Err(format!("未知的时间窗口模式: {}", mode));
+    }
+    Ok(parsed)
 }
 
-#[tauri::command]
-fn get_projects_paginated(
-    db: tauri::State<DatabaseManager>,
-    page: usize,
-    page_size: usize,
-) -> Result<ProjectListResponse, String> {
-    println!(
-        "[DEBUG] get_projects_paginated called: page={}, page_size={}",
-        page, page_size
-    );
-
-    let conn = db.get_connection().map_err(|e| {
-        println!("[DEBUG] get_connection error: {}", e);
-        e.to_string()
-    })?;
-
-    // 确保 page 至少为 1
-    let page = if page == 0 { 1 } else { page };
-
-    // 计算偏移量
-    let offset = (page - 1) * page_size;
-    println!("[DEBUG] offset={}", offset);
-
-    // 获取总数
-    let total: usize = conn
-        .query_row("SELECT COUNT(*) FROM projects", [], |row| {
-            let count: usize = row.get(0)?;
-            println!("[DEBUG] total projects count: {}", count);
-            Ok(count)
-        })
-        .map_err(|e| {
-            println!("[DEBUG] COUNT query error: {}", e);
-            e.to_string()
-        })?;
-
-    // 计算总页数
-    let total_pages = if total == 0 {
-        1
-    } else {
-        (total + page_size - 1) / page_size
+/// 设置项目的时间窗口模式；frozen 以项目创建时间为终点按当前的 time_range 固定起止时间
+#[cfg(feature = "gui")]
+fn update_window_mode(conn: &Connection, project_id: &str, mode: &str) -> Result<(), String> {
+    let mode = validate_window_mode(mode)?;
+    let (time_range, created_at): (String, String) = conn
+        .query_row(
+            "SELECT time_range, created_at FROM projects WHERE id = ?",
+            [project_id],
+            |row| Ok((row.get(0)?, row.get(1)?)),
+        )
+        .map_err(|_| "项目不存在".to_string())?;
+    let (start, end) = match mode {
+        WindowMode::Frozen => {
+            let anchor = scan::parse_local_time(&created_at).unwrap_or_else(chrono::Local::now);
+            let (start, end) = scan::frozen_bounds(&time_range, anchor);
+            (start, Some(end))
+        }
+        _ => (None, None),
     };
+    conn.execute(
+        "UPDATE projects SET window_mode = ?, window_start = ?, window_end = ? WHERE id = ?",
+        rusqlite::params![mode.as_str(), start, end, project_id],
+    )
+    .map_err(|e| e.to_string())?;
+    Ok(())
+}
 
-    // 获取分页数据
-    let mut stmt = conn.prepare("SELECT id, name, folder_path, time_range, scan_scope, scan_folders, scan_summary, created_at, updated_at FROM projects ORDER BY updated_at DESC LIMIT ? OFFSET ?")
-        .map_err(|e| {
-            println!("[DEBUG] prepare error: {}", e);
-            e.to_string()
-        })?;
+/// 校验采集器 id 均已注册，返回 JSON 字符串
+fn validate_collectors(ids: Vec<String>) -> Result<String, String> {
+    let registry = CollectorRegistry::builtin();
+    if let Some(unknown) = ids.iter().find(|id| !registry.contains(id)) {
+        return Err(format!("未知的采集器: {}", unknown));
+    }
+    serde_json::to_string(&ids).map_err(|e| e.to_string())
+}
 
-    let params: &[&dyn rusqlite::ToSql] = &[&(page_size as i64), &(offset as i64)];
-    println!(
-        "[DEBUG] query params: page_size={}, offset={}",
-        page_size as i64, offset as i64
-    );
+/// 按最近更新时间列出全部项目（get_projects 与命令行共用）
+fn list_projects(conn: &Connection) -> Result<Vec<Project>, String> {
+    let mut stmt = conn
+        .prepare(&format!(
+            "SELECT {} FROM projects ORDER BY updated_at DESC",
+            PROJECT_COLUMNS
+        ))
+        .map_err(|e| e.to_string())?;
 
     let projects = stmt
-        .query_map(params, |row| {
-            Ok(Project {
-                id: row.get(0)?,
-                name: row.get(1)?,
-                folder_path: row.get(2)?,
-                time_range: row.get(3)?,
-                scan_scope: row.get(4).ok(),
-                scan_folders: row.get(5).ok(),
-                scan_summary: row.get(6).ok(),
-                created_at: row.get(7)?,
-                updated_at: row.get(8)?,
-            })
-        })
-        .map_err(|e| {
-            println!("[DEBUG] query_map error: {}", e);
-            e.to_string()
-        })?;
+        .query_map([], project_from_row)
+        .map_err(|e| e.to_string())?;
 
     let result: Vec<Project> = projects
         .map(|p| p.map_err(|e| e.to_string()))
         .collect::<Result<_, _>>()?;
 
-    println!("[DEBUG] returning {} projects", result.len());
-
-    Ok(ProjectListResponse {
-        projects: result,
-        total,
-        page,
-        total_pages,
-    })
-}
-
-#[tauri::command]
-fn get_project_by_name(
-    db: tauri::State<DatabaseManager>,
-    name: String,
-) -> Result<Option<Project>, String> {
-    let conn = db.get_connection().map_err(|e| e.to_string())?;
-
-    let mut stmt = conn.prepare("SELECT id, name, folder_path, time_range, scan_scope, scan_folders, scan_summary, created_at, updated_at FROM projects WHERE name = ?")
-        .map_err(|e| e.to_string())?;
-
-    let mut rows = stmt.query(&[&name]).map_err(|e| e.to_string())?;
-
-    if let Some(row) = rows.next().map_err(|e| e.to_string())? {
-        Ok(Some(Project {
-            id: row.get(0).map_err(|e| e.to_string())?,
-            name: row.get(1).map_err(|e| e.to_string())?,
-            folder_path: row.get(2).map_err(|e| e.to_string())?,
-            time_range: row.get(3).map_err(|e| e.to_string())?,
-            scan_scope: row.get(4).ok(),
-            scan_folders: row.get(5).ok(),
-            scan_summary: row.get(6).ok(),
-            created_at: row.get(7).map_err(|e| e.to_string())?,
-            updated_at: row.get(8).map_err(|e| e.to_string())?,
-        }))
-    } else {
-        Ok(None)
-    }
-}
-
-#[tauri::command]
-fn get_project_by_id(
-    db: tauri::State<DatabaseManager>,
-    id: String,
-) -> Result<Option<Project>, String> {
-    let conn = db.get_connection().map_err(|e| e.to_string())?;
-    let mut stmt = conn.prepare("SELECT id, name, folder_path, time_range, scan_scope, scan_folders, scan_summary, created_at, updated_at FROM projects WHERE id = ?")
-        .map_err(|e| e.to_string())?;
-    let mut rows = stmt.query(&[&id]).map_err(|e| e.to_string())?;
-    if let Some(row) = rows.next().map_err(|e| e.to_string())? {
-        Ok(Some(Project {
-            id: row.get(0).map_err(|e| e.to_string())?,
-            name: row.get(1).map_err(|e| e.to_string())?,
-            folder_path: row.get(2).map_err(|e| e.to_string())?,
-            time_range: row.get(3).map_err(|e| e.to_string())?,
-            scan_scope: row.get(4).ok(),
-            scan_folders: row.get(5).ok(),
-            scan_summary: row.get(6).ok(),
-            created_at: row.get(7).map_err(|e| e.to_string())?,
-            updated_at: row.get(8).map_err(|e| e.to_string())?,
-        }))
-    } else {
-        Ok(None)
-    }
+    Ok(result)
 }
 
 // ====== KV 配置：app_config ======
 
-#[tauri::command]
-fn set_config(db: tauri::State<DatabaseManager>, key: String, value: String) -> Result<(), String> {
-    let conn = db.get_connection().map_err(|e| e.to_string())?;
-    conn.execute("INSERT INTO app_config(key,value) VALUES(?,?) ON CONFLICT(key) DO UPDATE SET value=excluded.value", &[&key, &value])
-        .map_err(|e| e.to_string())?;
-    Ok(())
-}
-
-#[tauri::command]
-fn get_config(db: tauri::State<DatabaseManager>, key: String) -> Result<Option<String>, String> {
-    let conn = db.get_connection().map_err(|e| e.to_string())?;
-    let mut stmt = conn
-        .prepare("SELECT value FROM app_config WHERE key = ?")
-        .map_err(|e| e.to_string())?;
-    let mut rows = stmt.query(&[&key]).map_err(|e| e.to_string())?;
-    if let Some(row) = rows.next().map_err(|e| e.to_string())? {
-        let v: String = row.get(0).map_err(|e| e.to_string())?;
-        Ok(Some(v))
-    } else {
-        Ok(None)
-    }
-}
-
+#[cfg(feature = "gui")]
 const CFG_CURRENT_PROJECT: &str = "current_project_id";
 
-#[tauri::command]
-fn set_current_project(
-    db: tauri::State<DatabaseManager>,
-    project_id: String,
-) -> Result<(), String> {
-    set_config(db, CFG_CURRENT_PROJECT.into(), project_id)
-}
-
-#[tauri::command]
-fn get_current_project(db: tauri::State<DatabaseManager>) -> Result<Option<Project>, String> {
-    if let Some(pid) = get_config(db.clone(), CFG_CURRENT_PROJECT.into())? {
-        if let Some(p) = get_project_by_id(db, pid)? {
-            return Ok(Some(p));
-        }
-    }
-    Ok(None)
-}
-
-/**
- * 删除项目及其所有关联数据
- *
- * @param db 数据库管理器
- * @param name 项目名称
- * @return Result<(), String>
- */
-#[tauri::command]
-fn delete_project(db: tauri::State<DatabaseManager>, name: String) -> Result<(), String> {
-    let conn = db.get_connection().map_err(|e| e.to_string())?;
-
+/// 删除项目记录、各采集器数据与项目文件夹（delete_project 与命令行共用）
+fn remove_project(conn: &Connection, name: &str) -> Result<(), String> {
     // 先查询项目ID和路径
     let mut stmt = conn
         .prepare("SELECT id, folder_path FROM projects WHERE name = ?")
         .map_err(|e| e.to_string())?;
-    let mut rows = stmt.query(&[&name]).map_err(|e| e.to_string())?;
+    let mut rows = stmt.query([name]).map_err(|e| e.to_string())?;
 
     if let Some(row) = rows.next().map_err(|e| e.to_string())? {
         let project_id: String = row.get(0).map_err(|e| e.to_string())?;
@@ -548,22 +611,45 @@ fn delete_project(db: tauri::State<DatabaseManager>, name: String) -> Result<(),
         conn.execute("BEGIN TRANSACTION", [])
             .map_err(|e| format!("开始事务失败: {}", e))?;
 
-        // 1. 删除 scan_results 表中所有关联数据
-        if let Err(e) = conn.execute("DELETE FROM scan_results WHERE project_id = ?", &[&project_id]) {
-            let _ = conn.execute("ROLLBACK", []);
-            return Err(format!("删除 scan_results 失败: {}", e));
+        // 1. 删除各采集器表中所有关联数据
+        for table in CollectorRegistry::builtin().project_tables() {
+            let sql = format!("DELETE FROM {} WHERE project_id = ?", table);
+            if let Err(e) = conn.execute(&sql, [&project_id]) {
+                let _ = conn.execute("ROLLBACK", []);
+                return Err(format!("删除 {} 失败: {}", table, e));
+            }
+        }
+        for table in [
+            "collector_settings",
+            "scan_runs",
+            "schedules",
+            "schedule_runs",
+            "saved_searches",
+            "result_annotations",
+            "result_tags",
+        ] {
+            let sql = format!("DELETE FROM {} WHERE project_id = ?", table);
+            if let Err(e) = conn.execute(&sql, [&project_id]) {
+                let _ = conn.execute("ROLLBACK", []);
+                return Err(format!("删除 {} 失败: {}", table, e));
+            }
         }
 
         // 2. 如果当前项目是正在使用的项目，清理 app_config
-        let current_project_id = conn.query_row(
-            "SELECT value FROM app_config WHERE key = 'current_project_id'",
-            [],
-            |row| row.get::<_, Option<String>>(0)
-        ).unwrap_or(None);
+        let current_project_id = conn
+            .query_row(
+                "SELECT value FROM app_config WHERE key = 'current_project_id'",
+                [],
+                |row| row.get::<_, Option<String>>(0),
+            )
+            .unwrap_or(None);
 
         if let Some(current_id) = current_project_id {
             if current_id == project_id {
-                if let Err(e) = conn.execute("DELETE FROM app_config WHERE key = 'current_project_id'", []) {
+                if let Err(e) = conn.execute(
+                    "DELETE FROM app_config WHERE key = 'current_project_id'",
+                    [],
+                ) {
                     let _ = conn.execute("ROLLBACK", []);
                     return Err(format!("清理当前项目配置失败: {}", e));
                 }
@@ -581,844 +667,120 @@ fn delete_project(db: tauri::State<DatabaseManager>, name: String) -> Result<(),
             .map_err(|e| format!("提交事务失败: {}", e))?;
 
         // 4. 删除文件夹及其所有内容（在事务外，因为文件操作可能失败但不影响数据库一致性）
+        let _ = std::fs::remove_file(scan::ScanLock::path(&project_id));
         std::fs::remove_dir_all(&folder_path).map_err(|e| format!("删除项目文件夹失败: {}", e))?;
     }
 
     Ok(())
 }
 
-// ==================== 扫描逻辑 ====================
-
-fn is_ignored(entry: &DirEntry) -> bool {
-    let name = entry.file_name().to_string_lossy();
-    let ignored = [
-        "node_modules",
-        "target",
-        ".git",
-        "Library/Caches",
-        "AppData/Local",
-        "AppData/LocalLow",
-        "AppData/Temp",
-        ".DS_Store",
-    ];
-    ignored.iter().any(|p| name.contains(p))
-}
-
-fn home() -> PathBuf {
-    dirs::home_dir().unwrap_or_else(|| PathBuf::from("."))
-}
-
-fn candidate_roots() -> Vec<PathBuf> {
-    let mut roots = vec![
-        home().join("Documents"),
-        home().join("Desktop"),
-        home().join("Downloads"),
-        home().join("Projects"),
-        home().join("Work"),
-    ];
-
-    if cfg!(target_os = "macos") {
-        roots.push(home().join("Library/Containers/com.tencent.xinWeChat/Data/Library/Application Support/com.tencent.xinWeChat"));
-        roots.push(home().join(
-            "Library/Containers/com.tencent.WeWorkMac/Data/Library/Application Support/WXWork",
-        ));
-        roots.push(
-            home().join(
-                "Library/Containers/com.alibaba.DingTalkMac/Data/Library/Application Support",
-            ),
-        );
-    }
-    if cfg!(target_os = "windows") {
-        if let Ok(userprofile) = std::env::var("USERPROFILE") {
-            roots.push(PathBuf::from(&userprofile).join("Documents/WeChat Files"));
-            roots.push(PathBuf::from(&userprofile).join("AppData/Roaming/Tencent/WeChat"));
-            roots.push(PathBuf::from(&userprofile).join("AppData/Roaming/WXWork"));
-            roots.push(PathBuf::from(&userprofile).join("AppData/Roaming/DingTalk"));
-        }
-    }
-    roots
-}
+// ==================== 扫描命令 ====================
 
-fn detect_chat_locations() -> Vec<ChatLocation> {
-    let mut found = Vec::new();
-    let push_if_exists = |v: &mut Vec<ChatLocation>, app: &str, p: PathBuf| {
-        if p.exists() {
-            v.push(ChatLocation {
-                app: app.to_string(),
-                path: p.to_string_lossy().to_string(),
-            });
-        }
-    };
-    if cfg!(target_os = "macos") {
-        push_if_exists(
-            &mut found,
-            "WeChat",
-            home().join("Library/Containers/com.tencent.xinWeChat/Data/Library/Application Support/com.tencent.xinWeChat"),
-        );
-        push_if_exists(
-            &mut found,
-            "WeCom",
-            home().join(
-                "Library/Containers/com.tencent.WeWorkMac/Data/Library/Application Support/WXWork",
-            ),
-        );
-        push_if_exists(
-            &mut found,
-            "DingTalk",
-            home().join(
-                "Library/Containers/com.alibaba.DingTalkMac/Data/Library/Application Support",
-            ),
-        );
-    }
-    if cfg!(target_os = "windows") {
-        if let Ok(userprofile) = std::env::var("USERPROFILE") {
-            push_if_exists(
-                &mut found,
-                "WeChat",
-                PathBuf::from(&userprofile).join("Documents/WeChat Files"),
-            );
-            push_if_exists(
-                &mut found,
-                "WeChat",
-                PathBuf::from(&userprofile).join("AppData/Roaming/Tencent/WeChat"),
-            );
-            push_if_exists(
-                &mut found,
-                "WeCom",
-                PathBuf::from(&userprofile).join("AppData/Roaming/WXWork"),
-            );
-            push_if_exists(
-                &mut found,
-                "DingTalk",
-                PathBuf::from(&userprofile).join("AppData/Roaming/DingTalk"),
-            );
-        }
-    }
-    found
-}
-
-// 仅扫描常见可用于分析的办公/文本/图片等文件
-fn is_doc(path: &Path) -> bool {
-    let ext = path
-        .extension()
-        .and_then(|s| s.to_str())
-        .unwrap_or("")
-        .to_ascii_lowercase();
-    // 常见文本/办公/数据/图片/矢量/设计等（可按需扩展）
-    const ALLOWED: &[&str] = &[
-        // 文本/标记/代码片段
-        "txt", "md", "markdown", "log", "rst", // Office / OpenOffice / iWork
-        "doc", "docx", "dotx", "rtf", "xls", "xlsx", "xlsm", "csv", "tsv", "ods", "numbers", "ppt",
-        "pptx", "odp", "key", // 文档与数据
-        "pdf", "json", "yaml", "yml", "xml", "sql", // 图片（便于做视觉回溯）
-        "png", "jpg", "jpeg", "gif", "webp", "bmp", "tif", "tiff", "heic",
-        // 矢量/设计
-        "svg", "ai", "psd",
-    ];
-    ALLOWED.contains(&ext.as_str())
-}
-
-fn classify_source_from_path(p: &str) -> &'static str {
-    // macOS
-    if p.contains("com.tencent.xinWeChat") {
-        return SourceKind::WeChatFile.as_str();
-    }
-    if p.contains("WXWork") {
-        return SourceKind::WeComFile.as_str();
-    }
-    if p.contains("com.alibaba.DingTalkMac") {
-        return SourceKind::DingTalkFile.as_str();
-    }
-    // Windows 典型路径
-    if p.contains("WeChat Files") || p.contains("Tencent/WeChat") {
-        return SourceKind::WeChatFile.as_str();
-    }
-    if p.contains("WXWork") {
-        return SourceKind::WeComFile.as_str();
-    }
-    if p.contains("DingTalk") {
-        return SourceKind::DingTalkFile.as_str();
-    }
-    // 常见用户目录
-    if p.contains("/Downloads/") || p.ends_with("/Downloads") {
-        return SourceKind::Downloads.as_str();
-    }
-    if p.contains("/Desktop/") || p.ends_with("/Desktop") {
-        return SourceKind::Desktop.as_str();
-    }
-    if p.contains("/Documents/") || p.ends_with("/Documents") {
-        return SourceKind::Documents.as_str();
-    }
-    SourceKind::Other.as_str()
-}
-
-fn is_within_time_range(path: &Path, time_range: &str) -> bool {
-    // 获取文件修改时间
-    match std::fs::metadata(path).and_then(|m| m.modified()) {
-        Ok(modified) => {
-            let now = std::time::SystemTime::now();
-            let duration = now.duration_since(modified).unwrap_or_default();
-            let days = duration.as_secs() / (24 * 3600);
-
-            match time_range {
-                "past_year" => days <= 365,
-                "past_month" => days <= 30,
-                "past_week" => days <= 7,
-                _ => true, // 默认不过滤
-            }
-        }
-        Err(_) => true, // 无法获取时间，不过滤
-    }
-}
-
-#[tauri::command]
-fn start_scan(
-    window: tauri::Window,
+/// 返回指定名称项目的 id（start_scan 与命令行共用）
+#[cfg(feature = "gui")]
+fn ensure_project(
+    conn: &Connection,
     project_name: String,
     time_range: String,
-) -> Result<(), String> {
-    // 获取项目信息
-    let db = window.state::<DatabaseManager>();
-
-    // 先尝试获取项目
-    let conn = db.get_connection().map_err(|e| e.to_string())?;
-    let mut stmt = conn.prepare("SELECT id, name, folder_path, time_range, scan_summary, created_at, updated_at FROM projects WHERE name = ?")
-        .map_err(|e| e.to_string())?;
-    let mut rows = stmt.query(&[&project_name]).map_err(|e| e.to_string())?;
-
-    let project_folder = if let Some(row) = rows.next().map_err(|e| e.to_string())? {
-        // 项目已存在
-        row.get(2).map_err(|e| e.to_string())?
-    } else {
-        // 项目不存在，创建它
-        let home = dirs::home_dir().unwrap_or_else(|| PathBuf::from("."));
-        let project_folder = home.join("FlashBack").join(&project_name);
-
-        std::fs::create_dir_all(&project_folder)
-            .map_err(|e| format!("创建项目文件夹失败: {}", e))?;
-
-        let id = Uuid::new_v4().to_string();
-        let now = chrono::Local::now().format("%Y-%m-%d %H:%M:%S").to_string();
-        let empty_string = String::new();
-
-        conn.execute(
-            "INSERT INTO projects (id, name, folder_path, time_range, scan_summary, created_at, updated_at) VALUES (?, ?, ?, ?, ?, ?, ?)",
-            &[&id, &project_name, &project_folder.to_string_lossy().to_string(), &time_range, &empty_string, &now, &now],
-        ).map_err(|e| e.to_string())?;
-
-        project_folder.to_string_lossy().to_string()
-    };
-
-    // 开新线程避免阻塞 UI
-    std::thread::spawn(move || {
-        let dev = std::env::var("DEV").unwrap_or_default() == "true";
-        let _ = window.emit(
-            "scan-log",
-            LogPayload {
-                icon: "check_circle",
-                text: "初始化扫描序列 v2.4.1 [OK]".into(),
-            },
-        );
-        if dev {
-            let _ = window.emit(
-                "scan-log",
-                LogPayload {
-                    icon: "shield",
-                    text: "DEV=true 检测到，跳过云端验证".into(),
-                },
-            );
-        }
-
-        // 显示配置信息
-        let _ = window.emit(
-            "scan-log",
-            LogPayload {
-                icon: "folder",
-                text: format!("项目名称: {}", project_name),
-            },
-        );
-        let range_label = match time_range.as_str() {
-            "past_year" => "过去一年",
-            "past_month" => "过去一个月",
-            "past_week" => "过去一周",
-            _ => "未知范围",
-        };
-        let _ = window.emit(
-            "scan-log",
-            LogPayload {
-                icon: "schedule",
-                text: format!("时间范围: {}", range_label),
-            },
-        );
-        let _ = window.emit(
-            "scan-log",
-            LogPayload {
-                icon: "folder_open",
-                text: format!("项目目录: {}", project_folder),
-            },
-        );
-
-        let mut summary = ScanSummary::default();
-
-        // 步骤 1：聊天数据库位置
-        let chats = detect_chat_locations();
-        for c in &chats {
-            let _ = window.emit(
-                "scan-log",
-                LogPayload {
-                    icon: "chat",
-                    text: format!("发现聊天数据路径: {} ({})", c.path, c.app),
-                },
-            );
-        }
-        summary.chat_locations = chats;
-        let _ = window.emit("scan-progress", ProgressPayload { progress: 18 });
-
-        // 步骤 2：Git 仓库识别
-        let mut git_count = 0usize;
-        let mut roots = candidate_roots();
-        // 添加项目的目录
-        roots.push(PathBuf::from(&project_folder));
-
-        for (idx, root) in roots.iter().enumerate() {
-            if !root.exists() {
-                continue;
-            }
-            let _ = window.emit(
-                "scan-log",
-                LogPayload {
-                    icon: "folder_open",
-                    text: format!("扫描目录: {}", root.to_string_lossy()),
-                },
-            );
-            for entry in WalkDir::new(root).into_iter().filter_map(Result::ok) {
-                if entry.file_name() == ".git" && entry.file_type().is_dir() {
-                    if let Some(repo_root) = entry.path().parent() {
-                        if git2::Repository::open(repo_root).is_ok() {
-                            git_count += 1;
-                            let _ = window.emit(
-                                "scan-log",
-                                LogPayload {
-                                    icon: "data_object",
-                                    text: format!("发现 Git 仓库: {}", repo_root.to_string_lossy()),
-                                },
-                            );
-                        }
-                    }
-                }
-                if is_ignored(&entry) {
-                    continue;
-                }
-            }
-            let pct = 18 + (((idx + 1) as f32 / roots.len().max(1) as f32) * 42.0) as u8;
-            let _ = window.emit(
-                "scan-progress",
-                ProgressPayload {
-                    progress: pct.min(60),
-                },
-            );
-        }
-        summary.git_repos = git_count;
-
-        // 步骤 3：文档抽样统计（带时间范围过滤）
-        let time_range_clone = time_range.clone();
-        let docs_count: usize = roots
-            .par_iter()
-            .filter(|p| p.exists())
-            .map(|root| {
-                WalkDir::new(root)
-                    .into_iter()
-                    .filter_map(Result::ok)
-                    .filter(|e| !e.file_type().is_dir())
-                    .filter(|e| !is_ignored(e))
-                    .filter(|e| is_doc(e.path()))
-                    .filter(|e| is_within_time_range(e.path(), &time_range_clone))
-                    .count()
-            })
-            .sum();
-        summary.documents = docs_count;
-        let _ = window.emit(
-            "scan-log",
-            LogPayload {
-                icon: "description",
-                text: format!(
-                    "文档统计完成: {} 个候选文件 (时间范围: {})",
-                    docs_count, time_range_clone
-                ),
-            },
-        );
-        let _ = window.emit("scan-progress", ProgressPayload { progress: 84 });
-
-        // 步骤 4：完成
-        if let Some(state) = window
-            .app_handle()
-            .try_state::<Mutex<Option<ScanSummary>>>()
-        {
-            if let Ok(mut s) = state.lock() {
-                *s = Some(summary.clone());
+) -> Result<String, String> {
+    // 兼容旧流程：按名称查找项目，不存在则按默认范围（ALL）创建；已存在则以本次传入的时间范围为准
+    let existing: Option<String> = conn
+        .query_row(
+            "SELECT id FROM projects WHERE name = ?",
+            [&project_name],
+            |row| row.get(0),
+        )
+        .ok();
+    let project_id = match existing {
+        Some(id) => {
+            conn.execute(
+                "UPDATE projects SET time_range = ? WHERE id = ?",
+                [&time_range, &id],
+            )
+            .map_err(|e| e.to_string())?;
+            // frozen 项目按新的时间范围重新固定起点
+            let mode: Option<String> = conn
+                .query_row(
+                    "SELECT window_mode FROM projects WHERE id = ?",
+                    [&id],
+                    |row| row.get(0),
+                )
+                .map_err(|e| e.to_string())?;
+            if let Some(mode @ "frozen") = mode.as_deref() {
+                update_window_mode(conn, &id, mode)?;
             }
+            id
         }
-        let _ = window.emit(
-            "scan-log",
-            LogPayload {
-                icon: "sync",
-                text: "正在分析会议记录...".into(),
-            },
-        );
-        std::thread::sleep(std::time::Duration::from_millis(500));
-
-        // 更新数据库中的扫描摘要
-        let summary_json = serde_json::to_string(&summary).unwrap_or_default();
-        if let Ok(conn) = Connection::open(DatabaseManager::get_db_path()) {
-            let _ = conn.execute(
-                "UPDATE projects SET scan_summary = ?, updated_at = ? WHERE name = ?",
-                &[
-                    &summary_json,
-                    &chrono::Local::now().format("%Y-%m-%d %H:%M:%S").to_string(),
-                    &project_name,
-                ],
-            );
+        None => {
+            insert_project(
+                conn,
+                ProjectInput {
+                    name: project_name,
+                    time_range,
+                    scan_scope: None,
+                    scan_folders: None,
+                    collectors: None,
+                    window_mode: None,
+                },
+            )?
+            .id
         }
-
-        let _ = window.emit("scan-progress", ProgressPayload { progress: 100 });
-        let _ = window.emit("scan-done", &summary);
-    });
-    Ok(())
+    };
+    Ok(project_id)
 }
 
-#[tauri::command]
-fn start_scan_by_id(window: tauri::Window, project_id: String) -> Result<(), String> {
-    let db = window.state::<DatabaseManager>();
-    let conn = db.get_connection().map_err(|e| e.to_string())?;
+// ====== 采集器配置 ======
 
-    // 查询项目信息
-    let mut stmt = conn.prepare("SELECT id, name, folder_path, time_range, scan_scope, scan_folders FROM projects WHERE id = ?")
-        .map_err(|e| e.to_string())?;
-    let mut rows = stmt.query(&[&project_id]).map_err(|e| e.to_string())?;
-    let (pid, name, project_folder, time_range, scan_scope, scan_folders_json): (
-        String,
-        String,
-        String,
-        String,
-        Option<String>,
-        Option<String>,
-    ) = if let Some(row) = rows.next().map_err(|e| e.to_string())? {
-        (
-            row.get(0).map_err(|e| e.to_string())?,
-            row.get(1).map_err(|e| e.to_string())?,
-            row.get(2).map_err(|e| e.to_string())?,
-            row.get(3).map_err(|e| e.to_string())?,
-            row.get(4).ok(),
-            row.get(5).ok(),
+fn set_project_walk_options(
+    conn: &Connection,
+    project_id: &str,
+    options: &WalkOptions,
+) -> Result<(), String> {
+    let json = serde_json::to_string(options).map_err(|e| e.to_string())?;
+    let changed = conn
+        .execute(
+            "UPDATE projects SET walk_options = ? WHERE id = ?",
+            [&json, project_id],
         )
-    } else {
+        .map_err(|e| e.to_string())?;
+    if changed == 0 {
         return Err("项目不存在".into());
-    };
-
-    // 根据范围决定扫描根目录
-    let mut roots: Vec<PathBuf> = vec![];
-    if scan_scope.as_deref() == Some("CUSTOM") {
-        if let Some(json) = scan_folders_json {
-            if let Ok(vec) = serde_json::from_str::<Vec<String>>(&json) {
-                for p in vec {
-                    roots.push(PathBuf::from(p));
-                }
-            }
-        }
-        if roots.is_empty() {
-            roots.push(PathBuf::from(&project_folder));
-        }
-    } else {
-        roots = candidate_roots();
-        roots.push(PathBuf::from(&project_folder));
     }
-
-    // 清空旧结果（本次全量重扫）
-    let _ = conn.execute("DELETE FROM scan_results WHERE project_id = ?", &[&pid]);
-
-    // 启动扫描线程
-    std::thread::spawn(move || {
-        let _ = window.emit(
-            "scan-log",
-            LogPayload {
-                icon: "folder",
-                text: format!("项目名称: {}", name),
-            },
-        );
-        let range_label = match time_range.as_str() {
-            "past_year" => "过去一年",
-            "past_month" => "过去一个月",
-            "past_week" => "过去一周",
-            _ => "未知范围",
-        };
-        let _ = window.emit(
-            "scan-log",
-            LogPayload {
-                icon: "schedule",
-                text: format!("时间范围: {}", range_label),
-            },
-        );
-
-        let mut processed_roots = 0usize;
-        let total_roots = roots.len().max(1);
-
-        // 扫描并入库
-        for root in &roots {
-            processed_roots += 1;
-            let _ = window.emit(
-                "scan-log",
-                LogPayload {
-                    icon: "folder_open",
-                    text: format!("扫描目录: {}", root.to_string_lossy()),
-                },
-            );
-
-            for entry in WalkDir::new(root).into_iter().filter_map(Result::ok) {
-                if entry.file_type().is_dir() {
-                    continue;
-                }
-                if is_ignored(&entry) {
-                    continue;
-                }
-                let path = entry.path();
-                if !is_doc(path) {
-                    continue;
-                }
-                if !is_within_time_range(path, &time_range) {
-                    continue;
-                }
-
-                // 采集元数据
-                let meta = match std::fs::metadata(path) {
-                    Ok(m) => m,
-                    Err(_) => continue,
-                };
-                let size = meta.len() as i64;
-                let modified_s = meta
-                    .modified()
-                    .ok()
-                    .map(|t| {
-                        chrono::DateTime::<chrono::Local>::from(t)
-                            .format("%Y-%m-%d %H:%M:%S")
-                            .to_string()
-                    })
-                    .unwrap_or_default();
-                let created_s = meta
-                    .created()
-                    .ok()
-                    .map(|t| {
-                        chrono::DateTime::<chrono::Local>::from(t)
-                            .format("%Y-%m-%d %H:%M:%S")
-                            .to_string()
-                    })
-                    .unwrap_or_else(|| modified_s.clone());
-                let file_type = path
-                    .extension()
-                    .and_then(|s| s.to_str())
-                    .unwrap_or("")
-                    .to_ascii_lowercase();
-                let source = if scan_scope.as_deref() == Some("CUSTOM") {
-                    SourceKind::CustomSpecified.as_str()
-                } else {
-                    classify_source_from_path(&path.to_string_lossy())
-                };
-
-                let id = Uuid::new_v4().to_string();
-                let now_s = chrono::Local::now().format("%Y-%m-%d %H:%M:%S").to_string();
-                let is_valid = true;
-
-                if let Ok(conn2) = Connection::open(DatabaseManager::get_db_path()) {
-                    let _ = conn2.execute(
-                        "INSERT OR REPLACE INTO scan_results (id, project_id, file_path, file_type, source, created_at, modified_at, size_bytes, is_valid, inserted_at, updated_at)
-                         VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)",
-                        &[&id as &dyn rusqlite::ToSql,
-                          &pid,
-                          &path.to_string_lossy().to_string(),
-                          &file_type,
-                          &source.to_string(),
-                          &created_s,
-                          &modified_s,
-                          &(size as i64),
-                          &(if is_valid {1i64} else {0i64}),
-                          &now_s,
-                          &now_s],
-                    );
-                }
-            }
-
-            let pct = (processed_roots as f32 / total_roots as f32 * 100.0) as u8;
-            let _ = window.emit(
-                "scan-progress",
-                ProgressPayload {
-                    progress: pct.min(95),
-                },
-            );
-        }
-
-        let _ = window.emit("scan-progress", ProgressPayload { progress: 100 });
-        let _ = window.emit(
-            "scan-done",
-            ScanSummary {
-                git_repos: 0,
-                documents: 0,
-                chat_locations: vec![],
-            },
-        );
-    });
-
     Ok(())
 }
 
-#[tauri::command]
-fn get_results_paginated(
-    db: tauri::State<DatabaseManager>,
-    project_id: String,
-    page: usize,
-    page_size: usize,
-) -> Result<ProjectListResponseLike<ScanResultItem>, String> {
-    paginate_results(&db, &project_id, page, page_size)
-}
-
 #[derive(Serialize, Deserialize, Debug, Clone)]
 struct ProjectListResponseLike<T> {
     items: Vec<T>,
     total: usize,
     page: usize,
     total_pages: usize,
+    /// 游标分页时下一页的游标，没有下一页或按页码分页时为空
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    next_cursor: Option<String>,
 }
 
-fn paginate_results(
-    db: &tauri::State<DatabaseManager>,
+/// scan_results 的筛选条件（关键词匹配路径、按类型过滤），分页查询与导出共用
+fn results_filter(
     project_id: &str,
-    page: usize,
-    page_size: usize,
-) -> Result<ProjectListResponseLike<ScanResultItem>, String> {
-    let conn = db.get_connection().map_err(|e| e.to_string())?;
-    let page = if page == 0 { 1 } else { page };
-    let offset = (page - 1) * page_size;
-
-    let total: usize = conn
-        .query_row(
-            "SELECT COUNT(*) FROM scan_results WHERE project_id = ?",
-            [&project_id],
-            |row| row.get(0),
-        )
-        .map_err(|e| e.to_string())?;
-    let total_pages = if total == 0 {
-        1
-    } else {
-        (total + page_size - 1) / page_size
-    };
-
-    let mut stmt = conn.prepare("SELECT id, project_id, file_path, file_type, source, created_at, modified_at, size_bytes, is_valid, inserted_at, updated_at FROM scan_results WHERE project_id = ? ORDER BY updated_at DESC LIMIT ? OFFSET ?").map_err(|e| e.to_string())?;
-    let items_iter = stmt
-        .query_map(
-            [
-                &project_id as &dyn rusqlite::ToSql,
-                &(page_size as i64),
-                &(offset as i64),
-            ],
-            |row| {
-                Ok(ScanResultItem {
-                    id: row.get(0)?,
-                    project_id: row.get(1)?,
-                    file_path: row.get(2)?,
-                    file_type: row.get(3)?,
-                    source: row.get(4)?,
-                    created_at: row.get(5)?,
-                    modified_at: row.get(6)?,
-                    size_bytes: row.get(7)?,
-                    is_valid: row.get::<_, i64>(8)? == 1,
-                    inserted_at: row.get(9)?,
-                    updated_at: row.get(10)?,
-                })
-            },
-        )
-        .map_err(|e| e.to_string())?;
-    let items: Vec<ScanResultItem> = items_iter.map(|r| r.unwrap()).collect();
-
-    Ok(ProjectListResponseLike {
-        items,
-        total,
-        page,
-        total_pages,
-    })
+    q: Option<&str>,
+    file_types: Option<&[String]>,
+) -> (String, Vec<Box<dyn rusqlite::ToSql>>) {
+    results::ResultQuery::simple(q, file_types).filter(project_id, None)
 }
 
-#[tauri::command]
-fn get_results_paginated_adv(
-    db: tauri::State<DatabaseManager>,
-    project_id: String,
-    page: usize,
-    page_size: usize,
-    q: Option<String>,
-    file_types: Option<Vec<String>>,
-) -> Result<ProjectListResponseLike<ScanResultItem>, String> {
-    let conn = db.get_connection().map_err(|e| e.to_string())?;
-    let page = if page == 0 { 1 } else { page };
-    let offset = (page - 1) * page_size;
-
-    // 构建 WHERE 子句与参数
-    let mut where_sql = String::from("project_id = ?");
-    let mut params: Vec<Box<dyn rusqlite::ToSql>> = vec![Box::new(project_id.clone())];
-
-    if let Some(query) = q.clone().filter(|s| !s.trim().is_empty()) {
-        where_sql.push_str(" AND file_path LIKE ? ESCAPE '\\'");
-        let like = format!("%{}%", query.replace('%', "\\%").replace('_', "\\_"));
-        params.push(Box::new(like));
-    }
-
-    if let Some(types) = file_types.clone().filter(|v| !v.is_empty()) {
-        let placeholders = std::iter::repeat("?")
-            .take(types.len())
-            .collect::<Vec<_>>()
-            .join(",");
-        where_sql.push_str(&format!(" AND file_type IN ({})", placeholders));
-        for t in types {
-            params.push(Box::new(t));
-        }
-    }
-
-    // 统计总数
-    let count_sql = format!("SELECT COUNT(*) FROM scan_results WHERE {}", where_sql);
-    let total: usize = {
-        let mut stmt = conn.prepare(&count_sql).map_err(|e| e.to_string())?;
-        let mut count: usize = 0;
-        {
-            let mut rows = stmt
-                .query(rusqlite::params_from_iter(params.iter().map(|b| &**b)))
-                .map_err(|e| e.to_string())?;
-            if let Some(row) = rows.next().map_err(|e| e.to_string())? {
-                count = row.get(0).map_err(|e| e.to_string())?;
-            }
-        }
-        count
-    };
-    let total_pages = if total == 0 {
-        1
-    } else {
-        (total + page_size - 1) / page_size
-    };
-
-    // 查询分页
-    let data_sql = format!(
-        "SELECT id, project_id, file_path, file_type, source, created_at, modified_at, size_bytes, is_valid, inserted_at, updated_at \
-         FROM scan_results WHERE {} ORDER BY updated_at DESC LIMIT ? OFFSET ?",
-        where_sql
-    );
-    let mut stmt = conn.prepare(&data_sql).map_err(|e| e.to_string())?;
-    // 注意：不能向 Vec<&dyn ToSql> 推入对临时值 (page_size as i64) 的引用，
-    // 否则会触发 E0716（临时值在使用时已被释放）。
-    // 这里通过中间变量延长生命周期，保证引用在 query_map 调用期间仍然有效。
-    let mut run_params: Vec<&dyn rusqlite::ToSql> = params
-        .iter()
-        .map(|b| &**b as &dyn rusqlite::ToSql)
-        .collect();
-    let page_size_i64 = page_size as i64;
-    let offset_i64 = offset as i64;
-    run_params.push(&page_size_i64);
-    run_params.push(&offset_i64);
-
-    let iter = stmt
-        .query_map(run_params.as_slice(), |row| {
-            Ok(ScanResultItem {
-                id: row.get(0)?,
-                project_id: row.get(1)?,
-                file_path: row.get(2)?,
-                file_type: row.get(3)?,
-                source: row.get(4)?,
-                created_at: row.get(5)?,
-                modified_at: row.get(6)?,
-                size_bytes: row.get(7)?,
-                is_valid: row.get::<_, i64>(8)? == 1,
-                inserted_at: row.get(9)?,
-                updated_at: row.get(10)?,
-            })
-        })
-        .map_err(|e| e.to_string())?;
-    let items: Vec<ScanResultItem> = iter.map(|r| r.unwrap()).collect();
-
-    Ok(ProjectListResponseLike {
-        items,
-        total,
-        page,
-        total_pages,
-    })
-}
-
-#[tauri::command]
-fn get_project_scan_roots(
-    db: tauri::State<DatabaseManager>,
+#[cfg(feature = "gui")]
+#[derive(Debug, Clone, Serialize)]
+struct ScanRunRecord {
+    id: String,
     project_id: String,
-) -> Result<Vec<String>, String> {
-    let conn = db.get_connection().map_err(|e| e.to_string())?;
-    let mut stmt = conn
-        .prepare("SELECT folder_path, scan_scope, scan_folders FROM projects WHERE id = ?")
-        .map_err(|e| e.to_string())?;
-    let mut rows = stmt.query(&[&project_id]).map_err(|e| e.to_string())?;
-    if let Some(row) = rows.next().map_err(|e| e.to_string())? {
-        let project_folder: String = row.get(0).map_err(|e| e.to_string())?;
-        let scan_scope: Option<String> = row.get(1).ok();
-        let scan_folders_json: Option<String> = row.get(2).ok();
-        let mut roots: Vec<String> = Vec::new();
-        if scan_scope.as_deref() == Some("CUSTOM") {
-            if let Some(json) = scan_folders_json {
-                if let Ok(vec) = serde_json::from_str::<Vec<String>>(&json) {
-                    roots.extend(vec);
-                }
-            }
-            if roots.is_empty() {
-                roots.push(project_folder);
-            }
-        } else {
-            roots = candidate_roots()
-                .into_iter()
-                .map(|p| p.to_string_lossy().to_string())
-                .collect();
-            roots.push(project_folder);
-        }
-        return Ok(roots);
-    }
-    Ok(vec![])
-}
-
-#[tauri::command]
-fn get_scan_summary(state: tauri::State<Mutex<Option<ScanSummary>>>) -> Option<ScanSummary> {
-    state.lock().ok().and_then(|s| s.clone())
+    started_at: String,
+    finished_at: String,
+    duration_ms: i64,
+    skipped: i64,
+    failed: i64,
+    summary: serde_json::Value,
 }
 
-#[cfg_attr(mobile, tauri::mobile_entry_point)]
-pub fn run() {
-    let db_manager = DatabaseManager::new();
-
-    // 初始化数据库（忽略错误，允许首次运行失败）
-    let _ = db_manager.initialize();
-
-    tauri::Builder::default()
-        .manage(db_manager)
-        .manage::<Mutex<Option<ScanSummary>>>(Mutex::new(None))
-        .plugin(tauri_plugin_dialog::init())
-        .plugin(tauri_plugin_opener::init())
-        .plugin(tauri_plugin_fs::init())
-        .invoke_handler(tauri::generate_handler![
-            init_database,
-            create_project,
-            get_projects,
-            get_projects_paginated,
-            get_project_by_name,
-            get_project_by_id,
-            delete_project,
-            start_scan,
-            start_scan_by_id,
-            get_results_paginated,
-            get_results_paginated_adv,
-            get_project_scan_roots,
-            get_scan_summary,
-            set_config,
-            get_config,
-            set_current_project,
-            get_current_project
-        ])
-        .run(tauri::generate_context!())
-        .expect("error while running tauri application");
-}
+#[cfg(feature = "gui")]
+pub use commands::run;
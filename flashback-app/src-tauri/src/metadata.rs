@@ -0,0 +1,453 @@
+//! 文件内嵌的元数据：照片的 EXIF（拍摄时间、相机，GPS 位置默认不提取）、
+//! Office 文档的 docProps/core.xml（作者、标题、最后修改者、修订号、创建与修改时间）与 PDF 的文档信息字典
+//! 复制、下载后文件的时间戳往往不准，内嵌的日期才是真实时间；文档采集按项目设置可改用内嵌日期判断时间范围
+//! 提取结果按 (project_id, file_path) 保存在 result_metadata，文件未变时重扫沿用，不再重新解析
+
+use chrono::{DateTime, FixedOffset, Local, NaiveDate, NaiveDateTime, TimeZone};
+use rusqlite::{params, Connection, OptionalExtension};
+use serde::Serialize;
+use std::collections::HashSet;
+use std::io::{BufReader, Read};
+use std::path::Path;
+
+const TIME_FORMAT: &str = "%Y-%m-%d %H:%M:%S";
+
+const EXIF_TYPES: &[&str] = &["jpg", "jpeg", "tif", "tiff", "heic", "png", "webp"];
+const OOXML_TYPES: &[&str] = &["docx", "dotx", "xlsx", "xlsm", "pptx"];
+/// 超过此大小的 PDF 不解析（需整个读入内存）
+const MAX_PDF_BYTES: u64 = 100 * 1024 * 1024;
+/// core.xml 的大小上限，防止异常的压缩包
+const MAX_CORE_XML_BYTES: u64 = 1024 * 1024;
+
+/// result_metadata 行对应的 scan_results 行（用于相关子查询，外层查询须为不带别名的 scan_results）
+const METADATA_OF_ROW: &str =
+    "m.project_id = scan_results.project_id AND m.file_path = scan_results.file_path";
+
+pub(crate) fn init_schema(conn: &Connection) -> rusqlite::Result<()> {
+    // file_modified_at、file_size、with_gps 为提取时的文件状态与设置，不变时沿用
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS result_metadata (
+            project_id TEXT NOT NULL,
+            file_path TEXT NOT NULL,
+            file_modified_at TEXT NOT NULL,
+            file_size INTEGER NOT NULL,
+            with_gps INTEGER NOT NULL DEFAULT 0,
+            created_at TEXT,
+            modified_at TEXT,
+            author TEXT,
+            title TEXT,
+            last_modified_by TEXT,
+            revision TEXT,
+            application TEXT,
+            camera TEXT,
+            latitude REAL,
+            longitude REAL,
+            extracted_at TEXT NOT NULL,
+            PRIMARY KEY (project_id, file_path)
+        )",
+        [],
+    )?;
+    Ok(())
+}
+
+/// 一个文件的内嵌元数据，时间统一为本地时间 `%Y-%m-%d %H:%M:%S`
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub(crate) struct FileMetadata {
+    /// 照片的拍摄时间，文档属性中的创建时间
+    pub created_at: Option<String>,
+    /// 文档属性中的最后修改时间
+    pub modified_at: Option<String>,
+    pub author: Option<String>,
+    pub title: Option<String>,
+    /// 最后修改者（Office 文档）
+    pub last_modified_by: Option<String>,
+    /// 修订号（Office 文档）
+    pub revision: Option<String>,
+    /// 生成文档的程序（PDF）
+    pub application: Option<String>,
+    /// 相机品牌与型号
+    pub camera: Option<String>,
+    /// 拍摄位置（十进制度，南纬、西经为负），项目开启后才提取
+    pub latitude: Option<f64>,
+    pub longitude: Option<f64>,
+}
+
+impl FileMetadata {
+    /// 判断时间范围所用的内嵌日期：文档的最后修改时间，其次为创建（拍摄）时间
+    pub fn content_time(&self) -> Option<DateTime<Local>> {
+        self.modified_at
+            .as_deref()
+            .or(self.created_at.as_deref())
+            .and_then(crate::scan::parse_local_time)
+    }
+}
+
+const METADATA_COLUMNS: &str = "created_at, modified_at, author, title, last_modified_by, revision, application, camera, latitude, longitude";
+
+fn metadata_from_row(row: &rusqlite::Row) -> rusqlite::Result<FileMetadata> {
+    Ok(FileMetadata {
+        created_at: row.get(0)?,
+        modified_at: row.get(1)?,
+        author: row.get(2)?,
+        title: row.get(3)?,
+        last_modified_by: row.get(4)?,
+        revision: row.get(5)?,
+        application: row.get(6)?,
+        camera: row.get(7)?,
+        latitude: row.get(8)?,
+        longitude: row.get(9)?,
+    })
+}
+
+/// 是否支持提取该类型文件的元数据
+pub(crate) fn supports(path: &Path) -> bool {
+    let ext = extension(path);
+    EXIF_TYPES.contains(&ext.as_str()) || OOXML_TYPES.contains(&ext.as_str()) || ext == "pdf"
+}
+
+fn extension(path: &Path) -> String {
+    path.extension()
+        .and_then(|s| s.to_str())
+        .unwrap_or("")
+        .to_ascii_lowercase()
+}
+
+/// 读取文件的元数据：文件的修改时间、大小与 GPS 设置都未变时沿用上次的结果，否则重新提取并保存；
+/// 不支持的类型返回 None，解析失败或没有元数据时保存空记录，避免每次重扫都重新解析
+pub(crate) fn load_or_extract(
+    conn: &Connection,
+    project_id: &str,
+    path: &Path,
+    file_modified_at: &str,
+    file_size: i64,
+    with_gps: bool,
+) -> rusqlite::Result<Option<FileMetadata>> {
+    if !supports(path) {
+        return Ok(None);
+    }
+    let path_s = path.to_string_lossy().to_string();
+    let cached = conn
+        .query_row(
+            &format!(
+                "SELECT {} FROM result_metadata WHERE project_id = ? AND file_path = ?
+                 AND file_modified_at = ? AND file_size = ? AND with_gps = ?",
+                METADATA_COLUMNS
+            ),
+            params![
+                project_id,
+                path_s,
+                file_modified_at,
+                file_size,
+                with_gps as i64
+            ],
+            metadata_from_row,
+        )
+        .optional()?;
+    if cached.is_some() {
+        return Ok(cached);
+    }
+    let extracted = extract(path, with_gps).unwrap_or_default();
+    conn.execute(
+        &format!(
+            "INSERT OR REPLACE INTO result_metadata (project_id, file_path, file_modified_at, file_size, with_gps, extracted_at, {})
+             VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)",
+            METADATA_COLUMNS
+        ),
+        params![
+            project_id,
+            path_s,
+            file_modified_at,
+            file_size,
+            with_gps as i64,
+            Local::now().format(TIME_FORMAT).to_string(),
+            extracted.created_at,
+            extracted.modified_at,
+            extracted.author,
+            extracted.title,
+            extracted.last_modified_by,
+            extracted.revision,
+            extracted.application,
+            extracted.camera,
+            extracted.latitude,
+            extracted.longitude
+        ],
+    )?;
+    Ok(Some(extracted))
+}
+
+/// 已保存的元数据
+#[cfg(feature = "gui")]
+pub(crate) fn get(
+    conn: &Connection,
+    project_id: &str,
+    file_path: &str,
+) -> Result<Option<FileMetadata>, String> {
+    conn.query_row(
+        &format!(
+            "SELECT {} FROM result_metadata WHERE project_id = ? AND file_path = ?",
+            METADATA_COLUMNS
+        ),
+        [project_id, file_path],
+        metadata_from_row,
+    )
+    .optional()
+    .map_err(|e| e.to_string())
+}
+
+/// 删除已不在扫描结果中的文件的元数据（keep 中的路径除外），返回删除条数
+pub(crate) fn remove_orphans(
+    conn: &Connection,
+    project_id: &str,
+    keep: &HashSet<String>,
+) -> rusqlite::Result<usize> {
+    let orphans: Vec<String> = conn
+        .prepare(
+            "SELECT file_path FROM result_metadata WHERE project_id = ?1 AND NOT EXISTS (
+                SELECT 1 FROM scan_results r
+                WHERE r.project_id = ?1 AND r.file_path = result_metadata.file_path
+            )",
+        )?
+        .query_map([project_id], |row| row.get(0))?
+        .collect::<rusqlite::Result<_>>()?;
+    let mut removed = 0;
+    for path in orphans.iter().filter(|p| !keep.contains(*p)) {
+        removed += conn.execute(
+            "DELETE FROM result_metadata WHERE project_id = ? AND file_path = ?",
+            params![project_id, path],
+        )?;
+    }
+    Ok(removed)
+}
+
+/// scan_results 行的时间优先取内嵌日期（没有时取文件时间），用于按内嵌日期筛选；
+/// column 为 created_at 或 modified_at，modified_at 没有内嵌修改时间时取内嵌的创建时间
+pub(crate) fn embedded_time_expr(column: &str) -> String {
+    let embedded = if column == "created_at" {
+        "m.created_at"
+    } else {
+        "COALESCE(m.modified_at, m.created_at)"
+    };
+    format!(
+        "COALESCE((SELECT {} FROM result_metadata m WHERE {}), {})",
+        embedded, METADATA_OF_ROW, column
+    )
+}
+
+/// 按类型提取元数据；不支持的类型或解析失败返回 None
+pub(crate) fn extract(path: &Path, with_gps: bool) -> Option<FileMetadata> {
+    let ext = extension(path);
+    if EXIF_TYPES.contains(&ext.as_str()) {
+        extract_exif(path, with_gps)
+    } else if OOXML_TYPES.contains(&ext.as_str()) {
+        extract_ooxml(path)
+    } else if ext == "pdf" {
+        extract_pdf(path)
+    } else {
+        None
+    }
+}
+
+// ==================== EXIF ====================
+
+fn extract_exif(path: &Path, with_gps: bool) -> Option<FileMetadata> {
+    use exif::{In, Tag};
+
+    let file = std::fs::File::open(path).ok()?;
+    let exif = exif::Reader::new()
+        .read_from_container(&mut BufReader::new(file))
+        .ok()?;
+    let ascii = |tag: Tag| -> Option<String> {
+        match &exif.get_field(tag, In::PRIMARY)?.value {
+            exif::Value::Ascii(values) => values
+                .first()
+                .map(|v| {
+                    String::from_utf8_lossy(v)
+                        .trim_end_matches('\0')
+                        .trim()
+                        .to_string()
+                })
+                .filter(|s| !s.is_empty()),
+            _ => None,
+        }
+    };
+
+    // 拍摄时间，带时区偏移（OffsetTimeOriginal）时换算为本地时间
+    let created_at = [Tag::DateTimeOriginal, Tag::DateTimeDigitized]
+        .into_iter()
+        .find_map(|tag| {
+            let raw = ascii(tag)?;
+            let mut dt = exif::DateTime::from_ascii(raw.as_bytes()).ok()?;
+            let offset_tag = if tag == Tag::DateTimeOriginal {
+                Tag::OffsetTimeOriginal
+            } else {
+                Tag::OffsetTimeDigitized
+            };
+            if let Some(offset) = ascii(offset_tag) {
+                let _ = dt.parse_offset(offset.as_bytes());
+            }
+            let naive = NaiveDate::from_ymd_opt(dt.year.into(), dt.month.into(), dt.day.into())?
+                .and_hms_opt(dt.hour.into(), dt.minute.into(), dt.second.into())?;
+            local_time(naive, dt.offset.map(|m| i32::from(m) * 60))
+        });
+
+    let camera = match (ascii(Tag::Make), ascii(Tag::Model)) {
+        (Some(make), Some(model)) if model.starts_with(&make) => Some(model),
+        (Some(make), Some(model)) => Some(format!("{} {}", make, model)),
+        (make, model) => make.or(model),
+    };
+
+    let (latitude, longitude) = if with_gps {
+        let coordinate = |tag: Tag, ref_tag: Tag, negative: &str| -> Option<f64> {
+            let degrees = match &exif.get_field(tag, In::PRIMARY)?.value {
+                exif::Value::Rational(v) if v.len() >= 3 => {
+                    v[0].to_f64() + v[1].to_f64() / 60.0 + v[2].to_f64() / 3600.0
+                }
+                _ => return None,
+            };
+            if !degrees.is_finite() {
+                return None;
+            }
+            Some(if ascii(ref_tag).as_deref() == Some(negative) {
+                -degrees
+            } else {
+                degrees
+            })
+        };
+        (
+            coordinate(Tag::GPSLatitude, Tag::GPSLatitudeRef, "S"),
+            coordinate(Tag::GPSLongitude, Tag::GPSLongitudeRef, "W"),
+        )
+    } else {
+        (None, None)
+    };
+
+    Some(FileMetadata {
+        created_at,
+        author: ascii(Tag::Artist),
+        title: ascii(Tag::ImageDescription),
+        camera,
+        latitude,
+        longitude,
+        ..Default::default()
+    })
+}
+
+// ==================== Office 文档（OOXML） ====================
+
+fn extract_ooxml(path: &Path) -> Option<FileMetadata> {
+    let file = std::fs::File::open(path).ok()?;
+    let mut archive = zip::ZipArchive::new(BufReader::new(file)).ok()?;
+    let entry = archive.by_name("docProps/core.xml").ok()?;
+    let mut xml = String::new();
+    entry
+        .take(MAX_CORE_XML_BYTES)
+        .read_to_string(&mut xml)
+        .ok()?;
+    parse_core_xml(&xml)
+}
+
+/// 解析 docProps/core.xml（按元素的本地名取值，忽略命名空间前缀）
+pub(crate) fn parse_core_xml(xml: &str) -> Option<FileMetadata> {
+    let doc = roxmltree::Document::parse(xml).ok()?;
+    let text = |name: &str| -> Option<String> {
+        doc.descendants()
+            .find(|n| n.is_element() && n.tag_name().name() == name)
+            .and_then(|n| n.text())
+            .map(str::trim)
+            .filter(|s| !s.is_empty())
+            .map(str::to_string)
+    };
+    Some(FileMetadata {
+        created_at: text("created").and_then(|s| parse_w3c_time(&s)),
+        modified_at: text("modified").and_then(|s| parse_w3c_time(&s)),
+        author: text("creator"),
+        title: text("title"),
+        last_modified_by: text("lastModifiedBy"),
+        revision: text("revision"),
+        ..Default::default()
+    })
+}
+
+/// W3CDTF 时间（如 2024-03-01T08:30:00Z），不带时区的按本地时间处理
+fn parse_w3c_time(s: &str) -> Option<String> {
+    if let Ok(t) = DateTime::parse_from_rfc3339(s) {
+        return Some(t.with_timezone(&Local).format(TIME_FORMAT).to_string());
+    }
+    let naive = NaiveDateTime::parse_from_str(s, "%Y-%m-%dT%H:%M:%S%.f")
+        .ok()
+        .or_else(|| {
+            NaiveDate::parse_from_str(s, "%Y-%m-%d")
+                .ok()
+                .and_then(|d| d.and_hms_opt(0, 0, 0))
+        })?;
+    local_time(naive, None)
+}
+
+// ==================== PDF ====================
+
+fn extract_pdf(path: &Path) -> Option<FileMetadata> {
+    if std::fs::metadata(path).ok()?.len() > MAX_PDF_BYTES {
+        return None;
+    }
+    let info = lopdf::Document::load_metadata(path).ok()?;
+    let clean = |s: Option<String>| {
+        s.map(|s| {
+            s.trim_matches(|c: char| c.is_whitespace() || c == '\0')
+                .to_string()
+        })
+        .filter(|s| !s.is_empty())
+    };
+    Some(FileMetadata {
+        created_at: info.creation_date.as_deref().and_then(parse_pdf_time),
+        modified_at: info.modification_date.as_deref().and_then(parse_pdf_time),
+        author: clean(info.author),
+        title: clean(info.title),
+        application: clean(info.creator).or_else(|| clean(info.producer)),
+        ..Default::default()
+    })
+}
+
+/// PDF 日期 `D:YYYYMMDDHHmmSSOHH'mm'`，年份之后的部分都可省略；没有时区的按本地时间处理
+pub(crate) fn parse_pdf_time(s: &str) -> Option<String> {
+    let s = s.trim();
+    let s = s.strip_prefix("D:").unwrap_or(s);
+    let digits = s.bytes().take_while(u8::is_ascii_digit).count();
+    if digits < 4 {
+        return None;
+    }
+    let field = |start: usize, default: u32| -> u32 {
+        s.get(start..start + 2)
+            .filter(|_| start + 2 <= digits)
+            .and_then(|v| v.parse().ok())
+            .unwrap_or(default)
+    };
+    let naive = NaiveDate::from_ymd_opt(s[..4].parse().ok()?, field(4, 1), field(6, 1))?
+        .and_hms_opt(field(8, 0), field(10, 0), field(12, 0))?;
+    let rest = &s[digits..];
+    let offset = match rest.chars().next() {
+        Some('Z') => Some(0),
+        Some(sign @ ('+' | '-')) => {
+            let parts: Vec<i32> = rest[1..]
+                .split('\'')
+                .filter_map(|p| p.trim().parse().ok())
+                .collect();
+            let seconds = parts.first().copied().unwrap_or(0) * 3600
+                + parts.get(1).copied().unwrap_or(0) * 60;
+            Some(if sign == '-' { -seconds } else { seconds })
+        }
+        _ => None,
+    };
+    local_time(naive, offset)
+}
+
+/// 给定时区偏移（秒）的时间换算为本地时间；没有偏移时视为本地时间
+fn local_time(naive: NaiveDateTime, offset_seconds: Option<i32>) -> Option<String> {
+    let local = match offset_seconds {
+        Some(secs) => FixedOffset::east_opt(secs)?
+            .from_local_datetime(&naive)
+            .single()?
+            .with_timezone(&Local),
+        None => naive.and_local_timezone(Local).earliest()?,
+    };
+    Some(local.format(TIME_FORMAT).to_string())
+}
@@ -0,0 +1,416 @@
+//! 回顾报告：汇总项目采集结果，渲染为 Markdown 与自包含 HTML，写入项目目录
+//! 模板使用 Jinja 语法（minijinja），内置模板见 src/templates；
+//! 在 ~/FlashBack/templates 下放置 report.md / report.html 即可覆盖对应的内置模板
+
+use rusqlite::Connection;
+use serde::Serialize;
+use std::path::{Path, PathBuf};
+
+use crate::results::{self, Paging, ResultQuery};
+use crate::{collection, curation, scan, timeline, DatabaseManager};
+
+const DEFAULT_MD_TEMPLATE: &str = include_str!("templates/report.md");
+const DEFAULT_HTML_TEMPLATE: &str = include_str!("templates/report.html");
+
+/// 各列表在报告中最多展示的条数
+const TOP_LIMIT: usize = 20;
+
+#[derive(Debug, Clone, Serialize)]
+pub(crate) struct GeneratedReport {
+    pub(crate) markdown_path: String,
+    pub(crate) html_path: String,
+}
+
+#[derive(Debug, Serialize)]
+struct ReportProject {
+    id: String,
+    name: String,
+    folder_path: String,
+    time_range: String,
+    time_range_label: String,
+}
+
+#[derive(Debug, Default, Serialize)]
+struct ReportStats {
+    documents: usize,
+    git_repos: usize,
+    commits: usize,
+    insertions: i64,
+    deletions: i64,
+    meetings: usize,
+    emails: usize,
+    shell_commands: usize,
+    web_pages: usize,
+    chat_locations: usize,
+}
+
+#[derive(Debug, Serialize)]
+struct FileTypeCount {
+    file_type: String,
+    count: usize,
+}
+
+#[derive(Debug, Serialize)]
+struct ReportDocument {
+    file_path: String,
+    file_type: String,
+    modified_at: String,
+    size_bytes: i64,
+}
+
+/// 人工标记星标的文件，按星标等级排序
+#[derive(Debug, Serialize)]
+struct ReportStarred {
+    file_path: String,
+    file_type: String,
+    modified_at: String,
+    star: u8,
+    note: String,
+    tags: Vec<String>,
+}
+
+/// 置顶到报告的智能集合，documents 按集合自身的排序取前 TOP_LIMIT 个
+#[derive(Debug, Serialize)]
+struct ReportCollection {
+    name: String,
+    count: usize,
+    documents: Vec<ReportDocument>,
+}
+
+#[derive(Debug, Serialize)]
+struct ReportRepo {
+    repo_path: String,
+    commits: usize,
+    insertions: i64,
+    deletions: i64,
+    first_commit_at: String,
+    last_commit_at: String,
+}
+
+#[derive(Debug, Serialize)]
+struct ReportCommit {
+    repo_path: String,
+    hash: String,
+    summary: String,
+    author_name: String,
+    committed_at: String,
+    files_changed: i64,
+    insertions: i64,
+    deletions: i64,
+}
+
+#[derive(Debug, Serialize)]
+struct ReportChatLocation {
+    app: String,
+    path: String,
+}
+
+/// 模板上下文，字段即模板中可用的变量
+#[derive(Debug, Serialize)]
+struct ReportData {
+    generated_at: String,
+    project: ReportProject,
+    stats: ReportStats,
+    timeline: timeline::Timeline,
+    document_types: Vec<FileTypeCount>,
+    top_documents: Vec<ReportDocument>,
+    starred_documents: Vec<ReportStarred>,
+    collections: Vec<ReportCollection>,
+    repos: Vec<ReportRepo>,
+    commit_highlights: Vec<ReportCommit>,
+    chat_locations: Vec<ReportChatLocation>,
+}
+
+/// 用户自定义模板所在目录
+pub(crate) fn templates_dir() -> PathBuf {
+    DatabaseManager::get_db_path()
+        .parent()
+        .map(Path::to_path_buf)
+        .unwrap_or_else(|| PathBuf::from("."))
+        .join("templates")
+}
+
+/// 把内置模板写入自定义模板目录（已存在的文件不覆盖），返回目录路径
+#[cfg(feature = "gui")]
+pub(crate) fn init_templates() -> Result<String, String> {
+    let dir = templates_dir();
+    std::fs::create_dir_all(&dir).map_err(|e| e.to_string())?;
+    for (name, content) in [
+        ("report.md", DEFAULT_MD_TEMPLATE),
+        ("report.html", DEFAULT_HTML_TEMPLATE),
+    ] {
+        let path = dir.join(name);
+        if !path.exists() {
+            std::fs::write(&path, content).map_err(|e| e.to_string())?;
+        }
+    }
+    Ok(dir.to_string_lossy().to_string())
+}
+
+fn load_template(name: &str, default: &'static str) -> Result<String, String> {
+    let custom = templates_dir().join(name);
+    if custom.is_file() {
+        std::fs::read_to_string(&custom)
+            .map_err(|e| format!("读取自定义模板失败: {} ({})", custom.to_string_lossy(), e))
+    } else {
+        Ok(default.to_string())
+    }
+}
+
+/// 生成报告并写入项目目录，返回两个文件的路径
+pub(crate) fn generate(conn: &Connection, project_id: &str) -> Result<GeneratedReport, String> {
+    let data = collect_data(conn, project_id)?;
+
+    let md_source = load_template("report.md", DEFAULT_MD_TEMPLATE)?;
+    let html_source = load_template("report.html", DEFAULT_HTML_TEMPLATE)?;
+    let mut env = minijinja::Environment::new();
+    env.set_trim_blocks(true);
+    env.set_lstrip_blocks(true);
+    // 模板名决定转义方式：.html 自动做 HTML 转义，.md 原样输出
+    env.add_template("report.md", &md_source)
+        .map_err(|e| format!("Markdown 模板有误: {}", e))?;
+    env.add_template("report.html", &html_source)
+        .map_err(|e| format!("HTML 模板有误: {}", e))?;
+    let render = |name: &str| -> Result<String, String> {
+        env.get_template(name)
+            .and_then(|t| t.render(&data))
+            .map_err(|e| format!("渲染 {} 失败: {}", name, e))
+    };
+    let markdown = render("report.md")?;
+    let html = render("report.html")?;
+
+    let folder = PathBuf::from(&data.project.folder_path);
+    std::fs::create_dir_all(&folder).map_err(|e| e.to_string())?;
+    let markdown_path = folder.join("flashback-report.md");
+    let html_path = folder.join("flashback-report.html");
+    std::fs::write(&markdown_path, markdown).map_err(|e| e.to_string())?;
+    std::fs::write(&html_path, html).map_err(|e| e.to_string())?;
+
+    Ok(GeneratedReport {
+        markdown_path: markdown_path.to_string_lossy().to_string(),
+        html_path: html_path.to_string_lossy().to_string(),
+    })
+}
+
+fn collect_data(conn: &Connection, project_id: &str) -> Result<ReportData, String> {
+    let (name, folder_path, time_range, scan_summary): (String, String, String, Option<String>) =
+        conn.query_row(
+            "SELECT name, folder_path, time_range, scan_summary FROM projects WHERE id = ?",
+            [project_id],
+            |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?, row.get(3)?)),
+        )
+        .map_err(|_| "项目不存在".to_string())?;
+
+    let count = |sql: &str| -> Result<usize, String> {
+        conn.query_row(sql, [project_id], |row| row.get::<_, i64>(0))
+            .map(|n| n as usize)
+            .map_err(|e| e.to_string())
+    };
+
+    // 聊天数据目前只记录数据位置，取自最近一次扫描摘要
+    let chat_locations: Vec<ReportChatLocation> = scan_summary
+        .as_deref()
+        .and_then(|s| serde_json::from_str::<serde_json::Value>(s).ok())
+        .and_then(|v| v.get("chat_locations").and_then(|c| c.as_array()).cloned())
+        .unwrap_or_default()
+        .iter()
+        .map(|c| ReportChatLocation {
+            app: c["app"].as_str().unwrap_or("").to_string(),
+            path: c["path"].as_str().unwrap_or("").to_string(),
+        })
+        .collect();
+
+    let repos = query_repos(conn, project_id)?;
+    let (insertions, deletions) = conn
+        .query_row(
+            "SELECT COALESCE(SUM(insertions), 0), COALESCE(SUM(deletions), 0) FROM git_commits WHERE project_id = ?",
+            [project_id],
+            |row| Ok((row.get(0)?, row.get(1)?)),
+        )
+        .map_err(|e| e.to_string())?;
+    let stats = ReportStats {
+        documents: count(&format!(
+            "SELECT COUNT(*) FROM scan_results WHERE project_id = ? AND {}",
+            curation::included_sql()
+        ))?,
+        git_repos: repos.len(),
+        commits: count("SELECT COUNT(*) FROM git_commits WHERE project_id = ?")?,
+        insertions,
+        deletions,
+        meetings: count("SELECT COUNT(*) FROM meetings WHERE project_id = ?")?,
+        emails: count("SELECT COUNT(*) FROM emails WHERE project_id = ?")?,
+        shell_commands: count("SELECT COUNT(*) FROM shell_history WHERE project_id = ?")?,
+        web_pages: count("SELECT COUNT(*) FROM browser_history WHERE project_id = ?")?,
+        chat_locations: chat_locations.len(),
+    };
+
+    Ok(ReportData {
+        generated_at: chrono::Local::now().format("%Y-%m-%d %H:%M").to_string(),
+        project: ReportProject {
+            id: project_id.to_string(),
+            name,
+            folder_path,
+            time_range_label: scan::range_label(&time_range).to_string(),
+            time_range,
+        },
+        stats,
+        timeline: timeline::build(conn, project_id, "month", None, Some(3))?,
+        document_types: query_document_types(conn, project_id)?,
+        top_documents: query_top_documents(conn, project_id)?,
+        starred_documents: query_starred_documents(conn, project_id)?,
+        collections: query_collections(conn, project_id)?,
+        repos,
+        commit_highlights: query_commit_highlights(conn, project_id)?,
+        chat_locations,
+    })
+}
+
+fn query_document_types(conn: &Connection, project_id: &str) -> Result<Vec<FileTypeCount>, String> {
+    let mut stmt = conn
+        .prepare(&format!(
+            "SELECT file_type, COUNT(*) FROM scan_results WHERE project_id = ? AND {}
+             GROUP BY file_type ORDER BY COUNT(*) DESC",
+            curation::included_sql()
+        ))
+        .map_err(|e| e.to_string())?;
+    let rows = stmt
+        .query_map([project_id], |row| {
+            Ok(FileTypeCount {
+                file_type: row.get(0)?,
+                count: row.get::<_, i64>(1)? as usize,
+            })
+        })
+        .map_err(|e| e.to_string())?;
+    rows.collect::<Result<_, _>>().map_err(|e| e.to_string())
+}
+
+/// 最近修改的文档（排除出报告的文件不计入报告的任何部分）
+fn query_top_documents(conn: &Connection, project_id: &str) -> Result<Vec<ReportDocument>, String> {
+    let mut stmt = conn
+        .prepare(&format!(
+            "SELECT file_path, file_type, modified_at, size_bytes FROM scan_results WHERE project_id = ? AND {}
+             ORDER BY modified_at DESC LIMIT ?",
+            curation::included_sql()
+        ))
+        .map_err(|e| e.to_string())?;
+    let rows = stmt
+        .query_map(rusqlite::params![project_id, TOP_LIMIT as i64], |row| {
+            Ok(ReportDocument {
+                file_path: row.get(0)?,
+                file_type: row.get(1)?,
+                modified_at: row.get(2)?,
+                size_bytes: row.get(3)?,
+            })
+        })
+        .map_err(|e| e.to_string())?;
+    rows.collect::<Result<_, _>>().map_err(|e| e.to_string())
+}
+
+fn query_starred_documents(
+    conn: &Connection,
+    project_id: &str,
+) -> Result<Vec<ReportStarred>, String> {
+    let star = curation::annotation_expr("star");
+    let mut stmt = conn
+        .prepare(&format!(
+            "SELECT file_path, file_type, modified_at, {0} FROM scan_results
+             WHERE project_id = ? AND {1} > 0 AND {2}
+             ORDER BY {1} DESC, modified_at DESC",
+            curation::select_columns(),
+            star,
+            curation::included_sql()
+        ))
+        .map_err(|e| e.to_string())?;
+    let rows = stmt
+        .query_map([project_id], |row| {
+            Ok(ReportStarred {
+                file_path: row.get(0)?,
+                file_type: row.get(1)?,
+                modified_at: row.get(2)?,
+                note: row.get::<_, Option<String>>(3)?.unwrap_or_default(),
+                star: row.get::<_, Option<u8>>(4)?.unwrap_or(0),
+                tags: curation::split_tags(row.get(6)?),
+            })
+        })
+        .map_err(|e| e.to_string())?;
+    rows.collect::<Result<_, _>>().map_err(|e| e.to_string())
+}
+
+fn query_collections(conn: &Connection, project_id: &str) -> Result<Vec<ReportCollection>, String> {
+    collection::pinned(conn, project_id)?
+        .into_iter()
+        .map(|saved| {
+            // 与报告其他部分一致，不列出也不计入已排除的文件
+            let query = ResultQuery {
+                excluded: Some(false),
+                ..saved.query
+            };
+            let page = results::fetch(conn, project_id, &query, Paging::Offset(1), TOP_LIMIT)?;
+            Ok(ReportCollection {
+                name: saved.name,
+                count: page.total,
+                documents: page
+                    .items
+                    .into_iter()
+                    .map(|item| ReportDocument {
+                        file_path: item.file_path,
+                        file_type: item.file_type,
+                        modified_at: item.modified_at,
+                        size_bytes: item.size_bytes,
+                    })
+                    .collect(),
+            })
+        })
+        .collect()
+}
+
+fn query_repos(conn: &Connection, project_id: &str) -> Result<Vec<ReportRepo>, String> {
+    let mut stmt = conn
+        .prepare(
+            "SELECT repo_path, COUNT(*), SUM(insertions), SUM(deletions), MIN(committed_at), MAX(committed_at)
+             FROM git_commits WHERE project_id = ? GROUP BY repo_path ORDER BY COUNT(*) DESC",
+        )
+        .map_err(|e| e.to_string())?;
+    let rows = stmt
+        .query_map([project_id], |row| {
+            Ok(ReportRepo {
+                repo_path: row.get(0)?,
+                commits: row.get::<_, i64>(1)? as usize,
+                insertions: row.get(2)?,
+                deletions: row.get(3)?,
+                first_commit_at: row.get(4)?,
+                last_commit_at: row.get(5)?,
+            })
+        })
+        .map_err(|e| e.to_string())?;
+    rows.collect::<Result<_, _>>().map_err(|e| e.to_string())
+}
+
+/// 改动量最大的提交
+fn query_commit_highlights(
+    conn: &Connection,
+    project_id: &str,
+) -> Result<Vec<ReportCommit>, String> {
+    let mut stmt = conn
+        .prepare(
+            "SELECT repo_path, hash, summary, author_name, committed_at, files_changed, insertions, deletions
+             FROM git_commits WHERE project_id = ?
+             ORDER BY insertions + deletions DESC, committed_at DESC LIMIT ?",
+        )
+        .map_err(|e| e.to_string())?;
+    let rows = stmt
+        .query_map(rusqlite::params![project_id, TOP_LIMIT as i64], |row| {
+            Ok(ReportCommit {
+                repo_path: row.get(0)?,
+                hash: row.get(1)?,
+                summary: row.get(2)?,
+                author_name: row.get(3)?,
+                committed_at: row.get(4)?,
+                files_changed: row.get(5)?,
+                insertions: row.get(6)?,
+                deletions: row.get(7)?,
+            })
+        })
+        .map_err(|e| e.to_string())?;
+    rows.collect::<Result<_, _>>().map_err(|e| e.to_string())
+}
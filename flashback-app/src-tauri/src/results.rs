@@ -0,0 +1,544 @@
+//! scan_results 的高级查询：多条件筛选、任意列排序，并在同一次响应中返回按类型、来源、月份的分面计数
+//! 分面计数不受自身维度的筛选影响（如已选类型时，类型分面仍给出其他类型的数量），便于结果页多选过滤
+//! 分页支持页码（LIMIT/OFFSET）与游标两种方式：游标记录上一页最后一行的排序值与 id，
+//! 下一页按 (排序列, id) 比较定位，配合 (project_id, 排序列, id) 复合索引，深翻页也不需要跳过前面的行
+
+use rusqlite::types::Value;
+use rusqlite::{Connection, ToSql};
+use serde::{Deserialize, Serialize};
+
+#[cfg(feature = "gui")]
+use crate::collectors::{search_emails, EmailItem};
+use crate::{curation, metadata, ProjectListResponseLike, ScanResultItem};
+
+const RESULT_COLUMNS: &str = "id, project_id, file_path, file_type, source, created_at, modified_at, size_bytes, is_valid, inserted_at, updated_at, archived";
+/// RESULT_COLUMNS 与整理字段之后的列序号
+const RESULT_COLUMN_COUNT: usize = 16;
+/// 带关键词查询时附带的邮件条数
+#[cfg(feature = "gui")]
+const EMAIL_HITS: usize = 5;
+
+/// 可排序的列，sort_by 只接受这些值；每列都有 (project_id, 列, id) 复合索引
+pub(crate) const SORTABLE_COLUMNS: [&str; 9] = [
+    "file_path",
+    "file_type",
+    "source",
+    "created_at",
+    "modified_at",
+    "size_bytes",
+    "is_valid",
+    "inserted_at",
+    "updated_at",
+];
+
+pub(crate) fn result_from_row(row: &rusqlite::Row) -> rusqlite::Result<ScanResultItem> {
+    Ok(ScanResultItem {
+        id: row.get(0)?,
+        project_id: row.get(1)?,
+        file_path: row.get(2)?,
+        file_type: row.get(3)?,
+        source: row.get(4)?,
+        created_at: row.get(5)?,
+        modified_at: row.get(6)?,
+        size_bytes: row.get(7)?,
+        is_valid: row.get::<_, i64>(8)? == 1,
+        inserted_at: row.get(9)?,
+        updated_at: row.get(10)?,
+        archived: row.get::<_, i64>(11)? == 1,
+        note: row.get::<_, Option<String>>(12)?.unwrap_or_default(),
+        star: row.get::<_, Option<u8>>(13)?.unwrap_or(0),
+        excluded: row.get::<_, Option<i64>>(14)?.unwrap_or(0) == 1,
+        tags: curation::split_tags(row.get(15)?),
+    })
+}
+
+/// 查询条件；未填的条件不参与过滤。日期为 `%Y-%m-%d` 或 `%Y-%m-%d %H:%M:%S`，只写日期的终点包含当天
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub(crate) struct ResultQuery {
+    /// 路径关键词
+    pub q: Option<String>,
+    pub file_types: Vec<String>,
+    pub sources: Vec<String>,
+    pub min_size: Option<i64>,
+    pub max_size: Option<i64>,
+    pub created_from: Option<String>,
+    pub created_to: Option<String>,
+    pub modified_from: Option<String>,
+    pub modified_to: Option<String>,
+    /// 创建、修改时间的筛选优先使用内嵌日期（照片的拍摄时间、文档属性中的时间），没有时用文件时间
+    pub embedded_dates: bool,
+    pub is_valid: Option<bool>,
+    /// 只看归档 / 未归档的条目
+    pub archived: Option<bool>,
+    /// 只看某个目录下的文件（含子目录）
+    pub root: Option<String>,
+    /// 带任一标签
+    pub tags: Vec<String>,
+    /// 星标等级不低于该值，1 即只看星标文件
+    pub min_star: Option<u8>,
+    /// 只看排除 / 未排除出报告的文件
+    pub excluded: Option<bool>,
+    /// 只看有 / 没有备注的文件
+    pub has_note: Option<bool>,
+    /// 排序列，默认 updated_at
+    pub sort_by: Option<String>,
+    /// asc 或 desc，默认 desc
+    pub sort_order: Option<String>,
+}
+
+/// 分面维度
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Facet {
+    Type,
+    Source,
+    Month,
+}
+
+impl Facet {
+    #[cfg(feature = "gui")]
+    fn expr(self) -> &'static str {
+        match self {
+            Facet::Type => "file_type",
+            Facet::Source => "source",
+            Facet::Month => "substr(modified_at, 1, 7)",
+        }
+    }
+}
+
+#[cfg(feature = "gui")]
+#[derive(Debug, Clone, Serialize)]
+pub(crate) struct FacetCount {
+    pub value: String,
+    pub count: usize,
+}
+
+#[cfg(feature = "gui")]
+#[derive(Debug, Clone, Default, Serialize)]
+pub(crate) struct ResultFacets {
+    pub by_type: Vec<FacetCount>,
+    pub by_source: Vec<FacetCount>,
+    /// 按修改时间的月份（YYYY-MM），从近到远
+    pub by_month: Vec<FacetCount>,
+}
+
+#[cfg(feature = "gui")]
+#[derive(Debug, Clone, Serialize)]
+pub(crate) struct ResultQueryResponse {
+    #[serde(flatten)]
+    pub page: ProjectListResponseLike<ScanResultItem>,
+    /// 游标续页时不重新计算，沿用第一页的分面
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub facets: Option<ResultFacets>,
+    /// 带关键词时同时全文检索邮件（主题、收发件人、正文、附件名），附带最相关的几封与命中总数；续页不返回
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub emails: Option<ProjectListResponseLike<EmailItem>>,
+}
+
+/// 分页方式
+#[derive(Debug, Clone, Copy)]
+pub(crate) enum Paging<'a> {
+    /// 页码，从 1 开始
+    Offset(usize),
+    /// 游标，None 为第一页；之后传上一页返回的 next_cursor
+    Cursor(Option<&'a str>),
+}
+
+impl<'a> Paging<'a> {
+    /// 命令参数：传了 cursor 即为游标方式，空字符串表示从第一页开始
+    pub fn from_args(page: usize, cursor: Option<&'a str>) -> Self {
+        match cursor {
+            Some("") => Paging::Cursor(None),
+            Some(c) => Paging::Cursor(Some(c)),
+            None => Paging::Offset(page),
+        }
+    }
+}
+
+/// 游标内容：上一页最后一行的排序值与 id；总数与页码在第一页统计后随游标传递，续页不再 COUNT
+#[derive(Debug, Serialize, Deserialize)]
+struct Cursor {
+    /// 生成游标时的排序（列与方向），排序变化后游标失效
+    sort: String,
+    value: serde_json::Value,
+    id: String,
+    total: usize,
+    page: usize,
+}
+
+impl Cursor {
+    fn encode(&self) -> String {
+        let json = serde_json::to_string(self).unwrap_or_default();
+        json.bytes().map(|b| format!("{:02x}", b)).collect()
+    }
+
+    fn decode(s: &str) -> Result<Self, String> {
+        let invalid = || "无效的分页游标".to_string();
+        if !s.len().is_multiple_of(2) || !s.is_ascii() {
+            return Err(invalid());
+        }
+        let bytes = (0..s.len())
+            .step_by(2)
+            .map(|i| u8::from_str_radix(&s[i..i + 2], 16))
+            .collect::<Result<Vec<u8>, _>>()
+            .map_err(|_| invalid())?;
+        serde_json::from_slice(&bytes).map_err(|_| invalid())
+    }
+}
+
+fn to_json(value: &Value) -> serde_json::Value {
+    match value {
+        Value::Integer(i) => (*i).into(),
+        Value::Real(f) => (*f).into(),
+        Value::Text(s) => s.clone().into(),
+        _ => serde_json::Value::Null,
+    }
+}
+
+fn from_json(value: &serde_json::Value) -> Value {
+    match value {
+        serde_json::Value::Number(n) => n
+            .as_i64()
+            .map(Value::Integer)
+            .unwrap_or_else(|| Value::Real(n.as_f64().unwrap_or_default())),
+        serde_json::Value::String(s) => Value::Text(s.clone()),
+        _ => Value::Null,
+    }
+}
+
+impl ResultQuery {
+    /// 只按关键词与类型过滤（get_results_paginated_adv、导出沿用的条件）
+    pub fn simple(q: Option<&str>, file_types: Option<&[String]>) -> Self {
+        ResultQuery {
+            q: q.map(str::to_string),
+            file_types: file_types.map(<[String]>::to_vec).unwrap_or_default(),
+            ..Default::default()
+        }
+    }
+
+    /// WHERE 子句与参数；skip 为计算分面时忽略的维度
+    pub fn filter(&self, project_id: &str, skip: Option<Facet>) -> (String, Vec<Box<dyn ToSql>>) {
+        let mut where_sql = String::from("project_id = ?");
+        let mut params: Vec<Box<dyn ToSql>> = vec![Box::new(project_id.to_string())];
+
+        if let Some(query) = self.q.as_deref().filter(|s| !s.trim().is_empty()) {
+            where_sql.push_str(" AND file_path LIKE ? ESCAPE '\\'");
+            let like = format!("%{}%", query.replace('%', "\\%").replace('_', "\\_"));
+            params.push(Box::new(like));
+        }
+        if skip != Some(Facet::Type) {
+            push_in(&mut where_sql, &mut params, "file_type", &self.file_types);
+        }
+        if skip != Some(Facet::Source) {
+            push_in(&mut where_sql, &mut params, "source", &self.sources);
+        }
+        if let Some(min) = self.min_size {
+            where_sql.push_str(" AND size_bytes >= ?");
+            params.push(Box::new(min));
+        }
+        if let Some(max) = self.max_size {
+            where_sql.push_str(" AND size_bytes <= ?");
+            params.push(Box::new(max));
+        }
+        let time_expr = |column: &str| {
+            if self.embedded_dates {
+                metadata::embedded_time_expr(column)
+            } else {
+                column.to_string()
+            }
+        };
+        push_range(
+            &mut where_sql,
+            &mut params,
+            &time_expr("created_at"),
+            self.created_from.as_deref(),
+            self.created_to.as_deref(),
+        );
+        if skip != Some(Facet::Month) {
+            push_range(
+                &mut where_sql,
+                &mut params,
+                &time_expr("modified_at"),
+                self.modified_from.as_deref(),
+                self.modified_to.as_deref(),
+            );
+        }
+        if let Some(valid) = self.is_valid {
+            where_sql.push_str(" AND is_valid = ?");
+            params.push(Box::new(valid as i64));
+        }
+        if let Some(archived) = self.archived {
+            where_sql.push_str(" AND archived = ?");
+            params.push(Box::new(archived as i64));
+        }
+        if let Some(root) = self.root.as_deref().filter(|s| !s.is_empty()) {
+            let root = root.trim_end_matches(['/', '\\']);
+            let prefix = format!("{}{}", root, std::path::MAIN_SEPARATOR);
+            where_sql.push_str(" AND (file_path = ? OR substr(file_path, 1, length(?)) = ?)");
+            params.push(Box::new(root.to_string()));
+            params.push(Box::new(prefix.clone()));
+            params.push(Box::new(prefix));
+        }
+        if !self.tags.is_empty() {
+            where_sql.push_str(&format!(
+                " AND {}",
+                curation::has_any_tag_sql(self.tags.len())
+            ));
+            for t in &self.tags {
+                params.push(Box::new(t.clone()));
+            }
+        }
+        if let Some(min) = self.min_star.filter(|m| *m > 0) {
+            where_sql.push_str(&format!(" AND {} >= ?", curation::annotation_expr("star")));
+            params.push(Box::new(min));
+        }
+        if let Some(excluded) = self.excluded {
+            where_sql.push_str(&format!(
+                " AND {} = ?",
+                curation::annotation_expr("excluded")
+            ));
+            params.push(Box::new(excluded as i64));
+        }
+        if let Some(has_note) = self.has_note {
+            let op = if has_note { "!=" } else { "=" };
+            where_sql.push_str(&format!(
+                " AND {} {} ''",
+                curation::annotation_expr("note"),
+                op
+            ));
+        }
+        (where_sql, params)
+    }
+
+    /// 排序列与是否降序；未知的列返回错误
+    pub fn sort(&self) -> Result<(&'static str, bool), String> {
+        let name = self.sort_by.as_deref().unwrap_or("updated_at");
+        let column = SORTABLE_COLUMNS
+            .iter()
+            .find(|c| **c == name)
+            .ok_or_else(|| format!("不支持的排序列: {}", name))?;
+        let desc = match self.sort_order.as_deref().unwrap_or("desc") {
+            o if o.eq_ignore_ascii_case("asc") => false,
+            o if o.eq_ignore_ascii_case("desc") => true,
+            o => return Err(format!("排序方向应为 asc 或 desc: {}", o)),
+        };
+        Ok((column, desc))
+    }
+}
+
+fn push_in(
+    where_sql: &mut String,
+    params: &mut Vec<Box<dyn ToSql>>,
+    column: &str,
+    values: &[String],
+) {
+    if values.is_empty() {
+        return;
+    }
+    let placeholders = vec!["?"; values.len()].join(",");
+    where_sql.push_str(&format!(" AND {} IN ({})", column, placeholders));
+    for v in values {
+        params.push(Box::new(v.clone()));
+    }
+}
+
+fn push_range(
+    where_sql: &mut String,
+    params: &mut Vec<Box<dyn ToSql>>,
+    column: &str,
+    from: Option<&str>,
+    to: Option<&str>,
+) {
+    if let Some(from) = from.filter(|s| !s.is_empty()) {
+        where_sql.push_str(&format!(" AND {} >= ?", column));
+        params.push(Box::new(from.to_string()));
+    }
+    if let Some(to) = to.filter(|s| !s.is_empty()) {
+        // 只写日期时包含当天
+        let to = if to.len() == 10 {
+            format!("{} 23:59:59", to)
+        } else {
+            to.to_string()
+        };
+        where_sql.push_str(&format!(" AND {} <= ?", column));
+        params.push(Box::new(to));
+    }
+}
+
+/// 按条件分页查询，并给出三个维度的分面计数
+#[cfg(feature = "gui")]
+pub(crate) fn query(
+    conn: &Connection,
+    project_id: &str,
+    query: &ResultQuery,
+    paging: Paging,
+    page_size: usize,
+) -> Result<ResultQueryResponse, String> {
+    let page = fetch(conn, project_id, query, paging, page_size)?;
+    let facets = match paging {
+        Paging::Cursor(Some(_)) => None,
+        _ => Some(ResultFacets {
+            by_type: facet_counts(conn, project_id, query, Facet::Type)?,
+            by_source: facet_counts(conn, project_id, query, Facet::Source)?,
+            by_month: facet_counts(conn, project_id, query, Facet::Month)?,
+        }),
+    };
+    let keyword = query.q.as_deref().map(str::trim).filter(|s| !s.is_empty());
+    let emails = match (paging, keyword) {
+        (Paging::Cursor(Some(_)), _) | (_, None) => None,
+        (_, Some(k)) => Some(search_emails(conn, project_id, Some(k), 1, EMAIL_HITS)?),
+    };
+    Ok(ResultQueryResponse {
+        page,
+        facets,
+        emails,
+    })
+}
+
+/// 按条件取一页结果（各结果查询命令与命令行共用）
+/// 页码方式每页统计总数；游标方式只在第一页统计，并且总会返回 next_cursor（最后一页为 None）
+pub(crate) fn fetch(
+    conn: &Connection,
+    project_id: &str,
+    query: &ResultQuery,
+    paging: Paging,
+    page_size: usize,
+) -> Result<ProjectListResponseLike<ScanResultItem>, String> {
+    let page_size = page_size.max(1);
+    let (column, desc) = query.sort()?;
+    let direction = if desc { "DESC" } else { "ASC" };
+    let sort_key = format!("{} {}", column, direction);
+    let (mut where_sql, mut params) = query.filter(project_id, None);
+
+    let cursor = match paging {
+        Paging::Cursor(Some(c)) => {
+            let cursor = Cursor::decode(c)?;
+            if cursor.sort != sort_key {
+                return Err("分页游标与当前排序不一致，请从第一页重新查询".into());
+            }
+            Some(cursor)
+        }
+        _ => None,
+    };
+    let (total, page) = match (&cursor, paging) {
+        (Some(c), _) => (c.total, c.page + 1),
+        (None, Paging::Offset(page)) => (count(conn, &where_sql, &params)?, page.max(1)),
+        (None, Paging::Cursor(_)) => (count(conn, &where_sql, &params)?, 1),
+    };
+    if let Some(c) = &cursor {
+        where_sql.push_str(&format!(
+            " AND ({}, id) {} (?, ?)",
+            column,
+            if desc { "<" } else { ">" }
+        ));
+        params.push(Box::new(from_json(&c.value)));
+        params.push(Box::new(c.id.clone()));
+    }
+
+    // 游标方式多取一行，用来判断是否还有下一页
+    let (limit, offset) = match paging {
+        Paging::Offset(_) => (page_size as i64, ((page - 1) * page_size) as i64),
+        Paging::Cursor(_) => (page_size as i64 + 1, 0),
+    };
+    let mut stmt = conn
+        .prepare(&format!(
+            "SELECT {0}, {1}, {2} FROM scan_results WHERE {3} ORDER BY {2} {4}, id {4} LIMIT ? OFFSET ?",
+            RESULT_COLUMNS,
+            curation::select_columns(),
+            column,
+            where_sql,
+            direction
+        ))
+        .map_err(|e| e.to_string())?;
+    let mut run_params: Vec<&dyn ToSql> = params.iter().map(|b| &**b).collect();
+    run_params.push(&limit);
+    run_params.push(&offset);
+    let mut rows = stmt
+        .query_map(run_params.as_slice(), |row| {
+            Ok((
+                result_from_row(row)?,
+                row.get::<_, Value>(RESULT_COLUMN_COUNT)?,
+            ))
+        })
+        .map_err(|e| e.to_string())?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string())?;
+
+    let next_cursor = match paging {
+        Paging::Cursor(_) if rows.len() > page_size => {
+            rows.truncate(page_size);
+            rows.last().map(|(item, value)| {
+                Cursor {
+                    sort: sort_key.clone(),
+                    value: to_json(value),
+                    id: item.id.clone(),
+                    total,
+                    page,
+                }
+                .encode()
+            })
+        }
+        _ => None,
+    };
+    Ok(ProjectListResponseLike {
+        items: rows.into_iter().map(|(item, _)| item).collect(),
+        total,
+        page,
+        total_pages: total.div_ceil(page_size).max(1),
+        next_cursor,
+    })
+}
+
+/// 符合条件的结果数
+pub(crate) fn total(
+    conn: &Connection,
+    project_id: &str,
+    query: &ResultQuery,
+) -> Result<usize, String> {
+    let (where_sql, params) = query.filter(project_id, None);
+    count(conn, &where_sql, &params)
+}
+
+fn count(conn: &Connection, where_sql: &str, params: &[Box<dyn ToSql>]) -> Result<usize, String> {
+    conn.query_row(
+        &format!("SELECT COUNT(*) FROM scan_results WHERE {}", where_sql),
+        rusqlite::params_from_iter(params.iter().map(|b| &**b)),
+        |row| row.get(0),
+    )
+    .map_err(|e| e.to_string())
+}
+
+#[cfg(feature = "gui")]
+fn facet_counts(
+    conn: &Connection,
+    project_id: &str,
+    query: &ResultQuery,
+    facet: Facet,
+) -> Result<Vec<FacetCount>, String> {
+    let (where_sql, params) = query.filter(project_id, Some(facet));
+    // 月份按时间倒序，其余按数量倒序
+    let order = match facet {
+        Facet::Month => "value DESC",
+        _ => "count DESC, value",
+    };
+    let mut stmt = conn
+        .prepare(&format!(
+            "SELECT {0} AS value, COUNT(*) AS count FROM scan_results WHERE {1} GROUP BY {0} ORDER BY {2}",
+            facet.expr(),
+            where_sql,
+            order
+        ))
+        .map_err(|e| e.to_string())?;
+    let rows = stmt
+        .query_map(
+            rusqlite::params_from_iter(params.iter().map(|b| &**b)),
+            |row| {
+                Ok(FacetCount {
+                    value: row.get(0)?,
+                    count: row.get::<_, i64>(1)? as usize,
+                })
+            },
+        )
+        .map_err(|e| e.to_string())?;
+    rows.collect::<Result<_, _>>().map_err(|e| e.to_string())
+}
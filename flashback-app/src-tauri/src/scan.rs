@@ -0,0 +1,958 @@
+//! 扫描引擎
+//! start_scan / start_scan_by_id 共用同一套流程：依次执行项目启用的采集器（见 collectors），
+//! 结果统一写入各采集器的表，摘要写回 projects.scan_summary，保证两条入口的结果与摘要一致
+
+use rusqlite::Connection;
+use serde::de::DeserializeOwned;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::fs::{File, OpenOptions, TryLockError};
+use std::path::{Path, PathBuf};
+#[cfg(feature = "gui")]
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+#[cfg(feature = "gui")]
+use tauri::{Emitter, Manager};
+use walkdir::DirEntry;
+
+use crate::collectors::{CollectorRegistry, DocumentSettings, TimelineSpec};
+use crate::metadata;
+use crate::progress::{ProgressSink, ScanEvent};
+use crate::walk::{PruneRule, Walk, WalkItem, WalkOptions};
+use crate::{DatabaseManager, IssueSample, ScanIssues, ScanSummary, SourceKind};
+
+// ==================== 扫描上下文 ====================
+
+/// 一次扫描所需的项目信息（从 projects 表加载）
+pub(crate) struct ScanContext {
+    pub project_id: String,
+    pub project_name: String,
+    pub project_folder: String,
+    pub time_range: String,
+    pub scan_scope: Option<String>,
+    pub roots: Vec<PathBuf>,
+    /// 项目启用的采集器 id；None 表示使用默认配置
+    pub collectors: Option<Vec<String>>,
+    /// 各采集器的项目级配置（collector_settings 表，key 为采集器 id）
+    pub settings: HashMap<String, serde_json::Value>,
+    /// 目录遍历选项（projects.walk_options）
+    pub walk_options: WalkOptions,
+    pub window_mode: WindowMode,
+    /// frozen 模式下固定的起止时间（`%Y-%m-%d %H:%M:%S`）
+    pub window_start: Option<String>,
+    pub window_end: Option<String>,
+    /// 加载项目时所用的数据库，扫描结果写回这里
+    pub db_path: PathBuf,
+}
+
+impl ScanContext {
+    pub fn load(conn: &Connection, project_id: &str) -> Result<Self, String> {
+        let mut stmt = conn
+            .prepare("SELECT id, name, folder_path, time_range, scan_scope, scan_folders, collectors, walk_options, window_mode, window_start, window_end FROM projects WHERE id = ?")
+            .map_err(|e| e.to_string())?;
+        let mut rows = stmt.query([project_id]).map_err(|e| e.to_string())?;
+        let row = match rows.next().map_err(|e| e.to_string())? {
+            Some(row) => row,
+            None => return Err("项目不存在".into()),
+        };
+        let project_folder: String = row.get(2).map_err(|e| e.to_string())?;
+        let scan_scope: Option<String> = row.get(4).ok();
+        let scan_folders_json: Option<String> = row.get(5).ok();
+        let collectors = row
+            .get::<_, Option<String>>(6)
+            .ok()
+            .flatten()
+            .and_then(|json| serde_json::from_str::<Vec<String>>(&json).ok());
+        let walk_options = row
+            .get::<_, Option<String>>(7)
+            .ok()
+            .flatten()
+            .and_then(|json| serde_json::from_str::<WalkOptions>(&json).ok())
+            .unwrap_or_default();
+        let roots = resolve_scan_roots(
+            &project_folder,
+            scan_scope.as_deref(),
+            scan_folders_json.as_deref(),
+        );
+        Ok(ScanContext {
+            project_id: row.get(0).map_err(|e| e.to_string())?,
+            project_name: row.get(1).map_err(|e| e.to_string())?,
+            project_folder,
+            time_range: row.get(3).map_err(|e| e.to_string())?,
+            scan_scope,
+            roots,
+            collectors,
+            settings: load_collector_settings(conn, project_id)?,
+            walk_options,
+            window_mode: WindowMode::parse(
+                row.get::<_, Option<String>>(8).ok().flatten().as_deref(),
+            ),
+            window_start: row.get(9).ok().flatten(),
+            window_end: row.get(10).ok().flatten(),
+            db_path: DatabaseManager::path_of(conn),
+        })
+    }
+
+    /// 打开项目所在的数据库
+    pub fn open_db(&self) -> Option<Connection> {
+        Connection::open(&self.db_path).ok()
+    }
+
+    pub fn is_custom(&self) -> bool {
+        self.scan_scope.as_deref() == Some("CUSTOM")
+    }
+
+    /// 时间范围的起点：frozen 模式为固定的起点，其他模式从当前时间往前推；None 表示不过滤
+    pub fn since(&self) -> Option<chrono::DateTime<chrono::Local>> {
+        if self.window_mode == WindowMode::Frozen {
+            return self.window_start.as_deref().and_then(parse_local_time);
+        }
+        range_days(&self.time_range).map(|days| chrono::Local::now() - chrono::Duration::days(days))
+    }
+
+    /// 时间范围的终点，只有 frozen 模式才有
+    pub fn until(&self) -> Option<chrono::DateTime<chrono::Local>> {
+        match self.window_mode {
+            WindowMode::Frozen => self.window_end.as_deref().and_then(parse_local_time),
+            _ => None,
+        }
+    }
+
+    /// 时间是否在时间范围内
+    pub fn contains(&self, t: chrono::DateTime<chrono::Local>) -> bool {
+        self.since().is_none_or(|since| t >= since) && self.until().is_none_or(|until| t <= until)
+    }
+
+    /// 读取某个采集器的配置，未配置或格式不符时使用默认值
+    pub fn collector_settings<T: DeserializeOwned + Default>(&self, collector_id: &str) -> T {
+        self.settings
+            .get(collector_id)
+            .and_then(|v| serde_json::from_value(v.clone()).ok())
+            .unwrap_or_default()
+    }
+}
+
+fn load_collector_settings(
+    conn: &Connection,
+    project_id: &str,
+) -> Result<HashMap<String, serde_json::Value>, String> {
+    let mut stmt = conn
+        .prepare("SELECT collector_id, settings FROM collector_settings WHERE project_id = ?")
+        .map_err(|e| e.to_string())?;
+    let rows = stmt
+        .query_map([project_id], |row| {
+            Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?))
+        })
+        .map_err(|e| e.to_string())?;
+    let mut settings = HashMap::new();
+    for (collector_id, json) in rows.filter_map(Result::ok) {
+        if let Ok(v) = serde_json::from_str(&json) {
+            settings.insert(collector_id, v);
+        }
+    }
+    Ok(settings)
+}
+
+// ==================== 时间窗口 ====================
+
+/// 项目时间范围的计算方式（projects.window_mode）
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum WindowMode {
+    /// 每次扫描从当前时间往前推，移出范围的旧数据随重扫清除（默认，与早期版本一致）
+    Rolling,
+    /// 创建项目时把范围固定为绝对起止时间（projects.window_start / window_end），重扫不再变化
+    Frozen,
+    /// 与 Rolling 一样滚动，但移出范围的条目保留并标记为 archived
+    Retain,
+}
+
+impl WindowMode {
+    /// 未设置或无法识别时按 Rolling 处理
+    pub fn parse(s: Option<&str>) -> Self {
+        match s {
+            Some("frozen") => WindowMode::Frozen,
+            Some("retain") => WindowMode::Retain,
+            _ => WindowMode::Rolling,
+        }
+    }
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            WindowMode::Rolling => "rolling",
+            WindowMode::Frozen => "frozen",
+            WindowMode::Retain => "retain",
+        }
+    }
+}
+
+fn range_days(time_range: &str) -> Option<i64> {
+    match time_range {
+        "past_year" => Some(365),
+        "past_month" => Some(30),
+        "past_week" => Some(7),
+        _ => None,
+    }
+}
+
+/// 以 anchor 为终点计算固定的起止时间；不限范围时起点为空
+pub(crate) fn frozen_bounds(
+    time_range: &str,
+    anchor: chrono::DateTime<chrono::Local>,
+) -> (Option<String>, String) {
+    let start = range_days(time_range).map(|days| {
+        (anchor - chrono::Duration::days(days))
+            .format("%Y-%m-%d %H:%M:%S")
+            .to_string()
+    });
+    (start, anchor.format("%Y-%m-%d %H:%M:%S").to_string())
+}
+
+pub(crate) fn parse_local_time(s: &str) -> Option<chrono::DateTime<chrono::Local>> {
+    chrono::NaiveDateTime::parse_from_str(s, "%Y-%m-%d %H:%M:%S")
+        .ok()
+        .and_then(|t| t.and_local_timezone(chrono::Local).single())
+}
+
+/// 判断条目是否在时间范围内所用的时间：文档按项目设置可优先使用内嵌日期
+fn window_time_expr(ctx: &ScanContext, spec: &TimelineSpec) -> String {
+    if spec.table == "scan_results" && DocumentSettings::of(ctx).embedded_dates {
+        metadata::embedded_time_expr(spec.time_column)
+    } else {
+        spec.time_column.to_string()
+    }
+}
+
+/// 采集前处理上次留下的数据：retain 模式把已移出范围的条目标记为归档（采集器只清空未归档的条目），
+/// 其他模式不保留归档，清除此前留下的归档条目；返回受影响的行数
+fn archive_outside_window(
+    conn: &Connection,
+    ctx: &ScanContext,
+    spec: &TimelineSpec,
+) -> rusqlite::Result<usize> {
+    match (ctx.window_mode, ctx.since()) {
+        (WindowMode::Retain, Some(since)) => conn.execute(
+            &format!(
+                "UPDATE {} SET archived = 1 WHERE project_id = ? AND archived = 0 AND {} < ?",
+                spec.table,
+                window_time_expr(ctx, spec)
+            ),
+            [
+                &ctx.project_id,
+                &since.format("%Y-%m-%d %H:%M:%S").to_string(),
+            ],
+        ),
+        (WindowMode::Retain, None) => Ok(0),
+        _ => conn.execute(
+            &format!(
+                "DELETE FROM {} WHERE project_id = ? AND archived = 1",
+                spec.table
+            ),
+            [&ctx.project_id],
+        ),
+    }
+}
+
+/// frozen 模式下采集器只按起点过滤，采集后移除晚于终点的条目，返回移除条数
+fn trim_after_window(
+    conn: &Connection,
+    ctx: &ScanContext,
+    spec: &TimelineSpec,
+) -> rusqlite::Result<usize> {
+    match ctx.until() {
+        Some(until) => conn.execute(
+            &format!(
+                "DELETE FROM {} WHERE project_id = ? AND archived = 0 AND {} > ?",
+                spec.table,
+                window_time_expr(ctx, spec)
+            ),
+            [
+                &ctx.project_id,
+                &until.format("%Y-%m-%d %H:%M:%S").to_string(),
+            ],
+        ),
+        None => Ok(0),
+    }
+}
+
+/// 根据扫描范围决定扫描根目录：CUSTOM 使用自定义目录（为空时退回项目目录），ALL 使用常见目录 + 项目目录
+pub(crate) fn resolve_scan_roots(
+    project_folder: &str,
+    scan_scope: Option<&str>,
+    scan_folders_json: Option<&str>,
+) -> Vec<PathBuf> {
+    let mut roots: Vec<PathBuf> = vec![];
+    if scan_scope == Some("CUSTOM") {
+        if let Some(json) = scan_folders_json {
+            if let Ok(vec) = serde_json::from_str::<Vec<String>>(json) {
+                roots.extend(vec.into_iter().map(PathBuf::from));
+            }
+        }
+        if roots.is_empty() {
+            roots.push(PathBuf::from(project_folder));
+        }
+    } else {
+        roots = candidate_roots();
+        roots.push(PathBuf::from(project_folder));
+    }
+    roots
+}
+
+// ==================== 扫描问题 ====================
+
+/// 每种原因最多保留的样例数，超出后只计数、不再推送警告
+const MAX_ISSUE_SAMPLES: usize = 5;
+
+/// 条目被跳过或处理失败的原因
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum IssueReason {
+    PermissionDenied,
+    BrokenSymlink,
+    SymlinkLoop,
+    NotFound,
+    IoError,
+    DbError,
+    ParseError,
+}
+
+impl IssueReason {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            IssueReason::PermissionDenied => "permission_denied",
+            IssueReason::BrokenSymlink => "broken_symlink",
+            IssueReason::SymlinkLoop => "symlink_loop",
+            IssueReason::NotFound => "not_found",
+            IssueReason::IoError => "io_error",
+            IssueReason::DbError => "db_error",
+            IssueReason::ParseError => "parse_error",
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            IssueReason::PermissionDenied => "权限不足",
+            IssueReason::BrokenSymlink => "符号链接失效",
+            IssueReason::SymlinkLoop => "符号链接循环",
+            IssueReason::NotFound => "文件不存在",
+            IssueReason::IoError => "读取失败",
+            IssueReason::DbError => "数据库错误",
+            IssueReason::ParseError => "解析失败",
+        }
+    }
+
+    /// 无法访问的条目计为跳过；已读取但处理失败的计为失败
+    pub fn is_failure(self) -> bool {
+        matches!(self, IssueReason::DbError | IssueReason::ParseError)
+    }
+
+    fn from_io_kind(kind: std::io::ErrorKind) -> Self {
+        match kind {
+            std::io::ErrorKind::PermissionDenied => IssueReason::PermissionDenied,
+            std::io::ErrorKind::NotFound => IssueReason::NotFound,
+            _ => IssueReason::IoError,
+        }
+    }
+}
+
+/// 单个条目（文件、目录、仓库、数据库……）的处理错误
+#[derive(Debug)]
+pub(crate) struct EntryError {
+    pub reason: IssueReason,
+    pub message: String,
+}
+
+impl EntryError {
+    pub fn new(reason: IssueReason, message: impl ToString) -> Self {
+        EntryError {
+            reason,
+            message: message.to_string(),
+        }
+    }
+
+    /// 按 io 错误归类；path 本身是符号链接而目标不存在时归为失效链接
+    pub fn io(err: &std::io::Error, path: &Path) -> Self {
+        let reason = match IssueReason::from_io_kind(err.kind()) {
+            IssueReason::NotFound if path.is_symlink() => IssueReason::BrokenSymlink,
+            r => r,
+        };
+        EntryError::new(reason, err)
+    }
+}
+
+impl std::fmt::Display for EntryError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}: {}", self.reason.label(), self.message)
+    }
+}
+
+impl From<std::io::Error> for EntryError {
+    fn from(e: std::io::Error) -> Self {
+        EntryError::new(IssueReason::from_io_kind(e.kind()), e)
+    }
+}
+
+impl From<rusqlite::Error> for EntryError {
+    fn from(e: rusqlite::Error) -> Self {
+        EntryError::new(IssueReason::DbError, e)
+    }
+}
+
+impl From<git2::Error> for EntryError {
+    fn from(e: git2::Error) -> Self {
+        let reason = match e.class() {
+            git2::ErrorClass::Os => IssueReason::IoError,
+            _ => IssueReason::ParseError,
+        };
+        EntryError::new(reason, e.message())
+    }
+}
+
+impl From<walkdir::Error> for EntryError {
+    fn from(e: walkdir::Error) -> Self {
+        if e.loop_ancestor().is_some() {
+            return EntryError::new(IssueReason::SymlinkLoop, e);
+        }
+        match (e.io_error(), e.path()) {
+            (Some(io), Some(path)) => EntryError::io(io, path),
+            (Some(io), None) => EntryError::new(IssueReason::from_io_kind(io.kind()), io),
+            _ => EntryError::new(IssueReason::IoError, e),
+        }
+    }
+}
+
+// ==================== 扫描锁 ====================
+
+/// 项目的扫描锁：同一项目同一时间只允许一次扫描（手动、定时重扫或命令行）或一批监听写入
+/// 使用 FlashBack 目录下 locks/<项目 id>.lock 的文件锁，桌面端与命令行之间也生效；进程退出时由系统释放
+pub(crate) struct ScanLock {
+    _file: File,
+}
+
+impl ScanLock {
+    /// 取得项目的扫描锁，已被占用时返回错误；锁随返回值 drop 释放
+    pub fn acquire(project_id: &str) -> Result<Self, String> {
+        let path = Self::path(project_id);
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir).map_err(|e| format!("创建锁目录失败: {}", e))?;
+        }
+        let file = OpenOptions::new()
+            .create(true)
+            .truncate(false)
+            .write(true)
+            .open(&path)
+            .map_err(|e| format!("打开扫描锁失败: {}", e))?;
+        match file.try_lock() {
+            Ok(()) => Ok(ScanLock { _file: file }),
+            Err(TryLockError::WouldBlock) => Err("项目正在扫描中，请稍后再试".into()),
+            Err(TryLockError::Error(e)) => Err(format!("获取扫描锁失败: {}", e)),
+        }
+    }
+
+    pub fn path(project_id: &str) -> PathBuf {
+        DatabaseManager::get_db_path()
+            .parent()
+            .map(Path::to_path_buf)
+            .unwrap_or_else(|| PathBuf::from("."))
+            .join("locks")
+            .join(format!("{}.lock", project_id))
+    }
+}
+
+// ==================== 扫描运行时 ====================
+
+/// 进度事件的最小推送间隔，避免百万级文件遍历时刷爆 IPC 通道
+const PROGRESS_INTERVAL: Duration = Duration::from_millis(200);
+
+/// 单次扫描的运行时：把采集器的发现、警告与进度转为 ScanEvent 交给 ProgressSink，各采集器共用
+/// 整体进度按各采集器预估的工作量加权，并据此计算吞吐量与剩余时间
+pub(crate) struct ScanRun<'a> {
+    sink: &'a dyn ProgressSink,
+    /// 当前采集器 id
+    stage: &'static str,
+    /// 各采集器的预估工作量（estimate 的结果，至少为 1）
+    weights: Vec<f64>,
+    /// 当前采集器在 weights 中的下标
+    collector_index: usize,
+    /// 根目录的文件预计数缓存，文档与 Git 采集器共用
+    file_counts: HashMap<PathBuf, usize>,
+    /// 开始采集的时间（预计数之后），用于计算吞吐量
+    started: Instant,
+    last_emit: Option<Instant>,
+    /// 本次扫描跳过与失败的条目
+    issues: ScanIssues,
+    /// 已记录过问题的路径，多个采集器遍历同一目录时不重复计数
+    issue_paths: HashSet<PathBuf>,
+    walk_options: WalkOptions,
+    /// 当前采集器因遍历选项剪掉的目录数，采集器结束时汇总输出
+    pruned: BTreeMap<PruneRule, usize>,
+}
+
+impl<'a> ScanRun<'a> {
+    /// 发现数据文件或目录；items 为从中读取到的条目数
+    pub fn file_found(&self, path: &Path, items: Option<usize>) {
+        self.sink.report(ScanEvent::FileFound {
+            stage: self.stage.to_string(),
+            path: path.to_string_lossy().to_string(),
+            items,
+        });
+    }
+
+    pub fn warning(&self, path: Option<&Path>, message: impl Into<String>) {
+        self.sink.report(ScanEvent::Warning {
+            stage: self.stage.to_string(),
+            path: path.map(|p| p.to_string_lossy().to_string()),
+            reason: None,
+            message: message.into(),
+        });
+    }
+
+    /// 记录无法访问（跳过）或处理失败的条目；每种原因的前几条作为样例保留并推送警告
+    pub fn issue(&mut self, path: Option<&Path>, err: EntryError) {
+        if let Some(p) = path {
+            if !self.issue_paths.insert(p.to_path_buf()) {
+                return;
+            }
+        }
+        if err.reason.is_failure() {
+            self.issues.failed += 1;
+        } else {
+            self.issues.skipped += 1;
+        }
+        let count = self
+            .issues
+            .by_reason
+            .entry(err.reason.as_str().to_string())
+            .or_insert(0);
+        *count += 1;
+        if *count > MAX_ISSUE_SAMPLES {
+            return;
+        }
+        let path = path.map(|p| p.to_string_lossy().to_string());
+        self.issues.samples.push(IssueSample {
+            stage: self.stage.to_string(),
+            reason: err.reason.as_str().to_string(),
+            path: path.clone(),
+            message: err.message.clone(),
+        });
+        self.sink.report(ScanEvent::Warning {
+            stage: self.stage.to_string(),
+            path,
+            reason: Some(err.reason.as_str()),
+            message: err.to_string(),
+        });
+    }
+
+    /// 按项目的遍历选项遍历根目录
+    pub fn walk(&self, root: &Path) -> Walk {
+        Walk::new(root, &self.walk_options)
+    }
+
+    /// 记录因遍历选项剪掉的目录；每种规则的前几条推送日志，其余只计数
+    pub fn pruned(&mut self, rule: PruneRule, path: &Path) {
+        let count = self.pruned.entry(rule).or_insert(0);
+        *count += 1;
+        if *count <= MAX_ISSUE_SAMPLES {
+            self.sink.report(ScanEvent::Pruned {
+                stage: self.stage.to_string(),
+                rule: rule.as_str(),
+                label: rule.label(),
+                path: Some(path.to_string_lossy().to_string()),
+                count: 1,
+            });
+        }
+    }
+
+    /// 采集器结束时按规则汇总本采集器剪掉的目录数
+    fn report_pruned(&mut self) {
+        for (rule, count) in std::mem::take(&mut self.pruned) {
+            self.sink.report(ScanEvent::Pruned {
+                stage: self.stage.to_string(),
+                rule: rule.as_str(),
+                label: rule.label(),
+                path: None,
+                count,
+            });
+        }
+    }
+
+    /// 根目录下的文件数（按遍历选项，跳过忽略目录），只读取目录项、不读取文件元数据；同一目录只统计一次
+    pub fn count_files(&mut self, root: &Path) -> usize {
+        if let Some(n) = self.file_counts.get(root) {
+            return *n;
+        }
+        let n = self
+            .walk(root)
+            .filter(|item| matches!(item, WalkItem::Entry(e) if !e.file_type().is_dir()))
+            .count();
+        self.file_counts.insert(root.to_path_buf(), n);
+        n
+    }
+
+    /// 当前采集器内的进度（done / total）；按间隔节流推送，本采集器完成时总会推送
+    pub fn progress(&mut self, done: usize, total: usize) {
+        let fraction = if total == 0 {
+            1.0
+        } else {
+            (done as f64 / total as f64).clamp(0.0, 1.0)
+        };
+        let now = Instant::now();
+        let due = self
+            .last_emit
+            .is_none_or(|t| now.duration_since(t) >= PROGRESS_INTERVAL);
+        if fraction < 1.0 && !due {
+            return;
+        }
+        self.last_emit = Some(now);
+
+        let total_units: f64 = self.weights.iter().sum();
+        let done_units = self.weights[..self.collector_index].iter().sum::<f64>()
+            + self
+                .weights
+                .get(self.collector_index)
+                .copied()
+                .unwrap_or(0.0)
+                * fraction;
+        self.sink.report(progress_event(
+            done_units,
+            total_units,
+            now.duration_since(self.started),
+        ));
+    }
+}
+
+/// 根据已完成与预估的工作量生成进度事件；100% 只在扫描全部结束后推送
+fn progress_event(done_units: f64, total_units: f64, elapsed: Duration) -> ScanEvent {
+    let ratio = if total_units > 0.0 {
+        (done_units / total_units).clamp(0.0, 1.0)
+    } else {
+        0.0
+    };
+    let secs = elapsed.as_secs_f64();
+    let per_second = if secs > 0.0 { done_units / secs } else { 0.0 };
+    // 刚开始时样本太少，不给出剩余时间
+    let eta_secs = (secs >= 1.0 && per_second > 0.0)
+        .then(|| ((total_units - done_units).max(0.0) / per_second).round() as u64);
+    ScanEvent::Progress {
+        progress: ((ratio * 99.0) as u8).min(99),
+        done: done_units.round() as u64,
+        total: total_units.round() as u64,
+        per_second: (per_second * 10.0).round() / 10.0,
+        eta_secs,
+    }
+}
+
+#[cfg(feature = "gui")]
+pub(crate) fn open_db() -> Option<Connection> {
+    Connection::open(DatabaseManager::get_db_path()).ok()
+}
+
+pub(crate) fn format_time(t: std::time::SystemTime) -> String {
+    chrono::DateTime::<chrono::Local>::from(t)
+        .format("%Y-%m-%d %H:%M:%S")
+        .to_string()
+}
+
+// ==================== 引擎入口 ====================
+
+/// 依次执行项目启用的采集器（先全部发现，再逐个采集），写回扫描摘要，返回本次摘要
+pub(crate) fn run_scan(
+    sink: &dyn ProgressSink,
+    ctx: &ScanContext,
+    registry: &CollectorRegistry,
+) -> ScanSummary {
+    let collectors = registry.enabled_for(ctx.collectors.as_deref());
+    let started_at = chrono::Local::now();
+    let mut scan = ScanRun {
+        sink,
+        stage: "",
+        weights: Vec::new(),
+        collector_index: 0,
+        file_counts: HashMap::new(),
+        started: Instant::now(),
+        last_emit: None,
+        issues: ScanIssues::default(),
+        issue_paths: HashSet::new(),
+        walk_options: ctx.walk_options.clone(),
+        pruned: BTreeMap::new(),
+    };
+
+    sink.report(ScanEvent::ScanStarted {
+        project_id: ctx.project_id.clone(),
+        project_name: ctx.project_name.clone(),
+        time_range: ctx.time_range.clone(),
+        project_folder: ctx.project_folder.clone(),
+    });
+
+    let discovered: Vec<_> = collectors
+        .iter()
+        .map(|c| {
+            scan.stage = c.id();
+            c.discover(ctx, &mut scan)
+        })
+        .collect();
+    // 预估各采集器的工作量作为进度权重
+    for (collector, sources) in collectors.iter().zip(discovered.iter()) {
+        scan.stage = collector.id();
+        let estimate = collector.estimate(ctx, sources, &mut scan);
+        scan.weights.push(estimate.max(1) as f64);
+    }
+    scan.started = Instant::now();
+
+    let mut summary = ScanSummary::default();
+    for (idx, (collector, sources)) in collectors.iter().zip(discovered.iter()).enumerate() {
+        scan.stage = collector.id();
+        scan.collector_index = idx;
+        sink.report(ScanEvent::StageStarted {
+            stage: collector.id().to_string(),
+            label: collector.label().to_string(),
+        });
+        // 时间窗口：采集前归档或清除窗口外的旧条目，采集后按固定终点裁剪
+        let spec = collector.timeline();
+        let mut touched = 0;
+        if let (Some(spec), Some(conn)) = (&spec, ctx.open_db()) {
+            touched += archive_outside_window(&conn, ctx, spec).unwrap_or(0);
+        }
+        let count = collector.collect(ctx, sources, &mut scan, &mut summary);
+        if let (Some(spec), Some(conn)) = (&spec, ctx.open_db()) {
+            // 采集器的计数单位未必是表的行（如 git 按仓库计数），移除的条数单独记录
+            let trimmed = trim_after_window(&conn, ctx, spec).unwrap_or(0);
+            if trimmed > 0 {
+                summary.trimmed.insert(collector.id().to_string(), trimmed);
+            }
+            touched += trimmed;
+            // 直接改动了采集器的表，重建其派生数据（如全文索引）
+            if touched > 0 {
+                let _ = collector.reindex(&conn, &ctx.project_id);
+            }
+        }
+        scan.report_pruned();
+        summary.collected.insert(collector.id().to_string(), count);
+        sink.report(ScanEvent::StageFinished {
+            stage: collector.id().to_string(),
+            label: collector.label().to_string(),
+            count,
+        });
+        scan.progress(1, 1);
+    }
+
+    summary.issues = std::mem::take(&mut scan.issues);
+    let finished_at = chrono::Local::now();
+    summary.started_at = started_at.format("%Y-%m-%d %H:%M:%S").to_string();
+    summary.finished_at = finished_at.format("%Y-%m-%d %H:%M:%S").to_string();
+    summary.duration_ms = (finished_at - started_at).num_milliseconds().max(0) as u64;
+
+    // 统计结果分布，更新项目的扫描摘要并记录本次运行
+    let saved = ctx
+        .open_db()
+        .ok_or_else(|| "无法打开数据库".to_string())
+        .and_then(|conn| save_run(&conn, ctx, &mut summary).map_err(|e| e.to_string()));
+    if let Err(e) = saved {
+        sink.report(ScanEvent::Warning {
+            stage: String::new(),
+            path: None,
+            reason: Some(IssueReason::DbError.as_str()),
+            message: format!("保存扫描记录失败: {}", e),
+        });
+    }
+    let total: f64 = scan.weights.iter().sum();
+    let mut done = progress_event(total, total, scan.started.elapsed());
+    if let ScanEvent::Progress { progress, .. } = &mut done {
+        *progress = 100;
+    }
+    sink.report(done);
+    summary
+}
+
+/// 补充 scan_results 的来源与类型分布，写回 projects.scan_summary 并追加一条 scan_runs 记录
+fn save_run(
+    conn: &Connection,
+    ctx: &ScanContext,
+    summary: &mut ScanSummary,
+) -> rusqlite::Result<()> {
+    summary.by_source = count_results_by(conn, "source", &ctx.project_id)?;
+    summary.by_type = count_results_by(conn, "file_type", &ctx.project_id)?;
+    let summary_json = serde_json::to_string(&summary).unwrap_or_default();
+    conn.execute(
+        "UPDATE projects SET scan_summary = ?, updated_at = ? WHERE id = ?",
+        [&summary_json, &summary.finished_at, &ctx.project_id],
+    )?;
+    conn.execute(
+        "INSERT INTO scan_runs (id, project_id, started_at, finished_at, duration_ms, skipped, failed, summary)
+         VALUES (?, ?, ?, ?, ?, ?, ?, ?)",
+        rusqlite::params![
+            uuid::Uuid::new_v4().to_string(),
+            ctx.project_id,
+            summary.started_at,
+            summary.finished_at,
+            summary.duration_ms as i64,
+            summary.issues.skipped as i64,
+            summary.issues.failed as i64,
+            summary_json
+        ],
+    )?;
+    Ok(())
+}
+
+fn count_results_by(
+    conn: &Connection,
+    column: &str,
+    project_id: &str,
+) -> rusqlite::Result<BTreeMap<String, usize>> {
+    let mut stmt = conn.prepare(&format!(
+        "SELECT {0}, COUNT(*) FROM scan_results WHERE project_id = ? GROUP BY {0}",
+        column
+    ))?;
+    let rows = stmt.query_map([project_id], |row| {
+        Ok((row.get::<_, String>(0)?, row.get::<_, i64>(1)? as usize))
+    })?;
+    rows.collect()
+}
+
+/// 开新线程执行扫描，避免阻塞 UI；结束后记录本次摘要并推送 scan-done
+/// 项目正在扫描（或监听正在写入）时直接返回错误
+#[cfg(feature = "gui")]
+pub(crate) fn spawn_scan(window: tauri::Window, ctx: ScanContext) -> Result<(), String> {
+    let lock = ScanLock::acquire(&ctx.project_id)?;
+    std::thread::spawn(move || {
+        let _lock = lock;
+        let summary = run_scan(&window, &ctx, &CollectorRegistry::builtin());
+        if let Some(state) = window
+            .app_handle()
+            .try_state::<Mutex<Option<ScanSummary>>>()
+        {
+            if let Ok(mut s) = state.lock() {
+                *s = Some(summary.clone());
+            }
+        }
+        let _ = window.emit("scan-done", &summary);
+    });
+    Ok(())
+}
+
+pub(crate) fn range_label(time_range: &str) -> &'static str {
+    match time_range {
+        "past_year" => "过去一年",
+        "past_month" => "过去一个月",
+        "past_week" => "过去一周",
+        _ => "未知范围",
+    }
+}
+
+// ==================== 路径与过滤规则 ====================
+
+/// 忽略的目录与文件名（包含即命中）
+const IGNORED_NAMES: [&str; 8] = [
+    "node_modules",
+    "target",
+    ".git",
+    "Library/Caches",
+    "AppData/Local",
+    "AppData/LocalLow",
+    "AppData/Temp",
+    ".DS_Store",
+];
+
+pub(crate) fn is_ignored(entry: &DirEntry) -> bool {
+    is_ignored_name(&entry.file_name().to_string_lossy())
+}
+
+/// 路径相对扫描根目录的任一层被忽略规则命中（监听事件没有 DirEntry，逐层判断）
+#[cfg(feature = "gui")]
+pub(crate) fn is_ignored_path(root: &Path, path: &Path) -> bool {
+    path.strip_prefix(root)
+        .unwrap_or(path)
+        .components()
+        .any(|c| is_ignored_name(&c.as_os_str().to_string_lossy()))
+}
+
+fn is_ignored_name(name: &str) -> bool {
+    IGNORED_NAMES.iter().any(|p| name.contains(p))
+}
+
+pub(crate) fn home() -> PathBuf {
+    dirs::home_dir().unwrap_or_else(|| PathBuf::from("."))
+}
+
+pub(crate) fn candidate_roots() -> Vec<PathBuf> {
+    let mut roots = vec![
+        home().join("Documents"),
+        home().join("Desktop"),
+        home().join("Downloads"),
+        home().join("Projects"),
+        home().join("Work"),
+    ];
+
+    if cfg!(target_os = "macos") {
+        roots.push(home().join("Library/Containers/com.tencent.xinWeChat/Data/Library/Application Support/com.tencent.xinWeChat"));
+        roots.push(home().join(
+            "Library/Containers/com.tencent.WeWorkMac/Data/Library/Application Support/WXWork",
+        ));
+        roots.push(
+            home().join(
+                "Library/Containers/com.alibaba.DingTalkMac/Data/Library/Application Support",
+            ),
+        );
+    }
+    if cfg!(target_os = "windows") {
+        if let Ok(userprofile) = std::env::var("USERPROFILE") {
+            roots.push(PathBuf::from(&userprofile).join("Documents/WeChat Files"));
+            roots.push(PathBuf::from(&userprofile).join("AppData/Roaming/Tencent/WeChat"));
+            roots.push(PathBuf::from(&userprofile).join("AppData/Roaming/WXWork"));
+            roots.push(PathBuf::from(&userprofile).join("AppData/Roaming/DingTalk"));
+        }
+    }
+    roots
+}
+
+// 仅扫描常见可用于分析的办公/文本/图片等文件
+pub(crate) fn is_doc(path: &Path) -> bool {
+    let ext = path
+        .extension()
+        .and_then(|s| s.to_str())
+        .unwrap_or("")
+        .to_ascii_lowercase();
+    // 常见文本/办公/数据/图片/矢量/设计等（可按需扩展）
+    const ALLOWED: &[&str] = &[
+        // 文本/标记/代码片段
+        "txt", "md", "markdown", "log", "rst", // Office / OpenOffice / iWork
+        "doc", "docx", "dotx", "rtf", "xls", "xlsx", "xlsm", "csv", "tsv", "ods", "numbers", "ppt",
+        "pptx", "odp", "key", // 文档与数据
+        "pdf", "json", "yaml", "yml", "xml", "sql", // 图片（便于做视觉回溯）
+        "png", "jpg", "jpeg", "gif", "webp", "bmp", "tif", "tiff", "heic",
+        // 矢量/设计
+        "svg", "ai", "psd",
+    ];
+    ALLOWED.contains(&ext.as_str())
+}
+
+pub(crate) fn classify_source_from_path(p: &str) -> &'static str {
+    // macOS
+    if p.contains("com.tencent.xinWeChat") {
+        return SourceKind::WeChatFile.as_str();
+    }
+    if p.contains("WXWork") {
+        return SourceKind::WeComFile.as_str();
+    }
+    if p.contains("com.alibaba.DingTalkMac") {
+        return SourceKind::DingTalkFile.as_str();
+    }
+    // Windows 典型路径
+    if p.contains("WeChat Files") || p.contains("Tencent/WeChat") {
+        return SourceKind::WeChatFile.as_str();
+    }
+    if p.contains("WXWork") {
+        return SourceKind::WeComFile.as_str();
+    }
+    if p.contains("DingTalk") {
+        return SourceKind::DingTalkFile.as_str();
+    }
+    // 常见用户目录
+    if p.contains("/Downloads/") || p.ends_with("/Downloads") {
+        return SourceKind::Downloads.as_str();
+    }
+    if p.contains("/Desktop/") || p.ends_with("/Desktop") {
+        return SourceKind::Desktop.as_str();
+    }
+    if p.contains("/Documents/") || p.ends_with("/Documents") {
+        return SourceKind::Documents.as_str();
+    }
+    SourceKind::Other.as_str()
+}
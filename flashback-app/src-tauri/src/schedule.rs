@@ -0,0 +1,584 @@
+//! 定时重扫：按类 cron 表达式定期重新扫描选定的项目（复用 run_scan），仅在应用运行期间生效
+//! 计划保存在 schedules 表，每次执行的结果记入 schedule_runs；应用启动时错过的计划会各补跑一次
+
+#[cfg(feature = "gui")]
+use chrono::{Datelike, Duration, Local, NaiveDateTime, Timelike};
+use rusqlite::Connection;
+#[cfg(feature = "gui")]
+use rusqlite::{params, OptionalExtension};
+#[cfg(feature = "gui")]
+use serde::Serialize;
+#[cfg(feature = "gui")]
+use tauri::Emitter;
+
+#[cfg(feature = "gui")]
+use crate::collectors::CollectorRegistry;
+#[cfg(feature = "gui")]
+use crate::progress::{ProgressSink, ScanEvent};
+#[cfg(feature = "gui")]
+use crate::scan::{open_db, run_scan, ScanContext, ScanLock};
+
+#[cfg(feature = "gui")]
+const TIME_FORMAT: &str = "%Y-%m-%d %H:%M:%S";
+/// 检查到期计划的间隔
+#[cfg(feature = "gui")]
+const TICK: std::time::Duration = std::time::Duration::from_secs(30);
+
+// ==================== cron 表达式 ====================
+
+/// 五段式 cron 表达式：分 时 日 月 周，支持 *、数字、列表（1,15）、范围（1-5）与步长（*/10、8-18/2），
+/// 周日可写 0 或 7；另支持 @hourly、@daily、@weekly、@monthly 简写
+#[cfg(feature = "gui")]
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct CronSpec {
+    minutes: u64,
+    hours: u32,
+    days: u32,
+    months: u16,
+    weekdays: u8,
+    /// 日、周字段是否以 * 开头（含 */2 这类步长）；与 Vixie cron 一致，两者都不以 * 开头时满足其一即可
+    any_day: bool,
+    any_weekday: bool,
+}
+
+#[cfg(feature = "gui")]
+impl CronSpec {
+    pub fn parse(expr: &str) -> Result<Self, String> {
+        let expr = match expr.trim() {
+            "@hourly" => "0 * * * *",
+            "@daily" => "0 0 * * *",
+            "@weekly" => "0 0 * * 0",
+            "@monthly" => "0 0 1 * *",
+            e => e,
+        };
+        let fields: Vec<&str> = expr.split_whitespace().collect();
+        if fields.len() != 5 {
+            return Err(format!("cron 表达式应为 5 段（分 时 日 月 周）: {}", expr));
+        }
+        let weekdays = parse_field(fields[4], 0, 7)?;
+        Ok(CronSpec {
+            minutes: parse_field(fields[0], 0, 59)?,
+            hours: parse_field(fields[1], 0, 23)? as u32,
+            days: parse_field(fields[2], 1, 31)? as u32,
+            months: parse_field(fields[3], 1, 12)? as u16,
+            // 7 与 0 都表示周日
+            weekdays: ((weekdays | (weekdays >> 7)) & 0x7f) as u8,
+            any_day: fields[2].starts_with('*'),
+            any_weekday: fields[4].starts_with('*'),
+        })
+    }
+
+    fn day_matches(&self, t: &NaiveDateTime) -> bool {
+        let dom = self.days & (1 << t.day()) != 0;
+        let dow = self.weekdays & (1 << t.weekday().num_days_from_sunday()) != 0;
+        match (self.any_day, self.any_weekday) {
+            (false, false) => dom || dow,
+            _ => dom && dow,
+        }
+    }
+
+    /// after 之后（不含）的下一次触发时间；四年内都不触发（如 2 月 30 日）时为 None
+    pub fn next_after(&self, after: NaiveDateTime) -> Option<NaiveDateTime> {
+        let mut t = after.with_second(0)?.with_nanosecond(0)? + Duration::minutes(1);
+        let limit = after + Duration::days(366 * 4);
+        while t <= limit {
+            if self.months & (1 << t.month()) == 0 {
+                // 跳到下个月 1 日零点
+                let (y, m) = if t.month() == 12 {
+                    (t.year() + 1, 1)
+                } else {
+                    (t.year(), t.month() + 1)
+                };
+                t = chrono::NaiveDate::from_ymd_opt(y, m, 1)?.and_hms_opt(0, 0, 0)?;
+            } else if !self.day_matches(&t) {
+                t = t.date().succ_opt()?.and_hms_opt(0, 0, 0)?;
+            } else if self.hours & (1 << t.hour()) == 0 {
+                t = t.with_minute(0)? + Duration::hours(1);
+            } else if self.minutes & (1 << t.minute()) == 0 {
+                t += Duration::minutes(1);
+            } else {
+                return Some(t);
+            }
+        }
+        None
+    }
+}
+
+/// 解析单段，返回按取值置位的位图
+#[cfg(feature = "gui")]
+fn parse_field(field: &str, min: u32, max: u32) -> Result<u64, String> {
+    let invalid = || format!("无效的 cron 字段: {}", field);
+    let mut bits = 0u64;
+    for part in field.split(',') {
+        let (range, step) = match part.split_once('/') {
+            Some((r, s)) => (r, s.parse::<u32>().map_err(|_| invalid())?),
+            None => (part, 1),
+        };
+        let (lo, hi) = if range == "*" {
+            (min, max)
+        } else if let Some((a, b)) = range.split_once('-') {
+            (
+                a.parse().map_err(|_| invalid())?,
+                b.parse().map_err(|_| invalid())?,
+            )
+        } else {
+            let v: u32 = range.parse().map_err(|_| invalid())?;
+            // 5/15 表示从 5 开始每 15 一次
+            (v, if part.contains('/') { max } else { v })
+        };
+        if step == 0 || lo < min || hi > max || lo > hi {
+            return Err(invalid());
+        }
+        for v in (lo..=hi).step_by(step as usize) {
+            bits |= 1 << v;
+        }
+    }
+    Ok(bits)
+}
+
+// ==================== 计划存储 ====================
+
+pub(crate) fn init_schema(conn: &Connection) -> rusqlite::Result<()> {
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS schedules (
+            id TEXT PRIMARY KEY,
+            project_id TEXT NOT NULL,
+            cron TEXT NOT NULL,
+            enabled INTEGER NOT NULL DEFAULT 1,
+            next_run_at TEXT,
+            last_run_at TEXT,
+            last_status TEXT,
+            last_message TEXT,
+            created_at TEXT NOT NULL,
+            updated_at TEXT NOT NULL
+        )",
+        [],
+    )?;
+    // 每次定时扫描的结果；status 为 ok、partial（有条目处理失败）或 failed（含项目正在扫描而未执行），catch_up 表示启动时补跑
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS schedule_runs (
+            id TEXT PRIMARY KEY,
+            schedule_id TEXT NOT NULL,
+            project_id TEXT NOT NULL,
+            started_at TEXT NOT NULL,
+            finished_at TEXT NOT NULL,
+            status TEXT NOT NULL,
+            message TEXT NOT NULL,
+            catch_up INTEGER NOT NULL DEFAULT 0
+        )",
+        [],
+    )?;
+    conn.execute(
+        "CREATE INDEX IF NOT EXISTS idx_schedule_runs_schedule ON schedule_runs(schedule_id, started_at)",
+        [],
+    )?;
+    Ok(())
+}
+
+#[cfg(feature = "gui")]
+#[derive(Debug, Clone, Serialize)]
+pub(crate) struct Schedule {
+    pub id: String,
+    pub project_id: String,
+    pub cron: String,
+    pub enabled: bool,
+    pub next_run_at: Option<String>,
+    pub last_run_at: Option<String>,
+    pub last_status: Option<String>,
+    pub last_message: Option<String>,
+    pub created_at: String,
+    pub updated_at: String,
+}
+
+#[cfg(feature = "gui")]
+#[derive(Debug, Clone, Serialize)]
+pub(crate) struct ScheduleRun {
+    pub id: String,
+    pub schedule_id: String,
+    pub project_id: String,
+    pub started_at: String,
+    pub finished_at: String,
+    pub status: String,
+    pub message: String,
+    pub catch_up: bool,
+}
+
+#[cfg(feature = "gui")]
+const SCHEDULE_COLUMNS: &str = "id, project_id, cron, enabled, next_run_at, last_run_at, last_status, last_message, created_at, updated_at";
+
+#[cfg(feature = "gui")]
+fn schedule_from_row(row: &rusqlite::Row) -> rusqlite::Result<Schedule> {
+    Ok(Schedule {
+        id: row.get(0)?,
+        project_id: row.get(1)?,
+        cron: row.get(2)?,
+        enabled: row.get::<_, i64>(3)? != 0,
+        next_run_at: row.get(4)?,
+        last_run_at: row.get(5)?,
+        last_status: row.get(6)?,
+        last_message: row.get(7)?,
+        created_at: row.get(8)?,
+        updated_at: row.get(9)?,
+    })
+}
+
+#[cfg(feature = "gui")]
+fn next_run_at(spec: &CronSpec) -> Option<String> {
+    spec.next_after(Local::now().naive_local())
+        .map(|t| t.format(TIME_FORMAT).to_string())
+}
+
+/// 列出计划；project_id 为空时列出全部
+#[cfg(feature = "gui")]
+pub(crate) fn list(conn: &Connection, project_id: Option<&str>) -> Result<Vec<Schedule>, String> {
+    let mut stmt = conn
+        .prepare(&format!(
+            "SELECT {} FROM schedules WHERE ?1 IS NULL OR project_id = ?1 ORDER BY created_at",
+            SCHEDULE_COLUMNS
+        ))
+        .map_err(|e| e.to_string())?;
+    let rows = stmt
+        .query_map([project_id], schedule_from_row)
+        .map_err(|e| e.to_string())?;
+    rows.collect::<Result<_, _>>().map_err(|e| e.to_string())
+}
+
+#[cfg(feature = "gui")]
+pub(crate) fn create(conn: &Connection, project_id: &str, cron: &str) -> Result<Schedule, String> {
+    let spec = CronSpec::parse(cron)?;
+    let exists: bool = conn
+        .query_row("SELECT 1 FROM projects WHERE id = ?", [project_id], |_| {
+            Ok(true)
+        })
+        .optional()
+        .map_err(|e| e.to_string())?
+        .unwrap_or(false);
+    if !exists {
+        return Err("项目不存在".into());
+    }
+    let id = uuid::Uuid::new_v4().to_string();
+    let now = Local::now().format(TIME_FORMAT).to_string();
+    conn.execute(
+        "INSERT INTO schedules (id, project_id, cron, enabled, next_run_at, created_at, updated_at)
+         VALUES (?, ?, ?, 1, ?, ?, ?)",
+        params![id, project_id, cron.trim(), next_run_at(&spec), now, now],
+    )
+    .map_err(|e| e.to_string())?;
+    get(conn, &id)
+}
+
+/// 修改表达式或启停；重新启用或修改表达式时从当前时间重新计算下次执行时间
+#[cfg(feature = "gui")]
+pub(crate) fn update(
+    conn: &Connection,
+    id: &str,
+    cron: Option<&str>,
+    enabled: Option<bool>,
+) -> Result<Schedule, String> {
+    let current = get(conn, id)?;
+    let cron = cron.map(str::trim).unwrap_or(&current.cron).to_string();
+    let spec = CronSpec::parse(&cron)?;
+    let enabled = enabled.unwrap_or(current.enabled);
+    let next = if cron != current.cron || (enabled && !current.enabled) {
+        next_run_at(&spec)
+    } else {
+        current.next_run_at.clone()
+    };
+    let now = Local::now().format(TIME_FORMAT).to_string();
+    conn.execute(
+        "UPDATE schedules SET cron = ?, enabled = ?, next_run_at = ?, updated_at = ? WHERE id = ?",
+        params![cron, enabled as i64, next, now, id],
+    )
+    .map_err(|e| e.to_string())?;
+    get(conn, id)
+}
+
+#[cfg(feature = "gui")]
+pub(crate) fn delete(conn: &Connection, id: &str) -> Result<(), String> {
+    conn.execute("DELETE FROM schedule_runs WHERE schedule_id = ?", [id])
+        .map_err(|e| e.to_string())?;
+    let changed = conn
+        .execute("DELETE FROM schedules WHERE id = ?", [id])
+        .map_err(|e| e.to_string())?;
+    if changed == 0 {
+        return Err("计划不存在".into());
+    }
+    Ok(())
+}
+
+#[cfg(feature = "gui")]
+fn get(conn: &Connection, id: &str) -> Result<Schedule, String> {
+    conn.query_row(
+        &format!("SELECT {} FROM schedules WHERE id = ?", SCHEDULE_COLUMNS),
+        [id],
+        schedule_from_row,
+    )
+    .map_err(|_| "计划不存在".to_string())
+}
+
+/// 计划的执行记录，最近的在前
+#[cfg(feature = "gui")]
+pub(crate) fn runs(
+    conn: &Connection,
+    schedule_id: &str,
+    limit: usize,
+) -> Result<Vec<ScheduleRun>, String> {
+    let mut stmt = conn
+        .prepare(
+            "SELECT id, schedule_id, project_id, started_at, finished_at, status, message, catch_up
+             FROM schedule_runs WHERE schedule_id = ? ORDER BY started_at DESC LIMIT ?",
+        )
+        .map_err(|e| e.to_string())?;
+    let rows = stmt
+        .query_map(params![schedule_id, limit as i64], |row| {
+            Ok(ScheduleRun {
+                id: row.get(0)?,
+                schedule_id: row.get(1)?,
+                project_id: row.get(2)?,
+                started_at: row.get(3)?,
+                finished_at: row.get(4)?,
+                status: row.get(5)?,
+                message: row.get(6)?,
+                catch_up: row.get::<_, i64>(7)? != 0,
+            })
+        })
+        .map_err(|e| e.to_string())?;
+    rows.collect::<Result<_, _>>().map_err(|e| e.to_string())
+}
+
+// ==================== 调度 ====================
+
+/// 定时扫描不向界面推送过程事件，只在结束后推送 schedule-run
+#[cfg(feature = "gui")]
+struct QuietSink;
+
+#[cfg(feature = "gui")]
+impl ProgressSink for QuietSink {
+    fn report(&self, _event: ScanEvent) {}
+}
+
+/// 启动调度线程：每隔 TICK 检查到期的计划并依次执行
+/// 第一次检查发生在启动时，此时已过期的计划（应用未运行期间错过的）只补跑一次，随后从当前时间重新排期
+#[cfg(feature = "gui")]
+pub(crate) fn start(app: tauri::AppHandle) {
+    std::thread::spawn(move || {
+        let mut startup = true;
+        loop {
+            if let Some(conn) = open_db() {
+                for schedule in due(&conn).unwrap_or_default() {
+                    let run = execute(&conn, &schedule, startup);
+                    let _ = app.emit("schedule-run", &run);
+                }
+            }
+            startup = false;
+            std::thread::sleep(TICK);
+        }
+    });
+}
+
+#[cfg(feature = "gui")]
+fn due(conn: &Connection) -> Result<Vec<Schedule>, String> {
+    let now = Local::now().format(TIME_FORMAT).to_string();
+    let mut stmt = conn
+        .prepare(&format!(
+            "SELECT {} FROM schedules WHERE enabled = 1 AND next_run_at IS NOT NULL AND next_run_at <= ? ORDER BY next_run_at",
+            SCHEDULE_COLUMNS
+        ))
+        .map_err(|e| e.to_string())?;
+    let rows = stmt
+        .query_map([now], schedule_from_row)
+        .map_err(|e| e.to_string())?;
+    rows.collect::<Result<_, _>>().map_err(|e| e.to_string())
+}
+
+/// 执行一次计划并记录结果；catch_up 为启动时补跑
+#[cfg(feature = "gui")]
+fn execute(conn: &Connection, schedule: &Schedule, catch_up: bool) -> ScheduleRun {
+    let started_at = Local::now().format(TIME_FORMAT).to_string();
+    let ctx = ScanContext::load(conn, &schedule.project_id)
+        .and_then(|ctx| ScanLock::acquire(&ctx.project_id).map(|lock| (ctx, lock)));
+    let (status, message) = match ctx {
+        Ok((ctx, _lock)) => {
+            let summary = run_scan(&QuietSink, &ctx, &CollectorRegistry::builtin());
+            let collected: usize = summary.collected.values().sum();
+            let status = if summary.issues.failed > 0 {
+                "partial"
+            } else {
+                "ok"
+            };
+            (
+                status,
+                format!(
+                    "采集 {} 条，跳过 {} 个，失败 {} 个",
+                    collected, summary.issues.skipped, summary.issues.failed
+                ),
+            )
+        }
+        Err(e) => ("failed", e),
+    };
+    let finished_at = Local::now().format(TIME_FORMAT).to_string();
+    let run = ScheduleRun {
+        id: uuid::Uuid::new_v4().to_string(),
+        schedule_id: schedule.id.clone(),
+        project_id: schedule.project_id.clone(),
+        started_at,
+        finished_at,
+        status: status.to_string(),
+        message,
+        catch_up,
+    };
+    let next = CronSpec::parse(&schedule.cron)
+        .ok()
+        .and_then(|spec| next_run_at(&spec));
+    let _ = conn.execute(
+        "INSERT INTO schedule_runs (id, schedule_id, project_id, started_at, finished_at, status, message, catch_up)
+         VALUES (?, ?, ?, ?, ?, ?, ?, ?)",
+        params![
+            run.id,
+            run.schedule_id,
+            run.project_id,
+            run.started_at,
+            run.finished_at,
+            run.status,
+            run.message,
+            catch_up as i64
+        ],
+    );
+    let _ = conn.execute(
+        "UPDATE schedules SET next_run_at = ?, last_run_at = ?, last_status = ?, last_message = ? WHERE id = ?",
+        params![next, run.finished_at, run.status, run.message, run.schedule_id],
+    );
+    run
+}
+
+#[cfg(all(test, feature = "gui"))]
+mod tests {
+    use super::*;
+
+    fn at(s: &str) -> NaiveDateTime {
+        NaiveDateTime::parse_from_str(s, "%Y-%m-%d %H:%M").unwrap()
+    }
+
+    fn next(expr: &str, after: &str) -> Option<NaiveDateTime> {
+        CronSpec::parse(expr).unwrap().next_after(at(after))
+    }
+
+    #[test]
+    fn parse_rejects_invalid() {
+        for expr in [
+            "",
+            "* * * *",
+            "60 * * * *",
+            "* 24 * * *",
+            "* * 0 * *",
+            "*/0 * * * *",
+            "5-1 * * * *",
+            "a * * * *",
+        ] {
+            assert!(CronSpec::parse(expr).is_err(), "{}", expr);
+        }
+        assert_eq!(CronSpec::parse("@daily"), CronSpec::parse("0 0 * * *"));
+        // 7 与 0 都表示周日
+        assert_eq!(CronSpec::parse("0 0 * * 7"), CronSpec::parse("0 0 * * 0"));
+    }
+
+    #[test]
+    fn steps() {
+        assert_eq!(
+            next("*/15 * * * *", "2024-01-01 10:07"),
+            Some(at("2024-01-01 10:15"))
+        );
+        assert_eq!(
+            next("*/15 * * * *", "2024-01-01 10:50"),
+            Some(at("2024-01-01 11:00"))
+        );
+        // 5/20：从 5 分开始每 20 分钟
+        assert_eq!(
+            next("5/20 * * * *", "2024-01-01 10:45"),
+            Some(at("2024-01-01 11:05"))
+        );
+        assert_eq!(
+            next("0 8-18/4 * * *", "2024-01-01 12:00"),
+            Some(at("2024-01-01 16:00"))
+        );
+        assert_eq!(
+            next("0 8-18/4 * * *", "2024-01-01 16:00"),
+            Some(at("2024-01-02 08:00"))
+        );
+    }
+
+    #[test]
+    fn ranges_and_lists() {
+        // 工作日 9 点
+        assert_eq!(
+            next("0 9 * * 1-5", "2024-01-05 09:00"),
+            Some(at("2024-01-08 09:00"))
+        );
+        assert_eq!(
+            next("30 9,13 * * *", "2024-01-01 10:00"),
+            Some(at("2024-01-01 13:30"))
+        );
+        assert_eq!(
+            next("0 0 1,15 * *", "2024-01-02 00:00"),
+            Some(at("2024-01-15 00:00"))
+        );
+        assert_eq!(
+            next("0 0 * 3-4,6 *", "2024-04-30 00:00"),
+            Some(at("2024-06-01 00:00"))
+        );
+    }
+
+    #[test]
+    fn day_of_month_or_weekday() {
+        // 日、周都限制时满足其一即可：13 号或周五
+        assert_eq!(
+            next("0 0 13 * 5", "2024-01-01 00:00"),
+            Some(at("2024-01-05 00:00"))
+        );
+        assert_eq!(
+            next("0 0 13 * 5", "2024-01-12 00:00"),
+            Some(at("2024-01-13 00:00"))
+        );
+        // 以 * 开头的步长不算限制，按另一字段筛选
+        assert_eq!(
+            next("0 0 */1 * 1", "2024-01-01 00:00"),
+            Some(at("2024-01-08 00:00"))
+        );
+        assert_eq!(
+            next("0 0 1 * */2", "2024-01-01 00:00"),
+            Some(at("2024-02-01 00:00"))
+        );
+        // 只限制其一时按该字段
+        assert_eq!(
+            next("0 0 * * 0", "2024-01-01 00:00"),
+            Some(at("2024-01-07 00:00"))
+        );
+        assert_eq!(
+            next("0 0 20 * *", "2024-01-01 00:00"),
+            Some(at("2024-01-20 00:00"))
+        );
+    }
+
+    #[test]
+    fn month_rollover() {
+        assert_eq!(
+            next("59 23 31 * *", "2024-01-31 23:59"),
+            Some(at("2024-03-31 23:59"))
+        );
+        assert_eq!(
+            next("0 0 1 1 *", "2024-06-15 12:00"),
+            Some(at("2025-01-01 00:00"))
+        );
+        assert_eq!(
+            next("* * * * *", "2024-12-31 23:59"),
+            Some(at("2025-01-01 00:00"))
+        );
+        // 闰日
+        assert_eq!(
+            next("0 12 29 2 *", "2024-03-01 00:00"),
+            Some(at("2028-02-29 12:00"))
+        );
+        // 永不触发
+        assert_eq!(next("0 0 30 2 *", "2024-01-01 00:00"), None);
+    }
+}
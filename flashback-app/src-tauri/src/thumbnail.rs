@@ -0,0 +1,424 @@
+//! 缩略图：为图片（png/jpg/gif/webp/bmp/tiff）与 PDF 首页生成 PNG 缩略图，缓存在项目文件夹的 thumbnails 目录下
+//! 缓存文件按源文件路径、修改时间、大小与边长命名，源文件变化后自然失效；目录总大小超出上限时删除最早生成的
+//! 后台单线程按队列生成，完成后推送 thumbnail-ready；PDF 借助 poppler 的 pdftoppm，
+//! macOS 上没有 pdftoppm 时改用系统自带的 sips，HEIC、PSD 也由 sips 处理，其他平台不支持这两种格式
+
+use rusqlite::{Connection, OptionalExtension};
+use serde::Serialize;
+use std::collections::HashSet;
+use std::fs::Metadata;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::sync::mpsc::{self, Sender, TryRecvError};
+use std::sync::{Arc, Mutex};
+use std::time::UNIX_EPOCH;
+use tauri::Emitter;
+
+use crate::identity::Fnv1a;
+
+/// 项目文件夹下的缓存目录名
+pub(crate) const CACHE_DIR: &str = "thumbnails";
+/// 默认边长（像素），缩略图等比缩放到长边不超过此值
+pub(crate) const DEFAULT_SIZE: u32 = 256;
+const MIN_SIZE: u32 = 32;
+const MAX_SIZE: u32 = 1024;
+/// 源文件超过此大小不生成
+const MAX_SOURCE_BYTES: u64 = 100 * 1024 * 1024;
+/// 解码图片的宽高与内存上限，防止超大或损坏的图片占满内存
+const MAX_DIMENSION: u32 = 20_000;
+const MAX_DECODE_BYTES: u64 = 512 * 1024 * 1024;
+/// 每个项目缓存目录的总大小上限
+const MAX_CACHE_BYTES: u64 = 256 * 1024 * 1024;
+
+const RASTER_TYPES: &[&str] = &["png", "jpg", "jpeg", "gif", "webp", "bmp", "tif", "tiff"];
+const SIPS_TYPES: &[&str] = &["heic", "psd"];
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Kind {
+    Raster,
+    Pdf,
+    /// 只能借助 macOS 的 sips
+    Sips,
+}
+
+fn kind_of(path: &Path) -> Option<Kind> {
+    let ext = path
+        .extension()
+        .and_then(|s| s.to_str())
+        .unwrap_or("")
+        .to_ascii_lowercase();
+    if RASTER_TYPES.contains(&ext.as_str()) {
+        Some(Kind::Raster)
+    } else if ext == "pdf" {
+        Some(Kind::Pdf)
+    } else if SIPS_TYPES.contains(&ext.as_str()) && cfg!(target_os = "macos") {
+        Some(Kind::Sips)
+    } else {
+        None
+    }
+}
+
+/// 一个待生成（或已缓存）的缩略图
+#[derive(Debug, Clone)]
+pub(crate) struct Target {
+    pub project_id: String,
+    pub source: PathBuf,
+    pub dest: PathBuf,
+    pub size: u32,
+}
+
+/// 核对文件属于项目的扫描结果且可以生成缩略图，算出缓存位置；size 不传时取默认边长
+pub(crate) fn locate(
+    conn: &Connection,
+    project_id: &str,
+    file_path: &str,
+    size: Option<u32>,
+) -> Result<Target, String> {
+    let size = size.unwrap_or(DEFAULT_SIZE);
+    if !(MIN_SIZE..=MAX_SIZE).contains(&size) {
+        return Err(format!("缩略图边长应为 {}–{}", MIN_SIZE, MAX_SIZE));
+    }
+    let folder: String = conn
+        .query_row(
+            "SELECT p.folder_path FROM scan_results r JOIN projects p ON p.id = r.project_id
+             WHERE r.project_id = ? AND r.file_path = ?",
+            [project_id, file_path],
+            |row| row.get(0),
+        )
+        .optional()
+        .map_err(|e| e.to_string())?
+        .ok_or_else(|| "文件不在项目的扫描结果中".to_string())?;
+    let source = PathBuf::from(file_path);
+    if kind_of(&source).is_none() {
+        return Err("不支持为此类型的文件生成缩略图".into());
+    }
+    let meta = std::fs::metadata(&source).map_err(|e| format!("读取文件失败: {}", e))?;
+    if meta.len() > MAX_SOURCE_BYTES {
+        return Err(format!(
+            "文件超过 {} MB，不生成缩略图",
+            MAX_SOURCE_BYTES / 1024 / 1024
+        ));
+    }
+    Ok(Target {
+        project_id: project_id.to_string(),
+        dest: cache_path(Path::new(&folder), &source, &meta, size),
+        source,
+        size,
+    })
+}
+
+/// 缓存文件的位置：源文件路径、修改时间与大小的哈希加边长
+pub(crate) fn cache_path(folder: &Path, source: &Path, meta: &Metadata, size: u32) -> PathBuf {
+    let mtime = meta
+        .modified()
+        .ok()
+        .and_then(|t| t.duration_since(UNIX_EPOCH).ok())
+        .map(|d| d.as_nanos())
+        .unwrap_or(0);
+    let mut hash = Fnv1a::new();
+    hash.write(source.to_string_lossy().as_bytes());
+    hash.write(&mtime.to_le_bytes());
+    hash.write(&meta.len().to_le_bytes());
+    folder
+        .join(CACHE_DIR)
+        .join(format!("{}-{}.png", hash.finish_hex(), size))
+}
+
+/// 返回缩略图文件，未缓存时当场生成
+pub(crate) fn ensure(target: &Target) -> Result<PathBuf, String> {
+    if !target.dest.is_file() {
+        render(&target.source, &target.dest, target.size)?;
+    }
+    Ok(target.dest.clone())
+}
+
+/// 生成缩略图写到 dest；先写临时文件再改名，读取方不会看到写了一半的文件
+pub(crate) fn render(source: &Path, dest: &Path, size: u32) -> Result<(), String> {
+    let kind = kind_of(source).ok_or("不支持为此类型的文件生成缩略图")?;
+    if let Some(dir) = dest.parent() {
+        std::fs::create_dir_all(dir).map_err(|e| format!("创建缩略图目录失败: {}", e))?;
+    }
+    let tmp = dest.with_extension("tmp.png");
+    let rendered = match kind {
+        Kind::Raster => render_raster(source, &tmp, size),
+        Kind::Pdf => render_pdf(source, &tmp, size),
+        Kind::Sips => render_sips(source, &tmp, size),
+    };
+    if let Err(e) = rendered {
+        let _ = std::fs::remove_file(&tmp);
+        return Err(e);
+    }
+    std::fs::rename(&tmp, dest).map_err(|e| e.to_string())
+}
+
+fn render_raster(source: &Path, dest: &Path, size: u32) -> Result<(), String> {
+    let mut reader = image::ImageReader::open(source)
+        .and_then(|r| r.with_guessed_format())
+        .map_err(|e| format!("读取图片失败: {}", e))?;
+    let mut limits = image::Limits::default();
+    limits.max_image_width = Some(MAX_DIMENSION);
+    limits.max_image_height = Some(MAX_DIMENSION);
+    limits.max_alloc = Some(MAX_DECODE_BYTES);
+    reader.limits(limits);
+    let img = reader
+        .decode()
+        .map_err(|e| format!("解码图片失败: {}", e))?;
+    // 小图不放大
+    let img = if img.width() > size || img.height() > size {
+        img.thumbnail(size, size)
+    } else {
+        img
+    };
+    img.to_rgba8()
+        .save_with_format(dest, image::ImageFormat::Png)
+        .map_err(|e| format!("写入缩略图失败: {}", e))
+}
+
+fn render_pdf(source: &Path, dest: &Path, size: u32) -> Result<(), String> {
+    // pdftoppm 的输出文件名为“前缀.png”
+    let prefix = dest.with_extension("");
+    let mut produced = prefix.clone().into_os_string();
+    produced.push(".png");
+    let output = Command::new("pdftoppm")
+        .args(["-png", "-singlefile", "-f", "1", "-l", "1", "-scale-to"])
+        .arg(size.to_string())
+        .arg(source)
+        .arg(&prefix)
+        .output();
+    match output {
+        Ok(out) if out.status.success() => {
+            std::fs::rename(&produced, dest).map_err(|e| e.to_string())
+        }
+        Ok(out) => Err(format!(
+            "pdftoppm 执行失败: {}",
+            String::from_utf8_lossy(&out.stderr).trim()
+        )),
+        Err(_) if cfg!(target_os = "macos") => render_sips(source, dest, size),
+        Err(_) => Err("生成 PDF 缩略图需要安装 poppler（pdftoppm）".into()),
+    }
+}
+
+/// macOS 自带的 sips，PDF 只取首页
+fn render_sips(source: &Path, dest: &Path, size: u32) -> Result<(), String> {
+    let out = Command::new("sips")
+        .args(["-s", "format", "png", "-Z"])
+        .arg(size.to_string())
+        .arg(source)
+        .arg("--out")
+        .arg(dest)
+        .output()
+        .map_err(|e| format!("调用 sips 失败: {}", e))?;
+    if out.status.success() && dest.is_file() {
+        Ok(())
+    } else {
+        Err(format!(
+            "sips 执行失败: {}",
+            String::from_utf8_lossy(&out.stderr).trim()
+        ))
+    }
+}
+
+/// 缓存目录超出上限时删除最早生成的缩略图
+pub(crate) fn prune(dir: &Path, max_bytes: u64) -> std::io::Result<()> {
+    let mut files = Vec::new();
+    let mut total = 0;
+    for entry in std::fs::read_dir(dir)? {
+        let entry = entry?;
+        let meta = entry.metadata()?;
+        if meta.is_file() {
+            total += meta.len();
+            files.push((meta.modified()?, meta.len(), entry.path()));
+        }
+    }
+    files.sort();
+    for (_, len, path) in files {
+        if total <= max_bytes {
+            break;
+        }
+        std::fs::remove_file(path)?;
+        total -= len;
+    }
+    Ok(())
+}
+
+/// thumbnail-ready 事件：path 与 error 恰有一个
+#[derive(Debug, Clone, Serialize)]
+pub(crate) struct ThumbnailReady {
+    pub project_id: String,
+    pub file_path: String,
+    pub size: u32,
+    pub path: Option<String>,
+    pub error: Option<String>,
+}
+
+/// 后台生成缩略图的队列，应用启动时创建
+pub(crate) struct ThumbnailService {
+    tx: Sender<Target>,
+    /// 已在队列中的缓存文件，重复请求不再入队
+    queued: Arc<Mutex<HashSet<PathBuf>>>,
+}
+
+impl ThumbnailService {
+    pub fn start(app: tauri::AppHandle) -> Self {
+        let (tx, rx) = mpsc::channel::<Target>();
+        let queued: Arc<Mutex<HashSet<PathBuf>>> = Arc::default();
+        let done = queued.clone();
+        std::thread::spawn(move || {
+            // 队列空闲时再清理写过的缓存目录
+            let mut dirty: HashSet<PathBuf> = HashSet::new();
+            loop {
+                let target = match rx.try_recv() {
+                    Ok(t) => t,
+                    Err(TryRecvError::Empty) => {
+                        for dir in dirty.drain() {
+                            let _ = prune(&dir, MAX_CACHE_BYTES);
+                        }
+                        match rx.recv() {
+                            Ok(t) => t,
+                            Err(_) => break,
+                        }
+                    }
+                    Err(TryRecvError::Disconnected) => break,
+                };
+                let result = ensure(&target);
+                if let Some(dir) = target.dest.parent() {
+                    dirty.insert(dir.to_path_buf());
+                }
+                if let Ok(mut queued) = done.lock() {
+                    queued.remove(&target.dest);
+                }
+                let (path, error) = match result {
+                    Ok(p) => (Some(p.to_string_lossy().to_string()), None),
+                    Err(e) => (None, Some(e)),
+                };
+                let _ = app.emit(
+                    "thumbnail-ready",
+                    ThumbnailReady {
+                        project_id: target.project_id,
+                        file_path: target.source.to_string_lossy().to_string(),
+                        size: target.size,
+                        path,
+                        error,
+                    },
+                );
+            }
+        });
+        ThumbnailService { tx, queued }
+    }
+
+    /// 已缓存时返回缓存文件；否则加入后台队列并返回 None，生成后推送 thumbnail-ready
+    pub fn request(&self, target: Target) -> Result<Option<PathBuf>, String> {
+        if target.dest.is_file() {
+            return Ok(Some(target.dest));
+        }
+        let mut queued = self.queued.lock().map_err(|e| e.to_string())?;
+        if queued.insert(target.dest.clone()) {
+            if let Err(e) = self.tx.send(target) {
+                queued.remove(&e.0.dest);
+                return Err("缩略图服务已停止".into());
+            }
+        }
+        Ok(None)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::{Duration, SystemTime};
+
+    fn fixture(name: &str) -> PathBuf {
+        Path::new(env!("CARGO_MANIFEST_DIR"))
+            .join("tests/fixtures/thumbnail")
+            .join(name)
+    }
+
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("flashback-{}-{}", name, std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[test]
+    fn render_raster_fits_size() {
+        let dir = temp_dir("thumb-raster");
+        // wide.jpg 为 64×32，tall.png 为 20×60
+        let dest = dir.join("wide.png");
+        render(&fixture("wide.jpg"), &dest, 32).unwrap();
+        assert_eq!(image::image_dimensions(&dest).unwrap(), (32, 16));
+        render(&fixture("tall.png"), &dest, 30).unwrap();
+        assert_eq!(image::image_dimensions(&dest).unwrap(), (10, 30));
+        // 小图不放大
+        render(&fixture("tall.png"), &dest, 256).unwrap();
+        assert_eq!(image::image_dimensions(&dest).unwrap(), (20, 60));
+        // 临时文件已改名
+        assert!(!dest.with_extension("tmp.png").exists());
+
+        let broken = dir.join("broken.png");
+        std::fs::write(&broken, b"not an image").unwrap();
+        assert!(render(&broken, &dir.join("broken-thumb.png"), 32).is_err());
+        assert!(!dir.join("broken-thumb.tmp.png").exists());
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn render_pdf_first_page() {
+        // 未安装 poppler 时跳过
+        if Command::new("pdftoppm").arg("-v").output().is_err() {
+            return;
+        }
+        let dir = temp_dir("thumb-pdf");
+        // wide.pdf 单页 144×72pt
+        let dest = dir.join("wide.png");
+        render(&fixture("wide.pdf"), &dest, 32).unwrap();
+        assert_eq!(image::image_dimensions(&dest).unwrap(), (32, 16));
+        assert_eq!(std::fs::read_dir(&dir).unwrap().count(), 1);
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn cache_path_stable() {
+        let dir = temp_dir("thumb-cache");
+        let source = dir.join("a.png");
+        std::fs::copy(fixture("tall.png"), &source).unwrap();
+        let meta = std::fs::metadata(&source).unwrap();
+        let folder = Path::new("/project");
+        let path = cache_path(folder, &source, &meta, 64);
+        assert_eq!(path, cache_path(folder, &source, &meta, 64));
+        assert!(path.starts_with(folder.join(CACHE_DIR)));
+        assert_ne!(path, cache_path(folder, &source, &meta, 128));
+        assert_ne!(path, cache_path(folder, &dir.join("b.png"), &meta, 64));
+
+        // 内容变化（修改时间、大小）后换用新的缓存文件
+        std::fs::copy(fixture("wide.jpg"), &source).unwrap();
+        let file = std::fs::File::options().write(true).open(&source).unwrap();
+        file.set_modified(SystemTime::now() + Duration::from_secs(10))
+            .unwrap();
+        let changed = std::fs::metadata(&source).unwrap();
+        assert_ne!(path, cache_path(folder, &source, &changed, 64));
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn prune_removes_oldest_over_cap() {
+        let dir = temp_dir("thumb-prune");
+        let now = SystemTime::now();
+        for (i, name) in ["old.png", "mid.png", "new.png"].iter().enumerate() {
+            let path = dir.join(name);
+            std::fs::write(&path, [0u8; 100]).unwrap();
+            let file = std::fs::File::options().write(true).open(&path).unwrap();
+            file.set_modified(now - Duration::from_secs(100 * (3 - i as u64)))
+                .unwrap();
+        }
+        prune(&dir, 300).unwrap();
+        assert_eq!(std::fs::read_dir(&dir).unwrap().count(), 3);
+        prune(&dir, 250).unwrap();
+        assert!(!dir.join("old.png").exists());
+        assert!(dir.join("mid.png").exists() && dir.join("new.png").exists());
+        prune(&dir, 100).unwrap();
+        assert!(!dir.join("mid.png").exists());
+        assert!(dir.join("new.png").exists());
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
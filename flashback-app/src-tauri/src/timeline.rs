@@ -0,0 +1,257 @@
+//! 时间线聚合：把各采集器带时间的数据（文件修改、提交、会议、邮件、终端命令、浏览记录）
+//! 按日 / 周 / 月分段统计，并给出每段的代表条目，供前端绘制年度回顾
+//! 参与聚合的数据由各采集器的 Collector::timeline 声明
+
+use chrono::{Datelike, Duration, NaiveDate};
+use rusqlite::Connection;
+use serde::Serialize;
+use std::collections::BTreeMap;
+
+use crate::collectors::{CollectorRegistry, TimelineSpec};
+
+/// 每个来源在每段中默认返回的代表条目数
+const DEFAULT_TOP_N: usize = 5;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Granularity {
+    Day,
+    Week,
+    Month,
+}
+
+impl Granularity {
+    fn parse(s: &str) -> Result<Self, String> {
+        match s {
+            "day" => Ok(Granularity::Day),
+            "week" => Ok(Granularity::Week),
+            "month" => Ok(Granularity::Month),
+            _ => Err(format!("不支持的时间粒度: {}", s)),
+        }
+    }
+
+    /// 把时间列换算为所在分段起始日期（YYYY-MM-DD）的 SQL 表达式；周从周一开始
+    fn bucket_sql(&self, column: &str) -> String {
+        match self {
+            Granularity::Day => format!("date({})", column),
+            Granularity::Week => format!("date({}, '-6 days', 'weekday 1')", column),
+            Granularity::Month => format!("strftime('%Y-%m-01', {})", column),
+        }
+    }
+
+    fn next(&self, start: NaiveDate) -> NaiveDate {
+        match self {
+            Granularity::Day => start + Duration::days(1),
+            Granularity::Week => start + Duration::days(7),
+            Granularity::Month => {
+                let (y, m) = if start.month() == 12 {
+                    (start.year() + 1, 1)
+                } else {
+                    (start.year(), start.month() + 1)
+                };
+                NaiveDate::from_ymd_opt(y, m, 1).unwrap_or(start)
+            }
+        }
+    }
+
+    fn label(&self, start: NaiveDate) -> String {
+        match self {
+            Granularity::Day => start.format("%Y-%m-%d").to_string(),
+            Granularity::Week => {
+                let w = start.iso_week();
+                format!("{}-W{:02}", w.year(), w.week())
+            }
+            Granularity::Month => start.format("%Y-%m").to_string(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub(crate) struct TimelineItem {
+    source: String,
+    title: String,
+    detail: String,
+    time: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub(crate) struct TimelineBucket {
+    /// 分段起始日期（含）
+    start: String,
+    /// 分段结束日期（不含）
+    end: String,
+    label: String,
+    total: usize,
+    /// 各来源条目数，键为采集器 id
+    counts: BTreeMap<String, usize>,
+    /// 各来源按权重选出的代表条目
+    top_items: Vec<TimelineItem>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub(crate) struct TimelineSource {
+    id: String,
+    label: String,
+    total: usize,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub(crate) struct Timeline {
+    granularity: String,
+    sources: Vec<TimelineSource>,
+    /// 按时间升序，首末分段之间没有数据的分段也会补齐
+    buckets: Vec<TimelineBucket>,
+}
+
+/// 聚合项目时间线；sources 为空表示全部来源
+pub(crate) fn build(
+    conn: &Connection,
+    project_id: &str,
+    granularity: &str,
+    sources: Option<&[String]>,
+    top_n: Option<usize>,
+) -> Result<Timeline, String> {
+    let gran = Granularity::parse(granularity)?;
+    let top_n = top_n.unwrap_or(DEFAULT_TOP_N);
+    let specs: Vec<_> = CollectorRegistry::builtin()
+        .timeline_specs()
+        .into_iter()
+        .filter(|(id, _, _)| match sources {
+            Some(ids) if !ids.is_empty() => ids.iter().any(|s| s == id),
+            _ => true,
+        })
+        .collect();
+
+    let mut buckets: BTreeMap<NaiveDate, TimelineBucket> = BTreeMap::new();
+    let mut source_totals = Vec::new();
+    for (id, label, spec) in &specs {
+        let mut source_total = 0usize;
+        for (start, count) in count_by_bucket(conn, project_id, gran, spec)? {
+            let bucket = buckets
+                .entry(start)
+                .or_insert_with(|| empty_bucket(gran, start));
+            bucket.counts.insert(id.to_string(), count);
+            bucket.total += count;
+            source_total += count;
+        }
+        if top_n > 0 {
+            for (start, item) in top_by_bucket(conn, project_id, gran, id, spec, top_n)? {
+                if let Some(bucket) = buckets.get_mut(&start) {
+                    bucket.top_items.push(item);
+                }
+            }
+        }
+        source_totals.push(TimelineSource {
+            id: id.to_string(),
+            label: label.to_string(),
+            total: source_total,
+        });
+    }
+
+    // 补齐首末之间的空分段，便于前端直接按序绘制
+    if let (Some(&first), Some(&last)) = (buckets.keys().next(), buckets.keys().last()) {
+        let mut cursor = first;
+        while cursor < last {
+            buckets
+                .entry(cursor)
+                .or_insert_with(|| empty_bucket(gran, cursor));
+            cursor = gran.next(cursor);
+        }
+    }
+
+    Ok(Timeline {
+        granularity: granularity.to_string(),
+        sources: source_totals,
+        buckets: buckets.into_values().collect(),
+    })
+}
+
+fn empty_bucket(gran: Granularity, start: NaiveDate) -> TimelineBucket {
+    TimelineBucket {
+        start: start.format("%Y-%m-%d").to_string(),
+        end: gran.next(start).format("%Y-%m-%d").to_string(),
+        label: gran.label(start),
+        total: 0,
+        counts: BTreeMap::new(),
+        top_items: Vec::new(),
+    }
+}
+
+fn parse_bucket(s: &str) -> Option<NaiveDate> {
+    NaiveDate::parse_from_str(s, "%Y-%m-%d").ok()
+}
+
+fn count_by_bucket(
+    conn: &Connection,
+    project_id: &str,
+    gran: Granularity,
+    spec: &TimelineSpec,
+) -> Result<Vec<(NaiveDate, usize)>, String> {
+    let bucket = gran.bucket_sql(spec.time_column);
+    let sql = format!(
+        "SELECT {bucket} AS bucket, COUNT(*) FROM {table} WHERE project_id = ? AND {time} != '' GROUP BY bucket",
+        bucket = bucket,
+        table = spec.table,
+        time = spec.time_column,
+    );
+    let mut stmt = conn.prepare(&sql).map_err(|e| e.to_string())?;
+    let rows = stmt
+        .query_map([project_id], |row| {
+            Ok((row.get::<_, Option<String>>(0)?, row.get::<_, i64>(1)?))
+        })
+        .map_err(|e| e.to_string())?;
+    let mut out = Vec::new();
+    for row in rows {
+        let (bucket, count) = row.map_err(|e| e.to_string())?;
+        // 无法解析的时间（如空串、非标准格式）不计入时间线
+        if let Some(start) = bucket.as_deref().and_then(parse_bucket) {
+            out.push((start, count as usize));
+        }
+    }
+    Ok(out)
+}
+
+fn top_by_bucket(
+    conn: &Connection,
+    project_id: &str,
+    gran: Granularity,
+    source: &str,
+    spec: &TimelineSpec,
+    top_n: usize,
+) -> Result<Vec<(NaiveDate, TimelineItem)>, String> {
+    let bucket = gran.bucket_sql(spec.time_column);
+    let sql = format!(
+        "SELECT bucket, title, detail, time FROM (
+            SELECT {bucket} AS bucket, {title} AS title, {detail} AS detail, {time} AS time,
+                   ROW_NUMBER() OVER (PARTITION BY {bucket} ORDER BY {weight} DESC, {time} DESC) AS rn
+            FROM {table} WHERE project_id = ? AND {time} != ''
+        ) WHERE rn <= ? ORDER BY bucket, rn",
+        bucket = bucket,
+        title = spec.title_expr,
+        detail = spec.detail_expr,
+        time = spec.time_column,
+        weight = spec.weight_expr,
+        table = spec.table,
+    );
+    let mut stmt = conn.prepare(&sql).map_err(|e| e.to_string())?;
+    let rows = stmt
+        .query_map(rusqlite::params![project_id, top_n as i64], |row| {
+            Ok((
+                row.get::<_, Option<String>>(0)?,
+                TimelineItem {
+                    source: source.to_string(),
+                    title: row.get::<_, Option<String>>(1)?.unwrap_or_default(),
+                    detail: row.get::<_, Option<String>>(2)?.unwrap_or_default(),
+                    time: row.get(3)?,
+                },
+            ))
+        })
+        .map_err(|e| e.to_string())?;
+    let mut out = Vec::new();
+    for row in rows {
+        let (bucket, item) = row.map_err(|e| e.to_string())?;
+        if let Some(start) = bucket.as_deref().and_then(parse_bucket) {
+            out.push((start, item));
+        }
+    }
+    Ok(out)
+}
@@ -0,0 +1,222 @@
+//! 目录遍历：文档、Git 采集器与文件预计数共用同一套遍历规则
+//! 在 walkdir 之上按项目的遍历选项（projects.walk_options）控制符号链接、文件系统边界、深度、
+//! 单目录文件数与隐藏目录；因选项被剪掉的目录以 WalkItem::Pruned 交给调用方记录
+
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use walkdir::{DirEntry, WalkDir};
+
+use crate::scan::is_ignored;
+
+/// 项目级遍历选项，默认值与此前的遍历行为一致
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub(crate) struct WalkOptions {
+    /// 跟随符号链接；链接形成循环时该目录计为跳过（symlink_loop）
+    pub follow_symlinks: bool,
+    /// 不进入与根目录不同的文件系统（网络盘、外接硬盘等挂载点）
+    pub same_file_system: bool,
+    /// 最大深度，与 walkdir 一致：根目录的直接子项深度为 1，只处理深度不超过该值的条目
+    pub max_depth: Option<usize>,
+    /// 单个目录最多处理的文件数，超出的文件跳过
+    pub max_files_per_dir: Option<usize>,
+    /// 跳过以 . 开头的隐藏目录
+    pub skip_hidden: bool,
+}
+
+impl WalkOptions {
+    /// 按这些选项遍历 root 时是否会遍历到其下的文件 path（忽略规则与单目录文件数上限不在此判断）；
+    /// 监听事件没有遍历过程，逐层检查父目录
+    #[cfg(feature = "gui")]
+    pub fn admits(&self, root: &Path, path: &Path) -> bool {
+        let rel = match path.strip_prefix(root) {
+            Ok(rel) => rel,
+            Err(_) => return false,
+        };
+        if self
+            .max_depth
+            .is_some_and(|max| rel.components().count() > max)
+        {
+            return false;
+        }
+        let root_dev = self.same_file_system.then(|| device_id(root)).flatten();
+        let mut dir = root.to_path_buf();
+        for component in rel.parent().into_iter().flat_map(Path::components) {
+            dir.push(component);
+            if self.skip_hidden && is_hidden(&component.as_os_str().to_string_lossy()) {
+                return false;
+            }
+            if root_dev.is_some_and(|root_dev| device_id(&dir).is_some_and(|dev| dev != root_dev)) {
+                return false;
+            }
+        }
+        true
+    }
+}
+
+/// 遍历选项剪掉目录或文件的原因
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub(crate) enum PruneRule {
+    Hidden,
+    OtherFileSystem,
+    MaxDepth,
+    MaxFilesPerDir,
+}
+
+impl PruneRule {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            PruneRule::Hidden => "hidden",
+            PruneRule::OtherFileSystem => "other_file_system",
+            PruneRule::MaxDepth => "max_depth",
+            PruneRule::MaxFilesPerDir => "max_files_per_dir",
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            PruneRule::Hidden => "跳过隐藏目录",
+            PruneRule::OtherFileSystem => "跳过其他文件系统",
+            PruneRule::MaxDepth => "超过最大深度",
+            PruneRule::MaxFilesPerDir => "目录文件数超过上限",
+        }
+    }
+}
+
+pub(crate) enum WalkItem {
+    Entry(DirEntry),
+    Error(walkdir::Error),
+    /// 目录因遍历选项不再深入；MaxFilesPerDir 时 path 为被截断的目录，每个目录只出现一次
+    Pruned(PruneRule, PathBuf),
+}
+
+/// 按遍历选项遍历一个根目录
+/// 目录条目总会先交给调用方，调用方可以像 walkdir 一样立即 skip_current_dir（如 Git 采集器跳过 .git）；
+/// 调用方没有跳过时，下一次 next 才按忽略规则与遍历选项决定是否深入
+pub(crate) struct Walk {
+    it: walkdir::IntoIter,
+    options: WalkOptions,
+    /// 根目录所在的设备号，用于判断是否跨文件系统
+    root_dev: Option<u64>,
+    /// 上一个交出的条目是否为目录（只有此时才能 skip_current_dir）
+    last_dir: bool,
+    /// 上一个交出的目录及其剪枝原因（None 表示被忽略规则静默跳过）
+    pending: Option<(Option<PruneRule>, PathBuf)>,
+    /// 按深度记录父目录已交出的文件数
+    dir_files: Vec<usize>,
+}
+
+impl Walk {
+    pub fn new(root: &Path, options: &WalkOptions) -> Self {
+        let mut walker = WalkDir::new(root).follow_links(options.follow_symlinks);
+        // 非 Unix 平台无法取得设备号，交给 walkdir 判断（剪掉的目录不会单独记录）
+        if options.same_file_system && !cfg!(unix) {
+            walker = walker.same_file_system(true);
+        }
+        Walk {
+            it: walker.into_iter(),
+            options: options.clone(),
+            root_dev: options.same_file_system.then(|| device_id(root)).flatten(),
+            last_dir: false,
+            pending: None,
+            dir_files: Vec::new(),
+        }
+    }
+
+    /// 不再深入刚交出的目录
+    pub fn skip_current_dir(&mut self) {
+        if self.last_dir {
+            self.last_dir = false;
+            self.pending = None;
+            self.it.skip_current_dir();
+        }
+    }
+
+    /// 目录是否应被剪掉：Some(None) 为忽略规则，Some(Some(rule)) 为遍历选项
+    fn prune_reason(&self, entry: &DirEntry) -> Option<Option<PruneRule>> {
+        let depth = entry.depth();
+        if depth > 0 && is_ignored(entry) {
+            return Some(None);
+        }
+        if depth > 0 && self.options.skip_hidden && is_hidden(&entry.file_name().to_string_lossy())
+        {
+            return Some(Some(PruneRule::Hidden));
+        }
+        if let Some(root_dev) = self.root_dev {
+            if device_id(entry.path()).is_some_and(|dev| dev != root_dev) {
+                return Some(Some(PruneRule::OtherFileSystem));
+            }
+        }
+        if self.options.max_depth.is_some_and(|max| depth >= max) {
+            return Some(Some(PruneRule::MaxDepth));
+        }
+        None
+    }
+}
+
+impl Iterator for Walk {
+    type Item = WalkItem;
+
+    fn next(&mut self) -> Option<WalkItem> {
+        self.last_dir = false;
+        if let Some((rule, path)) = self.pending.take() {
+            self.it.skip_current_dir();
+            if let Some(rule) = rule {
+                return Some(WalkItem::Pruned(rule, path));
+            }
+        }
+        loop {
+            let entry = match self.it.next()? {
+                Ok(e) => e,
+                Err(e) => return Some(WalkItem::Error(e)),
+            };
+            let depth = entry.depth();
+            if entry.file_type().is_dir() {
+                // 该目录的子项深度为 depth + 1，重新开始计数
+                self.dir_files.truncate(depth + 1);
+                self.dir_files.resize(depth + 2, 0);
+                if let Some(rule) = self.prune_reason(&entry) {
+                    self.pending = Some((rule, entry.path().to_path_buf()));
+                }
+                self.last_dir = true;
+                return Some(WalkItem::Entry(entry));
+            }
+            if depth > 0 && is_ignored(&entry) {
+                continue;
+            }
+            if let Some(max) = self.options.max_files_per_dir {
+                if self.dir_files.len() <= depth {
+                    self.dir_files.resize(depth + 1, 0);
+                }
+                self.dir_files[depth] += 1;
+                let n = self.dir_files[depth];
+                if n > max {
+                    if n == max + 1 {
+                        let dir = entry.path().parent().unwrap_or(entry.path());
+                        return Some(WalkItem::Pruned(
+                            PruneRule::MaxFilesPerDir,
+                            dir.to_path_buf(),
+                        ));
+                    }
+                    continue;
+                }
+            }
+            return Some(WalkItem::Entry(entry));
+        }
+    }
+}
+
+fn is_hidden(name: &str) -> bool {
+    name.starts_with('.')
+}
+
+#[cfg(unix)]
+fn device_id(path: &Path) -> Option<u64> {
+    use std::os::unix::fs::MetadataExt;
+    std::fs::metadata(path).ok().map(|m| m.dev())
+}
+
+#[cfg(not(unix))]
+fn device_id(_path: &Path) -> Option<u64> {
+    None
+}
@@ -0,0 +1,427 @@
+//! 实时监听：扫描结束后对项目的扫描根目录开启文件系统监听（notify，Linux 下为 inotify），
+//! 按与文档采集相同的规则（is_doc、is_ignored、遍历选项、时间范围）把新增、修改、重命名与删除同步到 scan_results，
+//! 改名与移动按 identity 模块的规则沿用原条目；每批变更写库后推送 results-changed
+
+use notify::{EventKind, RecommendedWatcher, RecursiveMode, Watcher};
+use rusqlite::{Connection, TransactionBehavior};
+use serde::Serialize;
+use std::collections::BTreeSet;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError};
+use std::sync::Mutex;
+use std::time::Duration;
+use tauri::Emitter;
+
+use crate::collectors::{insert_document, DocumentSettings};
+use crate::identity::{FileMove, Identities};
+use crate::scan::{is_doc, is_ignored_path, EntryError, IssueReason, ScanContext, ScanLock};
+use crate::walk::{Walk, WalkItem};
+
+/// 合并窗口：编辑器保存一次往往连续触发多个事件，静默这么久后再统一写库
+const DEBOUNCE: Duration = Duration::from_millis(500);
+/// 持续有事件时，积累到这么多路径也立即写库
+const MAX_BATCH: usize = 1000;
+/// 项目正在扫描或写库失败后等待这么久再重试，期间的事件留在 channel 中
+const RETRY_DELAY: Duration = Duration::from_secs(2);
+
+/// 当前的监听，同一时间只监听一个项目
+#[derive(Default)]
+pub(crate) struct WatchState(Mutex<Option<ProjectWatch>>);
+
+struct ProjectWatch {
+    status: WatchStatus,
+    // drop 时停止监听，事件 channel 随之关闭，同步线程退出
+    _watcher: RecommendedWatcher,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub(crate) struct WatchStatus {
+    pub project_id: String,
+    /// 开启监听的根目录（不存在的扫描目录会被跳过）
+    pub roots: Vec<String>,
+}
+
+/// results-changed 事件
+#[derive(Debug, Clone, Serialize)]
+pub(crate) struct ResultsChanged {
+    pub project_id: String,
+    /// 新增或更新的文件
+    pub upserted: Vec<String>,
+    /// 从结果中移除的文件或目录
+    pub removed: Vec<String>,
+    /// 识别为改名或移动的文件，条目 id 与人工整理数据已随之迁移
+    pub moved: Vec<FileMove>,
+}
+
+impl WatchState {
+    /// 开始监听项目的扫描根目录，替换正在进行的监听
+    pub fn start(&self, app: tauri::AppHandle, ctx: ScanContext) -> Result<WatchStatus, String> {
+        let (tx, rx) = mpsc::channel();
+        let mut watcher = notify::recommended_watcher(tx).map_err(|e| e.to_string())?;
+        let mut roots = Vec::new();
+        for root in ctx.roots.iter().filter(|r| r.is_dir()) {
+            watcher
+                .watch(root, RecursiveMode::Recursive)
+                .map_err(|e| format!("无法监听 {}: {}", root.display(), e))?;
+            roots.push(root.clone());
+        }
+        if roots.is_empty() {
+            return Err("没有可监听的扫描目录".into());
+        }
+        let status = WatchStatus {
+            project_id: ctx.project_id.clone(),
+            roots: roots
+                .iter()
+                .map(|p| p.to_string_lossy().to_string())
+                .collect(),
+        };
+        let mut current = self.0.lock().map_err(|e| e.to_string())?;
+        *current = Some(ProjectWatch {
+            status: status.clone(),
+            _watcher: watcher,
+        });
+        std::thread::spawn(move || sync_loop(&app, &ctx, &roots, rx));
+        Ok(status)
+    }
+
+    /// 停止监听，返回之前是否在监听
+    pub fn stop(&self) -> bool {
+        self.0
+            .lock()
+            .map(|mut w| w.take().is_some())
+            .unwrap_or(false)
+    }
+
+    /// 正在监听的是该项目时停止监听（删除项目时调用），返回是否停止
+    pub fn stop_project(&self, project_id: &str) -> bool {
+        self.0
+            .lock()
+            .map(|mut w| {
+                let watching = w
+                    .as_ref()
+                    .is_some_and(|w| w.status.project_id == project_id);
+                if watching {
+                    *w = None;
+                }
+                watching
+            })
+            .unwrap_or(false)
+    }
+
+    pub fn status(&self) -> Option<WatchStatus> {
+        self.0
+            .lock()
+            .ok()
+            .and_then(|w| w.as_ref().map(|w| w.status.clone()))
+    }
+}
+
+/// 收集事件涉及的路径，合并窗口结束后按文件系统的当前状态统一处理：
+/// 路径存在则按采集规则写入（目录则遍历其中的文件），不存在则从结果中移除（含其下的文件）
+/// 这样新增、修改、重命名（新旧路径各触发一次）与删除走同一套逻辑，不依赖各平台的事件细分
+fn sync_loop(
+    app: &tauri::AppHandle,
+    ctx: &ScanContext,
+    roots: &[PathBuf],
+    rx: Receiver<notify::Result<notify::Event>>,
+) {
+    let mut dirty: BTreeSet<PathBuf> = BTreeSet::new();
+    loop {
+        let next = if dirty.is_empty() {
+            rx.recv().map_err(|_| RecvTimeoutError::Disconnected)
+        } else {
+            rx.recv_timeout(DEBOUNCE)
+        };
+        match next {
+            Ok(Ok(event)) => {
+                if !matches!(event.kind, EventKind::Access(_)) {
+                    dirty.extend(event.paths);
+                }
+                if dirty.len() < MAX_BATCH {
+                    continue;
+                }
+            }
+            // 监听本身的错误（如事件队列溢出）不影响后续事件
+            Ok(Err(_)) => continue,
+            Err(RecvTimeoutError::Timeout) => {}
+            Err(RecvTimeoutError::Disconnected) => return,
+        }
+        let paths = std::mem::take(&mut dirty);
+        // 扫描会改写结果，持有扫描锁时才写入
+        let applied = ScanLock::acquire(&ctx.project_id).ok().and_then(|_lock| {
+            ctx.open_db()
+                .map(|mut conn| apply_changes(&mut conn, ctx, roots, &paths))
+        });
+        match applied {
+            Some(Ok(changed)) => {
+                if !changed.upserted.is_empty()
+                    || !changed.removed.is_empty()
+                    || !changed.moved.is_empty()
+                {
+                    let _ = app.emit("results-changed", &changed);
+                }
+            }
+            // 项目正在扫描、打不开数据库或写库失败时放回待处理集合，稍后与新事件一起重试，不丢失变更
+            _ => {
+                dirty.extend(paths);
+                std::thread::sleep(RETRY_DELAY);
+            }
+        }
+    }
+}
+
+fn apply_changes(
+    conn: &mut Connection,
+    ctx: &ScanContext,
+    roots: &[PathBuf],
+    paths: &BTreeSet<PathBuf>,
+) -> Result<ResultsChanged, EntryError> {
+    let mut changed = ResultsChanged {
+        project_id: ctx.project_id.clone(),
+        upserted: Vec::new(),
+        removed: Vec::new(),
+        moved: Vec::new(),
+    };
+    // 立即取得写锁：数据库被占用时在这里失败并整批重试，而不是在单个文件写入时被当作文件错误忽略
+    let tx = conn.transaction_with_behavior(TransactionBehavior::Immediate)?;
+    // 项目已被删除（如在命令行中删除）时不再写入
+    let exists: bool = tx.query_row(
+        "SELECT EXISTS(SELECT 1 FROM projects WHERE id = ?)",
+        [&ctx.project_id],
+        |row| row.get(0),
+    )?;
+    if !exists {
+        return Ok(changed);
+    }
+    let paths: Vec<PathBuf> = paths.iter().cloned().collect();
+    let mut ids = Identities::load_paths(&tx, &ctx.project_id, &paths)?;
+    let settings = DocumentSettings::of(ctx);
+    for path in paths {
+        let root = match roots.iter().find(|r| path.starts_with(r)) {
+            Some(r) => r,
+            None => continue,
+        };
+        if is_ignored_path(root, &path) {
+            continue;
+        }
+        if path.is_dir() {
+            for item in Walk::new(&path, &ctx.walk_options) {
+                if let WalkItem::Entry(entry) = item {
+                    if !entry.file_type().is_dir() {
+                        let file = entry.path();
+                        if ctx.walk_options.admits(root, file) {
+                            upsert(&tx, ctx, &settings, file, &mut ids, &mut changed)?;
+                        } else if remove(&tx, &ctx.project_id, file)? > 0 {
+                            changed.removed.push(file.to_string_lossy().to_string());
+                        }
+                    }
+                }
+            }
+        } else if path.is_file() && ctx.walk_options.admits(root, &path) {
+            upsert(&tx, ctx, &settings, &path, &mut ids, &mut changed)?;
+        } else if remove(&tx, &ctx.project_id, &path)? > 0 {
+            // 已删除，或按遍历选项扫描时不会遍历到（如隐藏目录、超过最大深度）
+            changed.removed.push(path.to_string_lossy().to_string());
+        }
+    }
+    changed.moved = ids.follow_moves(&tx, &ctx.project_id)?;
+    tx.commit()?;
+    Ok(changed)
+}
+
+/// 符合采集规则的文件写入结果；不再符合的（如改名为非文档扩展名）从结果中移除
+fn upsert(
+    conn: &Connection,
+    ctx: &ScanContext,
+    settings: &DocumentSettings,
+    path: &Path,
+    ids: &mut Identities,
+    changed: &mut ResultsChanged,
+) -> Result<(), EntryError> {
+    let path_s = path.to_string_lossy().to_string();
+    if is_doc(path) {
+        match insert_document(conn, ctx, settings, path, ids) {
+            Ok(true) => {
+                changed.upserted.push(path_s);
+                return Ok(());
+            }
+            // 不在时间范围内：移出结果（retain 模式归档的条目保留），元数据缓存留给之后的扫描判断
+            Ok(false) => {
+                if conn.execute(
+                    "DELETE FROM scan_results WHERE project_id = ? AND file_path = ? AND archived = 0",
+                    [&ctx.project_id, &path_s],
+                )? > 0
+                {
+                    changed.removed.push(path_s);
+                }
+                return Ok(());
+            }
+            // 文件在写库前又被删除，留给随后的删除事件处理
+            Err(e) if matches!(e.reason, IssueReason::NotFound | IssueReason::BrokenSymlink) => {
+                return Ok(())
+            }
+            // 写库失败时整批放回重试
+            Err(e) if matches!(e.reason, IssueReason::DbError) => return Err(e),
+            // 无法读取的文件与扫描时一样不出现在结果中
+            Err(_) => {}
+        }
+    }
+    if remove(conn, &ctx.project_id, path)? > 0 {
+        changed.removed.push(path_s);
+    }
+    Ok(())
+}
+
+/// 移除路径本身及其下的所有结果（retain 模式归档的条目及其元数据保留，与扫描一致），返回移除条数
+fn remove(conn: &Connection, project_id: &str, path: &Path) -> rusqlite::Result<usize> {
+    let path_s = path.to_string_lossy().to_string();
+    let prefix = format!("{}{}", path_s, std::path::MAIN_SEPARATOR);
+    conn.execute(
+        "DELETE FROM result_metadata WHERE project_id = ?1
+         AND (file_path = ?2 OR substr(file_path, 1, length(?3)) = ?3)
+         AND NOT EXISTS (
+            SELECT 1 FROM scan_results r WHERE r.project_id = ?1
+            AND r.file_path = result_metadata.file_path AND r.archived = 1
+         )",
+        [project_id, &path_s, &prefix],
+    )?;
+    conn.execute(
+        "DELETE FROM scan_results WHERE project_id = ?1 AND archived = 0
+         AND (file_path = ?2 OR substr(file_path, 1, length(?3)) = ?3)",
+        [project_id, &path_s, &prefix],
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::SystemTime;
+
+    use crate::{insert_project, DatabaseManager, ProjectInput};
+
+    fn archived(conn: &Connection, path: &Path) -> Option<i64> {
+        conn.query_row(
+            "SELECT archived FROM scan_results WHERE file_path = ?",
+            [path.to_string_lossy()],
+            |row| row.get(0),
+        )
+        .ok()
+    }
+
+    #[test]
+    fn retained_rows_survive_events() {
+        let dir = std::env::temp_dir().join(format!("flashback-watch-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        let docs = dir.join("docs");
+        std::fs::create_dir_all(&docs).unwrap();
+        let old = docs.join("old.md");
+        let new = docs.join("new.md");
+        std::fs::write(&old, "old").unwrap();
+        std::fs::write(&new, "new").unwrap();
+        std::fs::File::options()
+            .write(true)
+            .open(&old)
+            .unwrap()
+            .set_modified(SystemTime::now() - Duration::from_secs(30 * 86400))
+            .unwrap();
+
+        let db = DatabaseManager::at(dir.join("FlashBack").join("flashback.db"));
+        db.initialize().unwrap();
+        let mut conn = db.get_connection().unwrap();
+        let project = insert_project(
+            &conn,
+            ProjectInput {
+                name: "监听".into(),
+                time_range: "past_week".into(),
+                scan_scope: Some("CUSTOM".into()),
+                scan_folders: Some(vec![docs.to_string_lossy().to_string()]),
+                collectors: Some(vec!["documents".into()]),
+                window_mode: Some("retain".into()),
+            },
+        )
+        .unwrap();
+        let ctx = ScanContext::load(&conn, &project.id).unwrap();
+        // 上次扫描时在范围内、此后被 retain 模式归档的条目
+        conn.execute(
+            "INSERT INTO scan_results (id, project_id, file_path, file_type, source, created_at, modified_at, size_bytes, is_valid, inserted_at, updated_at, archived)
+             VALUES ('old', ?, ?, 'md', 'CUSTOM_SPECIFIED', '', '', 3, 1, '', '', 1)",
+            rusqlite::params![project.id, old.to_string_lossy()],
+        )
+        .unwrap();
+
+        let roots = vec![docs.clone()];
+        let batch: BTreeSet<PathBuf> = [old.clone(), new.clone()].into_iter().collect();
+        let changed = apply_changes(&mut conn, &ctx, &roots, &batch).unwrap();
+        assert_eq!(changed.upserted, vec![new.to_string_lossy().to_string()]);
+        assert!(changed.removed.is_empty());
+        assert_eq!(archived(&conn, &old), Some(1));
+
+        // 删除文件：未归档的条目移除，归档条目保留
+        std::fs::remove_file(&old).unwrap();
+        std::fs::remove_file(&new).unwrap();
+        let changed = apply_changes(&mut conn, &ctx, &roots, &batch).unwrap();
+        assert_eq!(changed.removed, vec![new.to_string_lossy().to_string()]);
+        assert_eq!(archived(&conn, &old), Some(1));
+        assert_eq!(archived(&conn, &new), None);
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn events_follow_walk_options() {
+        let dir = std::env::temp_dir().join(format!("flashback-watch-walk-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        let docs = dir.join("docs");
+        std::fs::create_dir_all(docs.join(".hidden")).unwrap();
+        std::fs::create_dir_all(docs.join("a/b")).unwrap();
+        let hidden = docs.join(".hidden/h.md");
+        let deep = docs.join("a/b/deep.md");
+        let shallow = docs.join("a/shallow.md");
+        for file in [&hidden, &deep, &shallow] {
+            std::fs::write(file, "x").unwrap();
+        }
+
+        let db = DatabaseManager::at(dir.join("FlashBack").join("flashback.db"));
+        db.initialize().unwrap();
+        let mut conn = db.get_connection().unwrap();
+        let project = insert_project(
+            &conn,
+            ProjectInput {
+                name: "遍历".into(),
+                time_range: "past_week".into(),
+                scan_scope: Some("CUSTOM".into()),
+                scan_folders: Some(vec![docs.to_string_lossy().to_string()]),
+                collectors: Some(vec!["documents".into()]),
+                window_mode: None,
+            },
+        )
+        .unwrap();
+        let mut ctx = ScanContext::load(&conn, &project.id).unwrap();
+        ctx.walk_options.skip_hidden = true;
+        ctx.walk_options.max_depth = Some(2);
+
+        let roots = vec![docs.clone()];
+        // 单个文件的事件
+        let files: BTreeSet<PathBuf> = [hidden.clone(), deep.clone(), shallow.clone()]
+            .into_iter()
+            .collect();
+        let changed = apply_changes(&mut conn, &ctx, &roots, &files).unwrap();
+        assert_eq!(
+            changed.upserted,
+            vec![shallow.to_string_lossy().to_string()]
+        );
+        // 目录的事件
+        let dirs: BTreeSet<PathBuf> = [docs.join(".hidden"), docs.join("a")].into_iter().collect();
+        let changed = apply_changes(&mut conn, &ctx, &roots, &dirs).unwrap();
+        assert_eq!(
+            changed.upserted,
+            vec![shallow.to_string_lossy().to_string()]
+        );
+        let n: i64 = conn
+            .query_row("SELECT COUNT(*) FROM scan_results", [], |row| row.get(0))
+            .unwrap();
+        assert_eq!(n, 1);
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}